@@ -0,0 +1,444 @@
+use std::sync::Arc;
+
+use arrow2::{
+    array::{
+        Array, BinaryArray, BooleanArray, DictionaryArray, ListArray, MapArray, NullArray,
+        PrimitiveArray, StructArray, Utf8Array,
+    },
+    buffer::Buffer,
+    datatypes::{DataType as ArrowDataType, Field, IntegerType, TimeUnit as ArrowTimeUnit},
+    types::{NativeType, Offset},
+};
+
+use crate::{
+    types::{decimal::MAX_DECIMAL_PRECISION, map::map_entries_type, variant, DataType},
+    util::constant_bitmap,
+    values::{Column, ColumnBuilder, ScalarRef},
+};
+
+/// The extension names of the types that arrow has no counterpart for.
+const EMPTY_ARRAY_EXTENSION: &str = "EmptyArray";
+const VARIANT_EXTENSION: &str = "Variant";
+
+impl DataType {
+    /// The arrow type of a column of this type. Arrow types carry no
+    /// nullability, so `Nullable<T>` maps to the type of `T`, and the nested
+    /// types record it in their fields.
+    pub fn to_arrow(&self) -> ArrowDataType {
+        match self {
+            DataType::Null => ArrowDataType::Null,
+            DataType::EmptyArray => ArrowDataType::Extension(
+                EMPTY_ARRAY_EXTENSION.to_string(),
+                Box::new(ArrowDataType::Null),
+                None,
+            ),
+            DataType::Boolean => ArrowDataType::Boolean,
+            DataType::String => ArrowDataType::LargeUtf8,
+            DataType::Binary => ArrowDataType::LargeBinary,
+            DataType::Variant => ArrowDataType::Extension(
+                VARIANT_EXTENSION.to_string(),
+                Box::new(ArrowDataType::LargeBinary),
+                None,
+            ),
+            DataType::UInt8 => ArrowDataType::UInt8,
+            DataType::UInt16 => ArrowDataType::UInt16,
+            DataType::UInt32 => ArrowDataType::UInt32,
+            DataType::UInt64 => ArrowDataType::UInt64,
+            DataType::Int8 => ArrowDataType::Int8,
+            DataType::Int16 => ArrowDataType::Int16,
+            DataType::Int32 => ArrowDataType::Int32,
+            DataType::Int64 => ArrowDataType::Int64,
+            DataType::Float32 => ArrowDataType::Float32,
+            DataType::Float64 => ArrowDataType::Float64,
+            DataType::Decimal { precision, scale } => {
+                ArrowDataType::Decimal(*precision as usize, *scale as usize)
+            }
+            DataType::Date => ArrowDataType::Date32,
+            DataType::Timestamp => ArrowDataType::Timestamp(ArrowTimeUnit::Microsecond, None),
+            DataType::Interval => ArrowDataType::Duration(ArrowTimeUnit::Microsecond),
+            DataType::Nullable(ty) => ty.to_arrow(),
+            DataType::Array(ty) => ArrowDataType::LargeList(Box::new(arrow_field("item", ty))),
+            DataType::Map(key, value) => ArrowDataType::Map(
+                Box::new(Field::new(
+                    "entries",
+                    ArrowDataType::Struct(vec![
+                        arrow_field("key", key),
+                        arrow_field("value", value),
+                    ]),
+                    false,
+                )),
+                false,
+            ),
+            DataType::Tuple {
+                fields_name,
+                fields_type,
+            } => ArrowDataType::Struct(
+                fields_name
+                    .iter()
+                    .zip(fields_type)
+                    .enumerate()
+                    .map(|(idx, (name, ty))| match name {
+                        Some(name) => arrow_field(name, ty),
+                        None => arrow_field(&idx.to_string(), ty),
+                    })
+                    .collect(),
+            ),
+            DataType::Generic(_) => unreachable!("generic type has no arrow type"),
+        }
+    }
+
+    /// The type of a non-nullable column of the arrow type.
+    pub fn from_arrow(ty: &ArrowDataType) -> Result<DataType, String> {
+        Ok(match ty {
+            ArrowDataType::Null => DataType::Null,
+            ArrowDataType::Extension(name, _, _) if name == EMPTY_ARRAY_EXTENSION => {
+                DataType::EmptyArray
+            }
+            ArrowDataType::Extension(name, _, _) if name == VARIANT_EXTENSION => DataType::Variant,
+            ArrowDataType::Boolean => DataType::Boolean,
+            ArrowDataType::Utf8 | ArrowDataType::LargeUtf8 => DataType::String,
+            ArrowDataType::Binary | ArrowDataType::LargeBinary => DataType::Binary,
+            ArrowDataType::UInt8 => DataType::UInt8,
+            ArrowDataType::UInt16 => DataType::UInt16,
+            ArrowDataType::UInt32 => DataType::UInt32,
+            ArrowDataType::UInt64 => DataType::UInt64,
+            ArrowDataType::Int8 => DataType::Int8,
+            ArrowDataType::Int16 => DataType::Int16,
+            ArrowDataType::Int32 => DataType::Int32,
+            ArrowDataType::Int64 => DataType::Int64,
+            ArrowDataType::Float32 => DataType::Float32,
+            ArrowDataType::Float64 => DataType::Float64,
+            ArrowDataType::Decimal(precision, scale) => {
+                if *precision == 0
+                    || *precision > MAX_DECIMAL_PRECISION as usize
+                    || scale > precision
+                {
+                    return Err(format!(
+                        "invalid decimal type Decimal({precision}, {scale})"
+                    ));
+                }
+                DataType::Decimal {
+                    precision: *precision as u8,
+                    scale: *scale as u8,
+                }
+            }
+            ArrowDataType::Date32 => DataType::Date,
+            ArrowDataType::Timestamp(ArrowTimeUnit::Microsecond, _) => DataType::Timestamp,
+            ArrowDataType::Duration(ArrowTimeUnit::Microsecond) => DataType::Interval,
+            ArrowDataType::List(field) | ArrowDataType::LargeList(field) => {
+                DataType::Array(Box::new(data_type_from_field(field)?))
+            }
+            ArrowDataType::Map(field, _) => match &field.data_type {
+                ArrowDataType::Struct(fields) if fields.len() == 2 => DataType::Map(
+                    Box::new(data_type_from_field(&fields[0])?),
+                    Box::new(data_type_from_field(&fields[1])?),
+                ),
+                _ => return Err(format!("unsupported arrow type {ty:?}")),
+            },
+            ArrowDataType::Struct(fields) => DataType::Tuple {
+                fields_name: fields
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, field)| (field.name != idx.to_string()).then(|| field.name.clone()))
+                    .collect(),
+                fields_type: fields
+                    .iter()
+                    .map(data_type_from_field)
+                    .collect::<Result<_, _>>()?,
+            },
+            ArrowDataType::Dictionary(_, ty, _) => DataType::from_arrow(ty)?,
+            ty => return Err(format!("unsupported arrow type {ty:?}")),
+        })
+    }
+}
+
+fn arrow_field(name: &str, ty: &DataType) -> Field {
+    Field::new(name, ty.to_arrow(), accepts_null(ty))
+}
+
+pub(crate) fn accepts_null(ty: &DataType) -> bool {
+    ty.is_null() || ty.as_nullable().is_some()
+}
+
+pub(crate) fn data_type_from_field(field: &Field) -> Result<DataType, String> {
+    let ty = DataType::from_arrow(&field.data_type)?;
+    if field.is_nullable && !accepts_null(&ty) {
+        Ok(DataType::Nullable(Box::new(ty)))
+    } else {
+        Ok(ty)
+    }
+}
+
+impl Column {
+    /// Converts the column into an arrow array, sharing the buffers. Only the
+    /// offsets are copied, since they are stored as `usize` here. Constant
+    /// columns are materialized, and tuple fields are named by their position.
+    pub fn to_arrow(&self) -> Box<dyn Array> {
+        let ty = || self.data_type().to_arrow();
+        match self {
+            Column::Null { len } | Column::EmptyArray { len } => {
+                Box::new(NullArray::new(ty(), *len))
+            }
+            Column::Int8(col) => Box::new(PrimitiveArray::new(ty(), col.clone(), None)),
+            Column::Int16(col) => Box::new(PrimitiveArray::new(ty(), col.clone(), None)),
+            Column::Int32(col) => Box::new(PrimitiveArray::new(ty(), col.clone(), None)),
+            Column::Int64(col) => Box::new(PrimitiveArray::new(ty(), col.clone(), None)),
+            Column::Date(col) => Box::new(PrimitiveArray::new(ty(), col.clone(), None)),
+            Column::Timestamp(col) => Box::new(PrimitiveArray::new(ty(), col.clone(), None)),
+            Column::Interval(col) => Box::new(PrimitiveArray::new(ty(), col.clone(), None)),
+            Column::Float32(col) => Box::new(PrimitiveArray::new(ty(), col.clone(), None)),
+            Column::Float64(col) => Box::new(PrimitiveArray::new(ty(), col.clone(), None)),
+            Column::Decimal { values, .. } => {
+                Box::new(PrimitiveArray::new(ty(), values.clone(), None))
+            }
+            Column::UInt8(col) => Box::new(PrimitiveArray::new(ty(), col.clone(), None)),
+            Column::UInt16(col) => Box::new(PrimitiveArray::new(ty(), col.clone(), None)),
+            Column::UInt32(col) => Box::new(PrimitiveArray::new(ty(), col.clone(), None)),
+            Column::UInt64(col) => Box::new(PrimitiveArray::new(ty(), col.clone(), None)),
+            Column::Boolean(col) => Box::new(BooleanArray::new(ty(), col.clone(), None)),
+            Column::String { data, offsets } => Box::new(Utf8Array::<i64>::new(
+                ty(),
+                offsets_to_arrow(offsets),
+                data.clone(),
+                None,
+            )),
+            Column::Binary { data, offsets } | Column::Variant { data, offsets } => Box::new(
+                BinaryArray::<i64>::new(ty(), offsets_to_arrow(offsets), data.clone(), None),
+            ),
+            Column::Array { array, offsets } => {
+                let values = array.to_arrow();
+                let field = Field::new(
+                    "item",
+                    values.data_type().clone(),
+                    accepts_null(&array.data_type()),
+                );
+                Box::new(ListArray::<i64>::new(
+                    ArrowDataType::LargeList(Box::new(field)),
+                    offsets_to_arrow(offsets),
+                    values.into(),
+                    None,
+                ))
+            }
+            Column::Nullable { column, validity } => {
+                column.to_arrow().with_validity(Some(validity.clone()))
+            }
+            Column::Tuple { fields, .. } => {
+                let values = fields
+                    .iter()
+                    .map(|field| Arc::from(field.to_arrow()))
+                    .collect::<Vec<Arc<dyn Array>>>();
+                let arrow_fields = fields
+                    .iter()
+                    .zip(&values)
+                    .enumerate()
+                    .map(|(idx, (field, values))| {
+                        Field::new(
+                            idx.to_string(),
+                            values.data_type().clone(),
+                            accepts_null(&field.data_type()),
+                        )
+                    })
+                    .collect();
+                Box::new(StructArray::new(
+                    ArrowDataType::Struct(arrow_fields),
+                    values,
+                    None,
+                ))
+            }
+            Column::Dictionary { keys, values } => {
+                let keys = PrimitiveArray::new(ArrowDataType::UInt32, keys.clone(), None);
+                Box::new(DictionaryArray::<u32>::from_data(
+                    keys,
+                    values.to_arrow().into(),
+                ))
+            }
+            Column::Const { .. } => self.flatten().to_arrow(),
+        }
+    }
+
+    /// Converts an arrow array into a column of the data type, sharing the
+    /// buffers. Only the offsets are copied, since they are stored as `usize`
+    /// here.
+    pub fn from_arrow(array: &dyn Array, ty: &DataType) -> Result<Column, String> {
+        if let ArrowDataType::Dictionary(key_type, _, _) = array.data_type() {
+            if *key_type != IntegerType::UInt32 {
+                return Err(format!("unsupported dictionary key type {key_type:?}"));
+            }
+            let array = downcast::<DictionaryArray<u32>>(array, ty)?;
+            if array.keys().null_count() > 0 {
+                return Err("dictionary keys can not be null".to_string());
+            }
+            let values = Column::from_arrow(array.values().as_ref(), ty)?;
+            let keys = array.keys().values().clone();
+            if let Some(key) = keys.iter().find(|key| **key as usize >= values.len()) {
+                return Err(format!(
+                    "key {key} is out of bounds for {} values",
+                    values.len()
+                ));
+            }
+            return Ok(Column::Dictionary {
+                keys,
+                values: Box::new(values),
+            });
+        }
+
+        match ty {
+            DataType::Nullable(_) if array.data_type() == &ArrowDataType::Null => {
+                let mut builder = ColumnBuilder::with_capacity(ty, array.len());
+                for _ in 0..array.len() {
+                    builder.push(ScalarRef::Null);
+                }
+                Ok(builder.build())
+            }
+            DataType::Nullable(inner) => Ok(Column::Nullable {
+                column: Box::new(from_arrow_values(array, inner)?),
+                validity: match array.validity() {
+                    Some(validity) => validity.clone(),
+                    None => constant_bitmap(true, array.len()).into(),
+                },
+            }),
+            DataType::Null => from_arrow_values(array, ty),
+            ty if array.null_count() > 0 => Err(format!(
+                "arrow array has nulls, but the data type {ty} is not nullable"
+            )),
+            ty => from_arrow_values(array, ty),
+        }
+    }
+}
+
+/// Converts the values of an arrow array, regardless of its validity.
+fn from_arrow_values(array: &dyn Array, ty: &DataType) -> Result<Column, String> {
+    Ok(match ty {
+        DataType::Null => Column::Null { len: array.len() },
+        DataType::EmptyArray => Column::EmptyArray { len: array.len() },
+        DataType::Boolean => Column::Boolean(downcast::<BooleanArray>(array, ty)?.values().clone()),
+        DataType::String => {
+            let (data, offsets) = match array.data_type().to_logical_type() {
+                ArrowDataType::Utf8 => {
+                    let array = downcast::<Utf8Array<i32>>(array, ty)?;
+                    (array.values().clone(), offsets_from_arrow(array.offsets()))
+                }
+                _ => {
+                    let array = downcast::<Utf8Array<i64>>(array, ty)?;
+                    (array.values().clone(), offsets_from_arrow(array.offsets()))
+                }
+            };
+            Column::String { data, offsets }
+        }
+        DataType::Binary | DataType::Variant => {
+            let (data, offsets) = match array.data_type().to_logical_type() {
+                ArrowDataType::Binary => {
+                    let array = downcast::<BinaryArray<i32>>(array, ty)?;
+                    (array.values().clone(), offsets_from_arrow(array.offsets()))
+                }
+                _ => {
+                    let array = downcast::<BinaryArray<i64>>(array, ty)?;
+                    (array.values().clone(), offsets_from_arrow(array.offsets()))
+                }
+            };
+            match ty {
+                DataType::Binary => Column::Binary { data, offsets },
+                _ => {
+                    // The values under nulls may be left empty by the writer.
+                    for (row, window) in offsets.windows(2).enumerate() {
+                        if array.is_valid(row) {
+                            variant::validate(&data[window[0]..window[1]])
+                                .map_err(|err| format!("{err} at row {row}"))?;
+                        }
+                    }
+                    Column::Variant { data, offsets }
+                }
+            }
+        }
+        DataType::UInt8 => Column::UInt8(primitive_values(array, ty)?),
+        DataType::UInt16 => Column::UInt16(primitive_values(array, ty)?),
+        DataType::UInt32 => Column::UInt32(primitive_values(array, ty)?),
+        DataType::UInt64 => Column::UInt64(primitive_values(array, ty)?),
+        DataType::Int8 => Column::Int8(primitive_values(array, ty)?),
+        DataType::Int16 => Column::Int16(primitive_values(array, ty)?),
+        DataType::Int32 => Column::Int32(primitive_values(array, ty)?),
+        DataType::Int64 => Column::Int64(primitive_values(array, ty)?),
+        DataType::Float32 => Column::Float32(primitive_values(array, ty)?),
+        DataType::Float64 => Column::Float64(primitive_values(array, ty)?),
+        DataType::Decimal { precision, scale } => Column::Decimal {
+            values: primitive_values(array, ty)?,
+            precision: *precision,
+            scale: *scale,
+        },
+        DataType::Date => Column::Date(primitive_values(array, ty)?),
+        DataType::Timestamp => Column::Timestamp(primitive_values(array, ty)?),
+        DataType::Interval => Column::Interval(primitive_values(array, ty)?),
+        DataType::Nullable(_) => Column::from_arrow(array, ty)?,
+        DataType::Array(item) => {
+            let (values, offsets) = match array.data_type().to_logical_type() {
+                ArrowDataType::List(_) => {
+                    let array = downcast::<ListArray<i32>>(array, ty)?;
+                    (array.values(), offsets_from_arrow(array.offsets()))
+                }
+                _ => {
+                    let array = downcast::<ListArray<i64>>(array, ty)?;
+                    (array.values(), offsets_from_arrow(array.offsets()))
+                }
+            };
+            Column::Array {
+                array: Box::new(Column::from_arrow(values.as_ref(), item)?),
+                offsets,
+            }
+        }
+        DataType::Map(key, value) => match array.data_type().to_logical_type() {
+            ArrowDataType::Map(_, _) => {
+                let array = downcast::<MapArray>(array, ty)?;
+                let entries = map_entries_type(key, value);
+                let entry = entries.as_array().unwrap();
+                Column::Array {
+                    array: Box::new(Column::from_arrow(array.field().as_ref(), entry)?),
+                    offsets: offsets_from_arrow(array.offsets()),
+                }
+            }
+            _ => from_arrow_values(array, &map_entries_type(key, value))?,
+        },
+        DataType::Tuple { fields_type, .. } => {
+            let array = downcast::<StructArray>(array, ty)?;
+            if array.values().len() != fields_type.len() {
+                return Err(format!(
+                    "arrow struct has {} fields, but the data type {ty} has {}",
+                    array.values().len(),
+                    fields_type.len()
+                ));
+            }
+            Column::Tuple {
+                fields: array
+                    .values()
+                    .iter()
+                    .zip(fields_type)
+                    .map(|(values, ty)| Column::from_arrow(values.as_ref(), ty))
+                    .collect::<Result<_, _>>()?,
+                len: array.len(),
+            }
+        }
+        DataType::Generic(_) => unreachable!("generic type has no arrow type"),
+    })
+}
+
+fn downcast<'a, T: 'static>(array: &'a dyn Array, ty: &DataType) -> Result<&'a T, String> {
+    array
+        .as_any()
+        .downcast_ref::<T>()
+        .ok_or_else(|| format!("can not convert arrow type {:?} to {ty}", array.data_type()))
+}
+
+fn primitive_values<T: NativeType>(array: &dyn Array, ty: &DataType) -> Result<Buffer<T>, String> {
+    Ok(downcast::<PrimitiveArray<T>>(array, ty)?.values().clone())
+}
+
+fn offsets_to_arrow(offsets: &[usize]) -> Buffer<i64> {
+    offsets
+        .iter()
+        .map(|offset| *offset as i64)
+        .collect::<Vec<_>>()
+        .into()
+}
+
+fn offsets_from_arrow<O: Offset>(offsets: &Buffer<O>) -> Vec<usize> {
+    offsets.iter().map(|offset| offset.to_usize()).collect()
+}
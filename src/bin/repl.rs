@@ -0,0 +1,440 @@
+//! An interactive shell that type checks and evaluates expressions against
+//! columns defined in the session. Type `:help` for the commands.
+
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+use typed_type_exercise::builtins::builtin_functions;
+use typed_type_exercise::display::display_span;
+use typed_type_exercise::expr::{Expr, Literal, Span, AST};
+use typed_type_exercise::function::FunctionRegistry;
+use typed_type_exercise::parser::{parse, parse_data_type};
+use typed_type_exercise::property::ValueProperty;
+use typed_type_exercise::runtime::Runtime;
+use typed_type_exercise::type_check::{self, can_cast_explicitly};
+use typed_type_exercise::types::{AnyType, DataType};
+use typed_type_exercise::values::{Column, ColumnBuilder, Scalar, ScalarRef, Value};
+
+const HELP: &str = "\
+<expr>                 type check and evaluate an expression
+:let <name> = <expr>   define a column from an array or from an expression of other columns
+:load <path>           define the columns of a CSV file, whose header names them as `name::Type`
+:columns               list the columns
+:functions [<name>]    list the functions and their signatures
+:casts                 toggle the display of the casts inserted by the type checker
+:help                  show this message
+:quit                  exit";
+
+pub fn main() {
+    let mut session = Session::new();
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    let mut line = String::new();
+    loop {
+        write!(stdout, "> ").unwrap();
+        stdout.flush().unwrap();
+        line.clear();
+        if stdin.lock().read_line(&mut line).unwrap() == 0 {
+            writeln!(stdout).unwrap();
+            break;
+        }
+        if !session.run_line(line.trim(), &mut stdout) {
+            break;
+        }
+    }
+}
+
+struct Session {
+    registry: FunctionRegistry,
+    columns: HashMap<String, Column>,
+    show_casts: bool,
+}
+
+impl Session {
+    fn new() -> Self {
+        Session {
+            registry: builtin_functions(),
+            columns: HashMap::new(),
+            show_casts: true,
+        }
+    }
+
+    /// Runs a line of input, returning false if the session should end.
+    fn run_line(&mut self, line: &str, output: &mut impl Write) -> bool {
+        let (command, rest) = match line.strip_prefix(':') {
+            Some(command) => command.split_once(' ').unwrap_or((command, "")),
+            None => {
+                if !line.is_empty() {
+                    self.run_expr(line, output);
+                }
+                return true;
+            }
+        };
+        let rest = rest.trim();
+        match command {
+            "let" => match rest.split_once('=') {
+                Some((name, text)) => {
+                    let result = self.eval_column(text.trim(), output);
+                    if let Some(column) = result {
+                        self.define_column(name.trim(), column, output);
+                    }
+                }
+                None => writeln!(output, "usage: :let <name> = <expr>").unwrap(),
+            },
+            "load" => match load_csv(rest, &self.registry) {
+                Ok(columns) => {
+                    for (name, column) in columns {
+                        self.define_column(&name, column, output);
+                    }
+                }
+                Err(err) => writeln!(output, "error: {err}").unwrap(),
+            },
+            "columns" => {
+                let mut names = self.columns.keys().collect::<Vec<_>>();
+                names.sort();
+                for name in names {
+                    let column = &self.columns[name];
+                    writeln!(
+                        output,
+                        "{name}: {} ({} rows)",
+                        column.data_type(),
+                        column.len()
+                    )
+                    .unwrap();
+                }
+            }
+            "functions" => self.list_functions(rest, output),
+            "casts" => {
+                self.show_casts = !self.show_casts;
+                let state = if self.show_casts { "shown" } else { "hidden" };
+                writeln!(output, "inserted casts are {state}").unwrap();
+            }
+            "help" => writeln!(output, "{HELP}").unwrap(),
+            "quit" | "q" => return false,
+            _ => writeln!(output, "unknown command `:{command}`, see `:help`").unwrap(),
+        }
+        true
+    }
+
+    fn schema(&self) -> HashMap<String, DataType> {
+        self.columns
+            .iter()
+            .map(|(name, column)| (name.clone(), column.data_type()))
+            .collect()
+    }
+
+    /// Parses and type checks an expression, printing the error if it fails.
+    fn check(
+        &self,
+        text: &str,
+        output: &mut impl Write,
+    ) -> Option<(AST, Expr, DataType, ValueProperty)> {
+        let ast = match parse(text, &self.schema()) {
+            Ok(ast) => ast,
+            Err(err) => {
+                write_error(output, text, &err.to_string(), Some(err.span));
+                return None;
+            }
+        };
+        match type_check::check(&ast, &self.registry) {
+            Ok((expr, ty, prop)) => Some((ast, expr, ty, prop)),
+            Err(err) => {
+                write_error(output, text, &err.to_string(), err.ast().span());
+                None
+            }
+        }
+    }
+
+    fn eval(&self, text: &str, expr: &Expr, output: &mut impl Write) -> Option<Value<AnyType>> {
+        let runtime = Runtime {
+            columns: self.columns.clone(),
+            selection: None,
+        };
+        match runtime.run(expr) {
+            Ok(result) => Some(result),
+            Err(err) => {
+                let span = err.span.clone();
+                write_error(output, text, &err.to_string(), span);
+                None
+            }
+        }
+    }
+
+    fn run_expr(&self, text: &str, output: &mut impl Write) {
+        let (ast, expr, ty, prop) = match self.check(text, output) {
+            Some(checked) => checked,
+            None => return,
+        };
+        writeln!(output, "ast: {ast}").unwrap();
+        if self.show_casts {
+            writeln!(output, "expr: {expr}").unwrap();
+        } else {
+            writeln!(output, "expr: {}", expr.strip_inserted_casts()).unwrap();
+        }
+        writeln!(output, "type: {ty}").unwrap();
+        writeln!(output, "property: {prop}").unwrap();
+        if let Some(result) = self.eval(text, &expr, output) {
+            writeln!(output, "result: {result}").unwrap();
+        }
+    }
+
+    /// Evaluates an expression into a column. An array scalar, such as
+    /// `[1, 2, 3]`, becomes the column of its items.
+    fn eval_column(&self, text: &str, output: &mut impl Write) -> Option<Column> {
+        let (_, expr, _, _) = self.check(text, output)?;
+        match self.eval(text, &expr, output)? {
+            Value::Column(column) => Some(column),
+            Value::Scalar(Scalar::Array(column)) => Some(column),
+            Value::Scalar(Scalar::EmptyArray) => Some(Column::EmptyArray { len: 0 }),
+            result => {
+                writeln!(
+                    output,
+                    "error: expected an array or a column, but got {result}"
+                )
+                .unwrap();
+                None
+            }
+        }
+    }
+
+    fn define_column(&mut self, name: &str, column: Column, output: &mut impl Write) {
+        let len = self
+            .columns
+            .iter()
+            .find(|(other, _)| other.as_str() != name)
+            .map(|(_, other)| other.len());
+        match len {
+            Some(len) if len != column.len() => writeln!(
+                output,
+                "error: column `{name}` has {} rows, but the other columns have {len}",
+                column.len()
+            )
+            .unwrap(),
+            _ => {
+                writeln!(output, "{name}: {}", column.data_type()).unwrap();
+                self.columns.insert(name.to_string(), column);
+            }
+        }
+    }
+
+    fn list_functions(&self, name: &str, output: &mut impl Write) {
+        let mut names = self
+            .registry
+            .funcs
+            .keys()
+            .chain(self.registry.factories.keys())
+            .filter(|func| name.is_empty() || **func == name)
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        if names.is_empty() {
+            writeln!(output, "function `{name}` does not exist").unwrap();
+        }
+        for func in names {
+            for function in self.registry.funcs.get(func).into_iter().flatten() {
+                writeln!(output, "{}", function.signature).unwrap();
+            }
+            if self.registry.factories.contains_key(func) {
+                writeln!(output, "{func}(..) :: depends on the arguments").unwrap();
+            }
+        }
+    }
+}
+
+fn write_error(output: &mut impl Write, text: &str, message: &str, span: Span) {
+    writeln!(output, "error: {message}").unwrap();
+    if let Some(span) = span {
+        writeln!(output, "{}", display_span(text, span)).unwrap();
+    }
+}
+
+/// Reads a CSV file into columns. The header names each column as `name` or
+/// `name::Type`, where the type defaults to `String`. A value is cast from its
+/// text if the type allows it, such as a date, and is otherwise read as a
+/// literal of the type, such as `1.5` or `[1, 2]`. An empty unquoted value is
+/// `NULL`.
+fn load_csv(path: &str, registry: &FunctionRegistry) -> Result<Vec<(String, Column)>, String> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+    let mut lines = text.lines().filter(|line| !line.is_empty());
+    let header = split_csv_line(lines.next().ok_or_else(|| format!("{path}: empty file"))?)?;
+    let rows = lines.map(split_csv_line).collect::<Result<Vec<_>, _>>()?;
+
+    header
+        .into_iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let field = field.unwrap_or_default();
+            let (name, ty) = match field.split_once("::") {
+                Some((name, ty)) => (name, parse_data_type(ty).map_err(|err| err.to_string())?),
+                None => (field.as_str(), DataType::String),
+            };
+            let values = rows
+                .iter()
+                .enumerate()
+                .map(|(row, values)| {
+                    values
+                        .get(idx)
+                        .ok_or_else(|| format!("{path}: row {} has no column `{name}`", row + 1))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let column = parse_csv_column(&values, &ty, registry)
+                .map_err(|err| format!("{path}: column `{name}`: {err}"))?;
+            Ok((name.trim().to_string(), column))
+        })
+        .collect()
+}
+
+fn parse_csv_column(
+    values: &[&Option<String>],
+    ty: &DataType,
+    registry: &FunctionRegistry,
+) -> Result<Column, String> {
+    let inner_ty = ty.as_nullable().map(|ty| &**ty).unwrap_or(ty);
+    let mut builder = ColumnBuilder::with_capacity(ty, values.len());
+    for value in values {
+        let value = match value {
+            Some(value) => value,
+            None if ty.is_null() || ty.as_nullable().is_some() => {
+                builder.push(ScalarRef::Null);
+                continue;
+            }
+            None => return Err(format!("NULL in a column of type {ty}")),
+        };
+        let ast = if can_cast_explicitly(&DataType::String, inner_ty) {
+            AST::Cast {
+                span: None,
+                expr: Box::new(AST::Literal {
+                    span: None,
+                    lit: Literal::String(value.clone()),
+                }),
+                dest_type: inner_ty.clone(),
+            }
+        } else {
+            parse(&format!("{value}::{inner_ty}"), &HashMap::new())
+                .map_err(|err| format!("invalid value `{value}`: {}", err.message))?
+        };
+        let (expr, _, _) = type_check::check(&ast, registry).map_err(|err| err.to_string())?;
+        let runtime = Runtime {
+            columns: HashMap::new(),
+            selection: None,
+        };
+        match runtime.run(&expr).map_err(|err| err.to_string())? {
+            Value::Scalar(scalar) => builder.push(scalar.as_ref()),
+            Value::Column(_) => unreachable!("a literal evaluated into a column"),
+        }
+    }
+    Ok(builder.build())
+}
+
+/// Splits a line of CSV into its values. A value may be quoted, in which
+/// case a quote inside it is escaped by doubling it.
+fn split_csv_line(line: &str) -> Result<Vec<Option<String>>, String> {
+    let mut values = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        let value = if chars.next_if_eq(&'"').is_some() {
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') if chars.next_if_eq(&'"').is_some() => value.push('"'),
+                    Some('"') => break,
+                    Some(c) => value.push(c),
+                    None => return Err(format!("unterminated quote in `{line}`")),
+                }
+            }
+            Some(value)
+        } else {
+            let mut value = String::new();
+            while let Some(c) = chars.next_if(|c| *c != ',') {
+                value.push(c);
+            }
+            (!value.is_empty()).then_some(value)
+        };
+        values.push(value);
+        match chars.next() {
+            Some(',') => continue,
+            None => return Ok(values),
+            Some(c) => return Err(format!("unexpected `{c}` after a quoted value in `{line}`")),
+        }
+    }
+}
+
+#[test]
+pub fn test_repl() {
+    let dir = std::env::temp_dir().join(format!("repl-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let csv = dir.join("data.csv");
+    std::fs::write(
+        &csv,
+        "a::Int16,s::Nullable<String>,d::Nullable<Date>\n1,\"x,\"\"y\",2022-01-02\n2,,\n",
+    )
+    .unwrap();
+
+    let script = [
+        format!(":load {}", csv.display()),
+        ":let b = [10, NULL]".to_string(),
+        ":let c = [1, 2, 3]".to_string(),
+        ":columns".to_string(),
+        "a + b".to_string(),
+        ":casts".to_string(),
+        "a + b".to_string(),
+        "a + c".to_string(),
+        "plus(a,".to_string(),
+        ":functions upper".to_string(),
+    ];
+    let mut session = Session::new();
+    let mut output = Vec::new();
+    for line in &script {
+        writeln!(output, "> {line}").unwrap();
+        assert!(session.run_line(line, &mut output));
+    }
+    assert!(!session.run_line(":quit", &mut output));
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    let expected = format!(
+        "\
+> :load {}
+a: Int16
+s: Nullable<String>
+d: Nullable<Date>
+> :let b = [10, NULL]
+b: Nullable<UInt8>
+> :let c = [1, 2, 3]
+error: column `c` has 3 rows, but the other columns have 2
+> :columns
+a: Int16 (2 rows)
+b: Nullable<UInt8> (2 rows)
+d: Nullable<Date> (2 rows)
+s: Nullable<String> (2 rows)
+> a + b
+ast: plus(a::Int16{{not_null}}, b::Nullable<UInt8>{{}})
+expr: plus<Nullable<Int16>, Nullable<UInt8>>(cast<dest_type=Nullable<Int16>>(a){{not_null}}, b{{}})
+type: Nullable<Int32>
+property: {{}}
+result: Nullable {{ column: Int32([11, 0]), validity: [0b______01] }}
+> :casts
+inserted casts are hidden
+> a + b
+ast: plus(a::Int16{{not_null}}, b::Nullable<UInt8>{{}})
+expr: plus<Nullable<Int16>, Nullable<UInt8>>(a{{not_null}}, b{{}})
+type: Nullable<Int32>
+property: {{}}
+result: Nullable {{ column: Int32([11, 0]), validity: [0b______01] }}
+> a + c
+error: column `c` does not exist at 4..5
+1 | a + c
+  |     ^
+> plus(a,
+error: unexpected end of the expression at 7..7
+1 | plus(a,
+  |        ^
+> :functions upper
+upper(Nullable<Nothing>) :: Nullable<Nothing>
+upper(String) :: String
+upper(Nullable<String>) :: Nullable<String>
+",
+        csv.display()
+    );
+    assert_eq!(output, expected);
+}
@@ -0,0 +1,596 @@
+use std::iter::once;
+use std::sync::Arc;
+
+use arrow2::types::NativeType;
+use chrono::{Datelike, Timelike};
+
+use crate::function::FunctionRegistry;
+use crate::function::{
+    downcast_arg, vectorize_1_arg, vectorize_2_arg, vectorize_with_writer_2_arg, ArithmeticKernel,
+    Function, FunctionSignature,
+};
+use crate::property::FunctionProperty;
+use crate::runtime::EvalError;
+use crate::types::decimal::DecimalOp;
+use crate::types::number::CheckedArithmetic;
+use crate::types::temporal::{
+    date_to_naive, timestamp_to_naive, trunc_date, trunc_timestamp, DateType, IntervalType,
+    TimeUnit, TimestampType, MICROS_PER_DAY,
+};
+use crate::types::variant::{
+    as_bool, as_f64, as_i64, as_str, as_u64, get_path, parse_path, VariantType,
+};
+use crate::types::DataType;
+use crate::types::*;
+use crate::types::{ArgType, ArrayType};
+use crate::values::{Column, ColumnBuilder, ValueRef};
+use crate::values::{Scalar, ScalarRef, Value};
+
+struct Plus;
+
+impl ArithmeticKernel for Plus {
+    fn eval<T: NativeType + CheckedArithmetic>(lhs: T, rhs: T) -> Result<T, String> {
+        lhs.checked_add(rhs)
+            .ok_or_else(|| "integer overflow".to_string())
+    }
+}
+
+struct Minus;
+
+impl ArithmeticKernel for Minus {
+    const SIGNED_OUTPUT: bool = true;
+
+    fn eval<T: NativeType + CheckedArithmetic>(lhs: T, rhs: T) -> Result<T, String> {
+        lhs.checked_sub(rhs)
+            .ok_or_else(|| "integer overflow".to_string())
+    }
+}
+
+struct Multiply;
+
+impl ArithmeticKernel for Multiply {
+    fn eval<T: NativeType + CheckedArithmetic>(lhs: T, rhs: T) -> Result<T, String> {
+        lhs.checked_mul(rhs)
+            .ok_or_else(|| "integer overflow".to_string())
+    }
+}
+
+/// Integer division truncates toward zero, and dividing an integer by zero is
+/// an error, as it is for decimals. Float division follows IEEE 754, so that
+/// `1.0 / 0.0` is `inf` and `0.0 / 0.0` is `NaN`.
+struct Divide;
+
+impl ArithmeticKernel for Divide {
+    fn eval<T: NativeType + CheckedArithmetic>(lhs: T, rhs: T) -> Result<T, String> {
+        match lhs.checked_div(rhs) {
+            Some(value) => Ok(value),
+            None if rhs.is_zero() => Err("division by zero".to_string()),
+            None => Err("integer overflow".to_string()),
+        }
+    }
+}
+
+pub fn builtin_functions() -> FunctionRegistry {
+    let mut registry = FunctionRegistry::default();
+
+    registry.register_2_arg::<BooleanType, BooleanType, BooleanType, _>(
+        "and",
+        FunctionProperty::default(),
+        |lhs, rhs| lhs && rhs,
+    );
+
+    registry.register_arithmetic_2_arg::<Plus>("plus", FunctionProperty::default());
+    registry.register_arithmetic_2_arg::<Minus>("minus", FunctionProperty::default());
+    registry.register_arithmetic_2_arg::<Multiply>("multiply", FunctionProperty::default());
+    registry.register_arithmetic_2_arg::<Divide>("divide", FunctionProperty::default());
+
+    registry.register_decimal_arithmetic("plus", DecimalOp::Plus);
+    registry.register_decimal_arithmetic("minus", DecimalOp::Minus);
+    registry.register_decimal_arithmetic("multiply", DecimalOp::Multiply);
+    registry.register_decimal_arithmetic("divide", DecimalOp::Divide);
+
+    register_temporal_functions(&mut registry);
+    register_string_functions(&mut registry);
+    register_map_functions(&mut registry);
+    register_variant_functions(&mut registry);
+
+    registry.register_1_arg::<BooleanType, BooleanType, _>(
+        "not",
+        FunctionProperty::default(),
+        |val| !val,
+    );
+
+    registry.register_function_factory("least", |_, args_type| {
+        Some(Arc::new(Function {
+            signature: FunctionSignature {
+                name: "least",
+                args_type: vec![DataType::Int16; args_type.len()],
+                return_type: DataType::Int16,
+                property: FunctionProperty::default().preserve_not_null(true),
+            },
+            eval: Box::new(|args, ctx| {
+                if args.is_empty() {
+                    Ok(Value::Scalar(Scalar::Int16(0)))
+                } else if args.len() == 1 {
+                    Ok(args[0].clone().to_owned())
+                } else {
+                    let mut min: Value<NumberType<i16>> = vectorize_2_arg(
+                        downcast_arg::<NumberType<i16>>(&args[0])?,
+                        downcast_arg::<NumberType<i16>>(&args[1])?,
+                        ctx,
+                        |lhs, rhs| lhs.min(rhs),
+                    );
+                    for arg in &args[2..] {
+                        min = vectorize_2_arg(
+                            min.as_ref(),
+                            downcast_arg::<NumberType<i16>>(arg)?,
+                            ctx,
+                            |lhs, rhs| lhs.min(rhs),
+                        );
+                    }
+                    Ok(NumberType::<i16>::upcast_value(min))
+                }
+            }),
+        }))
+    });
+
+    registry.register_0_arg_core::<EmptyArrayType, _>(
+        "create_array",
+        FunctionProperty::default(),
+        |_| Ok(Value::Scalar(())),
+    );
+
+    registry.register_function_factory("create_array", |_, args_type| {
+        Some(Arc::new(Function {
+            signature: FunctionSignature {
+                name: "create_array",
+                args_type: vec![DataType::Generic(0); args_type.len()],
+                return_type: DataType::Array(Box::new(DataType::Generic(0))),
+                property: FunctionProperty::default().preserve_not_null(true),
+            },
+            eval: Box::new(|args, ctx| {
+                let len = args.iter().find_map(|arg| match arg {
+                    ValueRef::Column(col) => Some(col.len()),
+                    _ => None,
+                });
+                if let Some(len) = len {
+                    let mut array_builder = ColumnBuilder::with_capacity(&ctx.generics[0], 0);
+                    for idx in 0..len {
+                        for arg in args {
+                            match arg {
+                                ValueRef::Scalar(scalar) => {
+                                    array_builder.push(scalar.as_ref());
+                                }
+                                ValueRef::Column(col) => {
+                                    array_builder.push(col.index(idx));
+                                }
+                            }
+                        }
+                    }
+                    let offsets = once(0)
+                        .chain((0..len).map(|row| args.len() * (row + 1)))
+                        .collect();
+                    Ok(Value::Column(Column::Array {
+                        array: Box::new(array_builder.build()),
+                        offsets,
+                    }))
+                } else {
+                    // All args are scalars, so we return a scalar as result
+                    let mut array = ColumnBuilder::with_capacity(&ctx.generics[0], 0);
+                    for arg in args {
+                        match arg {
+                            ValueRef::Scalar(scalar) => {
+                                array.push(scalar.as_ref());
+                            }
+                            ValueRef::Column(_) => unreachable!(),
+                        }
+                    }
+                    Ok(Value::Scalar(Scalar::Array(array.build())))
+                }
+            }),
+        }))
+    });
+
+    registry.register_with_writer_2_arg::<ArrayType<GenericType<0>>, NumberType<i16>, GenericType<0>, _>(
+        "get",
+        FunctionProperty::default(),
+        |array, idx, output| {
+            if idx < 0 || idx as usize >= array.len() {
+                return Err(format!(
+                    "index {idx} is out of bounds for array of length {}",
+                    array.len()
+                ));
+            }
+            output.push(array.index(idx as usize));
+            Ok(())
+        },
+    );
+
+    registry.register_function_factory("create_tuple", |_, args_type| {
+        Some(Arc::new(Function {
+            signature: FunctionSignature {
+                name: "create_tuple",
+                args_type: args_type.to_vec(),
+                return_type: DataType::Tuple {
+                    fields_name: vec![None; args_type.len()],
+                    fields_type: args_type.to_vec(),
+                },
+                property: FunctionProperty::default().preserve_not_null(true),
+            },
+            eval: Box::new(move |args, _ctx| {
+                let len = args.iter().find_map(|arg| match arg {
+                    ValueRef::Column(col) => Some(col.len()),
+                    _ => None,
+                });
+                if let Some(len) = len {
+                    let fields = args
+                        .iter()
+                        .map(|arg| match arg {
+                            ValueRef::Scalar(scalar) => Column::Const {
+                                scalar: Box::new((*scalar).to_owned()),
+                                len,
+                            },
+                            ValueRef::Column(col) => col.clone(),
+                        })
+                        .collect();
+                    Ok(Value::Column(Column::Tuple { fields, len }))
+                } else {
+                    // All args are scalars, so we return a scalar as result
+                    let fields = args
+                        .iter()
+                        .map(|arg| match arg {
+                            ValueRef::Scalar(scalar) => (*scalar).to_owned(),
+                            ValueRef::Column(_) => unreachable!(),
+                        })
+                        .collect();
+                    Ok(Value::Scalar(Scalar::Tuple(fields)))
+                }
+            }),
+        }))
+    });
+
+    registry.register_function_factory("get_tuple", |params, args_type| {
+        let idx = *params.first()?;
+        let tuple_tys = match args_type.first() {
+            Some(DataType::Tuple { fields_type, .. }) => fields_type,
+            _ => return None,
+        };
+        if idx >= tuple_tys.len() {
+            return None;
+        }
+
+        Some(Arc::new(Function {
+            signature: FunctionSignature {
+                name: "get_tuple",
+                args_type: vec![args_type[0].clone()],
+                return_type: tuple_tys[idx].clone(),
+                property: FunctionProperty::default().preserve_not_null(true),
+            },
+            eval: Box::new(move |args, _| match &args[0] {
+                ValueRef::Scalar(Scalar::Tuple(fields)) => {
+                    Ok(Value::Scalar(fields[idx].to_owned()))
+                }
+                ValueRef::Column(Column::Const {
+                    scalar: box Scalar::Tuple(fields),
+                    len,
+                }) => Ok(Value::Column(Column::Const {
+                    scalar: Box::new(fields[idx].to_owned()),
+                    len: *len,
+                })),
+                ValueRef::Column(col) => match &*col.flatten() {
+                    Column::Tuple { fields, .. } => Ok(Value::Column(fields[idx].to_owned())),
+                    _ => Err(EvalError::new("expected a tuple").value(args[0].to_string())),
+                },
+                arg => Err(EvalError::new("expected a tuple").value(arg.to_string())),
+            }),
+        }))
+    });
+
+    registry.register_function_factory("get_tuple", |params, args_type| {
+        let idx = *params.first()?;
+        let tuple_tys = match args_type.first() {
+            Some(DataType::Nullable(box DataType::Tuple { fields_type, .. })) => fields_type,
+            _ => return None,
+        };
+        if idx >= tuple_tys.len() {
+            return None;
+        }
+
+        Some(Arc::new(Function {
+            signature: FunctionSignature {
+                name: "get_tuple",
+                args_type: vec![args_type[0].clone()],
+                return_type: DataType::Nullable(Box::new(tuple_tys[idx].clone())),
+                property: FunctionProperty::default().preserve_not_null(true),
+            },
+            eval: Box::new(move |args, _| match &args[0] {
+                ValueRef::Scalar(Scalar::Null) => Ok(Value::Scalar(Scalar::Null)),
+                ValueRef::Scalar(Scalar::Tuple(fields)) => {
+                    Ok(Value::Scalar(fields[idx].to_owned()))
+                }
+                ValueRef::Column(Column::Const { scalar, len }) => {
+                    let scalar = match &**scalar {
+                        Scalar::Tuple(fields) => fields[idx].to_owned(),
+                        _ => Scalar::Null,
+                    };
+                    Ok(Value::Column(Column::Const {
+                        scalar: Box::new(scalar),
+                        len: *len,
+                    }))
+                }
+                ValueRef::Column(col) => match &*col.flatten() {
+                    Column::Nullable {
+                        column: box Column::Tuple { fields, .. },
+                        validity,
+                    } => Ok(Value::Column(Column::Nullable {
+                        column: Box::new(fields[idx].to_owned()),
+                        validity: validity.clone(),
+                    })),
+                    _ => {
+                        Err(EvalError::new("expected a nullable tuple").value(args[0].to_string()))
+                    }
+                },
+                arg => Err(EvalError::new("expected a nullable tuple").value(arg.to_string())),
+            }),
+        }))
+    });
+
+    registry
+}
+
+fn register_map_functions(registry: &mut FunctionRegistry) {
+    type GenericMapType = MapType<GenericType<0>, GenericType<1>>;
+
+    registry.register_with_writer_2_arg::<
+        ArrayType<GenericType<0>>,
+        ArrayType<GenericType<1>>,
+        GenericMapType,
+        _,
+    >("map", FunctionProperty::default(), |keys, values, output| {
+        if keys.len() != values.len() {
+            return Err(format!(
+                "{} keys and {} values can not be zipped into a map",
+                keys.len(),
+                values.len()
+            ));
+        }
+        if keys
+            .iter()
+            .enumerate()
+            .any(|(idx, key)| map_lookup(&keys, &key) != Some(idx))
+        {
+            return Err("duplicate keys can not be zipped into a map".to_string());
+        }
+        GenericMapType::push_item(output, (keys, values));
+        Ok(())
+    });
+
+    // The key is nullable in the lookups, so that a single overload serves
+    // both nullable and non-nullable keys. Separate overloads would be equally
+    // cheap for a map with a nullable key, since the key type is generic.
+    registry.register_2_arg_core::<
+        NullableType<GenericMapType>,
+        NullableType<GenericType<0>>,
+        NullableType<GenericType<1>>,
+        _,
+    >(
+        "map_get",
+        FunctionProperty::default(),
+        |map, key, ctx| {
+            Ok(vectorize_2_arg(map, key, ctx, |map, key| {
+                let ((keys, values), key) = map.zip(key)?;
+                map_lookup(&keys, &key).map(|idx| values.index(idx).to_owned())
+            }))
+        },
+    );
+    registry.register_2_arg_core::<
+        NullableType<GenericMapType>,
+        NullableType<GenericType<0>>,
+        NullableType<BooleanType>,
+        _,
+    >(
+        "map_contains",
+        FunctionProperty::default().preserve_not_null(true),
+        |map, key, ctx| {
+            Ok(vectorize_2_arg(map, key, ctx, |map, key| {
+                let ((keys, _), key) = map.zip(key)?;
+                Some(map_lookup(&keys, &key).is_some())
+            }))
+        },
+    );
+
+    registry.register_1_arg::<GenericMapType, ArrayType<GenericType<0>>, _>(
+        "map_keys",
+        FunctionProperty::default(),
+        |(keys, _)| keys,
+    );
+    registry.register_1_arg::<GenericMapType, ArrayType<GenericType<1>>, _>(
+        "map_values",
+        FunctionProperty::default(),
+        |(_, values)| values,
+    );
+}
+
+/// Returns the index of the first entry with the key.
+fn map_lookup(keys: &Column, key: &ScalarRef) -> Option<usize> {
+    keys.iter().position(|item| item == *key)
+}
+
+/// The variant functions return `NULL` when the path does not exist or the
+/// value is of another kind, so that they can be applied to irregular events.
+fn register_variant_functions(registry: &mut FunctionRegistry) {
+    registry.register_2_arg_core::<
+        NullableType<VariantType>,
+        NullableType<StringType>,
+        NullableType<VariantType>,
+        _,
+    >(
+        "get_path",
+        FunctionProperty::default(),
+        |variant, path, ctx| {
+            vectorize_with_writer_2_arg(variant, path, ctx, |variant, path, output| {
+                let value = match variant.zip(path) {
+                    Some((variant, path)) => get_path(variant, &parse_path(path)?),
+                    None => None,
+                };
+                NullableType::<VariantType>::push_item(output, value);
+                Ok(())
+            })
+        },
+    );
+
+    macro_rules! register_as {
+        ($name:expr, $output:ty, $extract:expr) => {
+            registry.register_1_arg_core::<NullableType<VariantType>, NullableType<$output>, _>(
+                $name,
+                FunctionProperty::default(),
+                |variant, ctx| {
+                    Ok(vectorize_1_arg(variant, ctx, |variant| {
+                        variant.and_then($extract)
+                    }))
+                },
+            );
+        };
+    }
+    register_as!("as_boolean", BooleanType, as_bool);
+    register_as!("as_int64", NumberType<i64>, as_i64);
+    register_as!("as_uint64", NumberType<u64>, as_u64);
+    register_as!("as_float64", NumberType<f64>, as_f64);
+    register_as!("as_string", StringType, |variant| as_str(variant)
+        .map(str::to_string));
+}
+/// String functions count and slice by characters, while the `Binary`
+/// overloads work on bytes.
+fn register_string_functions(registry: &mut FunctionRegistry) {
+    registry.register_1_arg::<StringType, NumberType<u64>, _>(
+        "length",
+        FunctionProperty::default(),
+        |text| text.chars().count() as u64,
+    );
+    registry.register_1_arg::<BinaryType, NumberType<u64>, _>(
+        "length",
+        FunctionProperty::default(),
+        |bytes| bytes.len() as u64,
+    );
+    registry.register_1_arg::<StringType, StringType, _>(
+        "upper",
+        FunctionProperty::default(),
+        |text| text.to_uppercase(),
+    );
+    registry.register_1_arg::<StringType, StringType, _>(
+        "lower",
+        FunctionProperty::default(),
+        |text| text.to_lowercase(),
+    );
+    registry.register_1_arg::<StringType, StringType, _>(
+        "reverse",
+        FunctionProperty::default(),
+        |text| text.chars().rev().collect(),
+    );
+    registry.register_1_arg::<BinaryType, BinaryType, _>(
+        "reverse",
+        FunctionProperty::default(),
+        |bytes| bytes.iter().rev().cloned().collect(),
+    );
+    // `substr(text, pos)` takes the characters from the 1-based position `pos`,
+    // or from the `-pos`th character counting from the end if it is negative.
+    registry.register_2_arg::<StringType, NumberType<i64>, StringType, _>(
+        "substr",
+        FunctionProperty::default(),
+        |text, pos| {
+            let len = text.chars().count() as i64;
+            let start = match pos {
+                pos if pos > 0 => pos - 1,
+                pos if pos < 0 => (len + pos).max(0),
+                _ => 0,
+            };
+            text.chars().skip(start as usize).collect()
+        },
+    );
+}
+
+fn register_temporal_functions(registry: &mut FunctionRegistry) {
+    macro_rules! register_part {
+        ($name:expr, $part:ident, $ty:ty, [$($input:ty: $to_naive:expr),*]) => {$(
+            registry.register_with_writer_1_arg::<$input, NumberType<$ty>, _>(
+                $name,
+                FunctionProperty::default(),
+                |val, output| {
+                    output.push($to_naive(val)?.$part() as $ty);
+                    Ok(())
+                },
+            );
+        )*};
+    }
+    register_part!("year", year, i32, [DateType: date_to_naive, TimestampType: timestamp_to_naive]);
+    register_part!("month", month, u8, [DateType: date_to_naive, TimestampType: timestamp_to_naive]);
+    register_part!("day", day, u8, [DateType: date_to_naive, TimestampType: timestamp_to_naive]);
+    register_part!("hour", hour, u8, [TimestampType: timestamp_to_naive]);
+    register_part!("minute", minute, u8, [TimestampType: timestamp_to_naive]);
+    register_part!("second", second, u8, [TimestampType: timestamp_to_naive]);
+
+    macro_rules! register_trunc {
+        ($name:expr, $unit:expr, [$($input:ty: $trunc:expr),*]) => {$(
+            registry.register_with_writer_1_arg::<$input, $input, _>(
+                $name,
+                FunctionProperty::default(),
+                |val, output| {
+                    output.push($trunc(val, $unit)?);
+                    Ok(())
+                },
+            );
+        )*};
+    }
+    register_trunc!("to_start_of_year", TimeUnit::Year, [DateType: trunc_date, TimestampType: trunc_timestamp]);
+    register_trunc!("to_start_of_month", TimeUnit::Month, [DateType: trunc_date, TimestampType: trunc_timestamp]);
+    register_trunc!("to_start_of_day", TimeUnit::Day, [TimestampType: trunc_timestamp]);
+    register_trunc!("to_start_of_hour", TimeUnit::Hour, [TimestampType: trunc_timestamp]);
+    register_trunc!("to_start_of_minute", TimeUnit::Minute, [TimestampType: trunc_timestamp]);
+    register_trunc!("to_start_of_second", TimeUnit::Second, [TimestampType: trunc_timestamp]);
+
+    registry.register_with_writer_2_arg::<DateType, DateType, IntervalType, _>(
+        "minus",
+        FunctionProperty::default(),
+        |lhs, rhs, output| {
+            let days = lhs as i64 - rhs as i64;
+            output.push(
+                days.checked_mul(MICROS_PER_DAY)
+                    .ok_or_else(|| "interval overflow".to_string())?,
+            );
+            Ok(())
+        },
+    );
+    registry.register_with_writer_2_arg::<TimestampType, TimestampType, IntervalType, _>(
+        "minus",
+        FunctionProperty::default(),
+        |lhs, rhs, output| {
+            output.push(
+                lhs.checked_sub(rhs)
+                    .ok_or_else(|| "interval overflow".to_string())?,
+            );
+            Ok(())
+        },
+    );
+    registry.register_with_writer_2_arg::<TimestampType, IntervalType, TimestampType, _>(
+        "minus",
+        FunctionProperty::default(),
+        |lhs, rhs, output| {
+            output.push(
+                lhs.checked_sub(rhs)
+                    .ok_or_else(|| "timestamp overflow".to_string())?,
+            );
+            Ok(())
+        },
+    );
+    registry.register_with_writer_2_arg::<TimestampType, IntervalType, TimestampType, _>(
+        "plus",
+        FunctionProperty::default(),
+        |lhs, rhs, output| {
+            output.push(
+                lhs.checked_add(rhs)
+                    .ok_or_else(|| "timestamp overflow".to_string())?,
+            );
+            Ok(())
+        },
+    );
+}
@@ -1,22 +1,36 @@
-use std::fmt::{Display, Formatter};
+use std::{
+    fmt::{Display, Formatter},
+    ops::Range,
+};
 
 use crate::{
-    expr::{Expr, Literal, AST},
+    expr::{Expr, Literal, Span, AST},
+    function::{AmbiguousOverload, FunctionSignature},
+    parser::ParseError,
     property::ValueProperty,
-    types::{DataType, ValueType},
-    values::{Value, ValueRef},
+    runtime::EvalError,
+    type_check::{TypeCheckError, UnifyError},
+    types::{variant::to_json, DataType, ValueType, VariantType},
+    values::{Column, Scalar, ScalarRef, Value, ValueRef},
 };
 
+/// The most overloads that a `NoMatchingOverload` error lists. The arithmetic
+/// functions have an overload for every pair of number types.
+const MAX_CANDIDATES: usize = 5;
+
 impl Display for AST {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            AST::Literal(literal) => write!(f, "{literal}"),
+            AST::Literal { lit, .. } => write!(f, "{lit}"),
             AST::ColumnRef {
                 name,
                 data_type,
                 property,
+                ..
             } => write!(f, "{name}::{data_type}{property}"),
-            AST::FunctionCall { name, args, params } => {
+            AST::FunctionCall {
+                name, args, params, ..
+            } => {
                 write!(f, "{name}")?;
                 if !params.is_empty() {
                     write!(f, "(")?;
@@ -37,6 +51,9 @@ impl Display for AST {
                 }
                 write!(f, ")")
             }
+            AST::Cast {
+                expr, dest_type, ..
+            } => write!(f, "cast({expr} as {dest_type})"),
         }
     }
 }
@@ -48,9 +65,25 @@ impl Display for Literal {
             Literal::Boolean(val) => write!(f, "{val}::Boolean"),
             Literal::UInt8(val) => write!(f, "{val}::UInt8"),
             Literal::UInt16(val) => write!(f, "{val}::UInt16"),
+            Literal::UInt32(val) => write!(f, "{val}::UInt32"),
+            Literal::UInt64(val) => write!(f, "{val}::UInt64"),
             Literal::Int8(val) => write!(f, "{val}::Int8"),
             Literal::Int16(val) => write!(f, "{val}::Int16"),
-            Literal::String(val) => write!(f, "{}::String", String::from_utf8_lossy(val)),
+            Literal::Int32(val) => write!(f, "{val}::Int32"),
+            Literal::Int64(val) => write!(f, "{val}::Int64"),
+            Literal::Float32(val) => write!(f, "{val}::Float32"),
+            Literal::Float64(val) => write!(f, "{val}::Float64"),
+            Literal::Decimal {
+                value,
+                precision,
+                scale,
+            } => write!(
+                f,
+                "{}::Decimal({precision}, {scale})",
+                display_decimal(*value, *scale)
+            ),
+            Literal::String(val) => write!(f, "{}::String", display_string(val)),
+            Literal::Binary(val) => write!(f, "{}::Binary", display_binary(val)),
         }
     }
 }
@@ -60,27 +93,45 @@ impl Display for DataType {
         match &self {
             DataType::Boolean => write!(f, "Boolean"),
             DataType::String => write!(f, "String"),
+            DataType::Binary => write!(f, "Binary"),
+            DataType::Variant => write!(f, "Variant"),
             DataType::UInt8 => write!(f, "UInt8"),
             DataType::UInt16 => write!(f, "UInt16"),
+            DataType::UInt32 => write!(f, "UInt32"),
+            DataType::UInt64 => write!(f, "UInt64"),
             DataType::Int8 => write!(f, "Int8"),
             DataType::Int16 => write!(f, "Int16"),
+            DataType::Int32 => write!(f, "Int32"),
+            DataType::Int64 => write!(f, "Int64"),
+            DataType::Date => write!(f, "Date"),
+            DataType::Timestamp => write!(f, "Timestamp"),
+            DataType::Interval => write!(f, "Interval"),
+            DataType::Float32 => write!(f, "Float32"),
+            DataType::Float64 => write!(f, "Float64"),
+            DataType::Decimal { precision, scale } => write!(f, "Decimal({precision}, {scale})"),
             DataType::Null => write!(f, "Nullable<Nothing>"),
             DataType::Nullable(inner) => write!(f, "Nullable<{inner}>"),
             DataType::EmptyArray => write!(f, "Array<Nothing>"),
             DataType::Array(inner) => write!(f, "Array<{inner}>"),
-            DataType::Tuple(tys) => {
-                if tys.len() == 1 {
-                    write!(f, "({},)", tys[0])
-                } else {
-                    write!(f, "(")?;
-                    for (i, ty) in tys.iter().enumerate() {
-                        if i > 0 {
-                            write!(f, ", ")?;
-                        }
-                        write!(f, "{ty}")?;
+            DataType::Map(key, value) => write!(f, "Map<{key}, {value}>"),
+            DataType::Tuple {
+                fields_name,
+                fields_type,
+            } => {
+                write!(f, "(")?;
+                for (i, (name, ty)) in fields_name.iter().zip(fields_type).enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    if let Some(name) = name {
+                        write!(f, "{name} ")?;
                     }
-                    write!(f, ")")
+                    write!(f, "{ty}")?;
+                }
+                if fields_type.len() == 1 {
+                    write!(f, ",")?;
                 }
+                write!(f, ")")
             }
             DataType::Generic(index) => write!(f, "T{index}"),
         }
@@ -101,8 +152,8 @@ impl Display for ValueProperty {
 impl Display for Expr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Expr::Literal(literal) => write!(f, "{literal}"),
-            Expr::ColumnRef { name } => write!(f, "{name}"),
+            Expr::Literal { lit, .. } => write!(f, "{lit}"),
+            Expr::ColumnRef { name, .. } => write!(f, "{name}"),
             Expr::FunctionCall {
                 function,
                 args,
@@ -137,18 +188,237 @@ impl Display for Expr {
                 }
                 write!(f, ")")
             }
-            Expr::Cast { expr, dest_type } => {
+            Expr::Cast {
+                expr, dest_type, ..
+            } => {
                 write!(f, "cast<dest_type={dest_type}>({expr})")
             }
         }
     }
 }
 
+impl Display for FunctionSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}(", self.name)?;
+        for (i, ty) in self.args_type.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{ty}")?;
+        }
+        write!(f, ") :: {}", self.return_type)
+    }
+}
+
+impl Display for TypeCheckError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypeCheckError::UnknownFunction { ast, name } => {
+                write!(f, "function `{name}` does not exist, in `{ast}`")?;
+                write_span(f, &ast.span())
+            }
+            TypeCheckError::WrongNumberOfArgs {
+                ast,
+                name,
+                expected,
+                found,
+            } => {
+                write!(f, "function `{name}` expects ")?;
+                for (i, n) in expected.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " or ")?;
+                    }
+                    write!(f, "{n}")?;
+                }
+                let plural = if expected.last() == Some(&1) { "" } else { "s" };
+                write!(f, " argument{plural}, but {found} were given, in `{ast}`")
+            }
+            TypeCheckError::NoMatchingOverload {
+                ast,
+                name,
+                args_type,
+                candidates,
+            } => {
+                write!(f, "no overload of function `{name}` accepts arguments (")?;
+                for (i, ty) in args_type.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{ty}")?;
+                }
+                write!(f, "), in `{ast}`")?;
+                write_span(f, &ast.span())?;
+                if !candidates.is_empty() {
+                    write!(f, "\n\ncandidates are:")?;
+                    // The overloads without nullable arguments come first, as
+                    // they are the ones that tell which types are accepted.
+                    let mut candidates = candidates.iter().collect::<Vec<_>>();
+                    candidates.sort_by_key(|(sig, _)| {
+                        sig.args_type
+                            .iter()
+                            .filter(|ty| ty.is_null() || ty.as_nullable().is_some())
+                            .count()
+                    });
+                    for (sig, reason) in candidates.iter().take(MAX_CANDIDATES) {
+                        write!(f, "\n  {sig}: {reason}")?;
+                    }
+                    if candidates.len() > MAX_CANDIDATES {
+                        write!(f, "\n  and {} more", candidates.len() - MAX_CANDIDATES)?;
+                    }
+                }
+                Ok(())
+            }
+            TypeCheckError::AmbiguousOverload {
+                ast,
+                name,
+                args_type,
+                candidates,
+            } => {
+                write!(f, "function `{name}` is ambiguous for arguments (")?;
+                for (i, ty) in args_type.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{ty}")?;
+                }
+                write!(f, "), in `{ast}`")?;
+                write_span(f, &ast.span())?;
+                write!(f, "\n\ncandidates are:")?;
+                for sig in candidates {
+                    write!(f, "\n  {sig}")?;
+                }
+                Ok(())
+            }
+            TypeCheckError::InvalidCast {
+                ast,
+                src_ty,
+                dest_ty,
+            } => {
+                write!(f, "unable to cast {src_ty} to {dest_ty}, in `{ast}`")?;
+                write_span(f, &ast.span())
+            }
+            TypeCheckError::UnknownField {
+                ast,
+                field,
+                data_type,
+            } => {
+                write!(f, "type {data_type} has no field `{field}`, in `{ast}`")?;
+                write_span(f, &ast.span())
+            }
+            TypeCheckError::FieldNameNotLiteral { ast } => {
+                write!(f, "field name must be a string literal, in `{ast}`")?;
+                write_span(f, &ast.span())
+            }
+        }
+    }
+}
+
+impl Display for AmbiguousOverload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "overloads of function `{}` are ambiguous for arguments (",
+            self.name
+        )?;
+        for (i, ty) in self.args_type.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{ty}")?;
+        }
+        write!(f, "):")?;
+        for sig in &self.candidates {
+            write!(f, "\n  {sig}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(function) = &self.function {
+            write!(f, ", while evaluating `{function}`")?;
+        }
+        if let Some(row) = self.row {
+            write!(f, ", at row {row}")?;
+        }
+        if let Some(value) = &self.value {
+            write!(f, ", with value {value}")?;
+        }
+        write_span(f, &self.span)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+        write_span(f, &Some(self.span.clone()))
+    }
+}
+
+fn write_span(f: &mut std::fmt::Formatter<'_>, span: &Span) -> std::fmt::Result {
+    match span {
+        Some(span) => write!(f, " at {}..{}", span.start, span.end),
+        None => Ok(()),
+    }
+}
+
+/// Renders the line of `source` where `span` starts, with the spanned text
+/// underlined by carets, for example:
+///
+/// ```text
+/// 1 | and(true, plus(true, 1))
+///   |           ^^^^^^^^^^^^^
+/// ```
+///
+/// A span that runs over multiple lines is underlined till the end of its first line.
+pub fn display_span(source: &str, span: Range<usize>) -> String {
+    let start = span.start.min(source.len());
+    let end = span.end.clamp(start, source.len());
+    let line_start = source[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = source[start..]
+        .find('\n')
+        .map(|i| start + i)
+        .unwrap_or(source.len());
+    let line_no = source[..line_start].matches('\n').count() + 1;
+
+    let line = &source[line_start..line_end];
+    let indent = source[line_start..start].chars().count();
+    let width = source[start..end.min(line_end)].chars().count().max(1);
+    let gutter = " ".repeat(line_no.to_string().len());
+
+    format!(
+        "{line_no} | {line}\n{gutter} | {}{}",
+        " ".repeat(indent),
+        "^".repeat(width)
+    )
+}
+
+impl Display for UnifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnifyError::CannotCast { src_ty, dest_ty } => {
+                write!(f, "`{src_ty}` can not be cast to `{dest_ty}`")
+            }
+            UnifyError::ConflictingGeneric { idx, ty1, ty2 } => {
+                write!(
+                    f,
+                    "`T{idx}` can not be unified with both `{ty1}` and `{ty2}`"
+                )
+            }
+            UnifyError::UnboundGeneric { idx } => {
+                write!(f, "`T{idx}` is not bound to any type")
+            }
+        }
+    }
+}
+
 impl<T: ValueType> Display for Value<T> {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         match self {
-            Value::Scalar(scalar) => write!(f, "{:?}", scalar),
-            Value::Column(col) => write!(f, "{:?}", col),
+            Value::Scalar(scalar) => write!(f, "{}", T::display_scalar(&T::to_scalar_ref(scalar))),
+            Value::Column(col) => write!(f, "{}", T::display_column(col)),
         }
     }
 }
@@ -156,8 +426,117 @@ impl<T: ValueType> Display for Value<T> {
 impl<'a, T: ValueType> Display for ValueRef<'a, T> {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         match self {
-            ValueRef::Scalar(scalar) => write!(f, "{:?}", scalar),
-            ValueRef::Column(col) => write!(f, "{:?}", col),
+            ValueRef::Scalar(scalar) => write!(f, "{}", T::display_scalar(scalar)),
+            ValueRef::Column(col) => write!(f, "{}", T::display_column(col)),
+        }
+    }
+}
+
+/// Same as `Debug`, except that variants are rendered as JSON text instead of
+/// their binary encoding.
+impl Display for Scalar {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_ref())
+    }
+}
+
+impl<'a> Display for ScalarRef<'a> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ScalarRef::Variant(variant) => write!(f, "Variant({})", to_json(variant)),
+            ScalarRef::Array(col) => write!(f, "Array({col})"),
+            ScalarRef::Tuple(fields) => {
+                write!(f, "Tuple(")?;
+                write_list(f, fields)?;
+                write!(f, ")")
+            }
+            scalar => write!(f, "{scalar:?}"),
         }
     }
 }
+
+/// Same as `Debug`, except that variants are rendered as JSON text instead of
+/// their binary encoding.
+impl Display for Column {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Column::Variant { data, offsets } => write!(
+                f,
+                "Variant({})",
+                VariantType::display_column(&(data.clone(), offsets.clone()))
+            ),
+            Column::Array { array, offsets } => {
+                write!(f, "Array {{ array: {array}, offsets: {offsets:?} }}")
+            }
+            Column::Nullable { column, validity } => {
+                write!(f, "Nullable {{ column: {column}, validity: {validity:?} }}")
+            }
+            Column::Tuple { fields, len } => {
+                write!(f, "Tuple {{ fields: ")?;
+                write_list(f, fields)?;
+                write!(f, ", len: {len} }}")
+            }
+            Column::Dictionary { keys, values } => {
+                write!(f, "Dictionary {{ keys: {keys:?}, values: {values} }}")
+            }
+            Column::Const { scalar, len } => write!(f, "Const {{ scalar: {scalar}, len: {len} }}"),
+            col => write!(f, "{col:?}"),
+        }
+    }
+}
+
+fn write_list<T: Display>(f: &mut Formatter, items: &[T]) -> std::fmt::Result {
+    write!(f, "[")?;
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{item}")?;
+    }
+    write!(f, "]")
+}
+
+/// Renders a decimal value stored as an integer scaled by `10^scale`.
+pub fn display_decimal(value: i128, scale: u8) -> String {
+    let digits = value.unsigned_abs().to_string();
+    let scale = scale as usize;
+    let sign = if value < 0 { "-" } else { "" };
+    if scale == 0 {
+        format!("{sign}{digits}")
+    } else {
+        let digits = format!("{digits:0>width$}", width = scale + 1);
+        let (integral, fraction) = digits.split_at(digits.len() - scale);
+        format!("{sign}{integral}.{fraction}")
+    }
+}
+
+/// Renders a string as a quoted literal that the parser reads back, escaping
+/// quotes, backslashes and control characters, such as `'it\'s\n'`.
+pub fn display_string(string: &str) -> String {
+    let mut quoted = String::with_capacity(string.len() + 2);
+    quoted.push('\'');
+    for c in string.chars() {
+        match c {
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            '\0' => quoted.push_str("\\0"),
+            '\\' | '\'' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// Renders bytes as a hexadecimal literal, such as `x'c328'`.
+pub fn display_binary(bytes: &[u8]) -> String {
+    let hex = bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    format!("x'{hex}'")
+}
@@ -1,37 +1,61 @@
-use std::sync::Arc;
+use std::{ops::Range, sync::Arc};
+
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    function::{Function, FunctionID},
+    function::{Function, FunctionID, FunctionRegistry},
     property::ValueProperty,
-    types::DataType,
+    type_check::{check_literal, Subsitution},
+    types::{DataType, F32, F64},
 };
 
+/// The byte range in the source text that an `AST` node is parsed from.
+pub type Span = Option<Range<usize>>;
+
 #[derive(Debug, Clone)]
 pub enum AST {
-    Literal(Literal),
+    Literal {
+        span: Span,
+        lit: Literal,
+    },
     ColumnRef {
+        span: Span,
         name: String,
         data_type: DataType,
         property: ValueProperty,
     },
     FunctionCall {
+        span: Span,
         name: String,
         params: Vec<usize>,
         args: Vec<AST>,
     },
+    /// An explicit cast, which also allows the conversions that may fail at
+    /// runtime, such as parsing a string.
+    Cast {
+        span: Span,
+        expr: Box<AST>,
+        dest_type: DataType,
+    },
 }
 
 #[derive(Debug, Clone)]
 pub enum Expr {
-    Literal(Literal),
+    Literal {
+        span: Span,
+        lit: Literal,
+    },
     ColumnRef {
+        span: Span,
         name: String,
     },
     Cast {
+        span: Span,
         expr: Box<Expr>,
         dest_type: DataType,
     },
     FunctionCall {
+        span: Span,
         id: FunctionID,
         function: Arc<Function>,
         generics: Vec<DataType>,
@@ -39,13 +63,233 @@ pub enum Expr {
     },
 }
 
-#[derive(Debug, Clone)]
+/// The serializable form of `Expr`, to send an expression to the remote
+/// execution nodes. The functions are referred to by their `FunctionID` and
+/// are rebuilt from the `FunctionRegistry` of the receiving node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RemoteExpr {
+    Literal {
+        span: Span,
+        lit: Literal,
+    },
+    ColumnRef {
+        span: Span,
+        name: String,
+    },
+    Cast {
+        span: Span,
+        expr: Box<RemoteExpr>,
+        dest_type: DataType,
+    },
+    FunctionCall {
+        span: Span,
+        id: FunctionID,
+        generics: Vec<DataType>,
+        args: Vec<(RemoteExpr, ValueProperty)>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Literal {
     Null,
     Int8(i8),
     Int16(i16),
+    Int32(i32),
+    Int64(i64),
+    Float32(F32),
+    Float64(F64),
+    Decimal {
+        value: i128,
+        precision: u8,
+        scale: u8,
+    },
     UInt8(u8),
     UInt16(u16),
+    UInt32(u32),
+    UInt64(u64),
     Boolean(bool),
-    String(Vec<u8>),
+    String(String),
+    Binary(Vec<u8>),
+}
+
+impl AST {
+    pub fn span(&self) -> Span {
+        match self {
+            AST::Literal { span, .. }
+            | AST::ColumnRef { span, .. }
+            | AST::FunctionCall { span, .. }
+            | AST::Cast { span, .. } => span.clone(),
+        }
+    }
+}
+
+impl Expr {
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Literal { span, .. }
+            | Expr::ColumnRef { span, .. }
+            | Expr::Cast { span, .. }
+            | Expr::FunctionCall { span, .. } => span.clone(),
+        }
+    }
+
+    pub fn to_remote(&self) -> RemoteExpr {
+        match self {
+            Expr::Literal { span, lit } => RemoteExpr::Literal {
+                span: span.clone(),
+                lit: lit.clone(),
+            },
+            Expr::ColumnRef { span, name } => RemoteExpr::ColumnRef {
+                span: span.clone(),
+                name: name.clone(),
+            },
+            Expr::Cast {
+                span,
+                expr,
+                dest_type,
+            } => RemoteExpr::Cast {
+                span: span.clone(),
+                expr: Box::new(expr.to_remote()),
+                dest_type: dest_type.clone(),
+            },
+            Expr::FunctionCall {
+                span,
+                id,
+                generics,
+                args,
+                ..
+            } => RemoteExpr::FunctionCall {
+                span: span.clone(),
+                id: id.clone(),
+                generics: generics.clone(),
+                args: args
+                    .iter()
+                    .map(|(arg, prop)| (arg.to_remote(), *prop))
+                    .collect(),
+            },
+        }
+    }
+
+    /// Removes the casts that the type checker inserts to match the function
+    /// signatures. They are told apart from the explicit casts by having the
+    /// same span as their argument.
+    pub fn strip_inserted_casts(&self) -> Expr {
+        match self {
+            Expr::Cast { span, expr, .. } if *span == expr.span() => expr.strip_inserted_casts(),
+            Expr::Cast {
+                span,
+                expr,
+                dest_type,
+            } => Expr::Cast {
+                span: span.clone(),
+                expr: Box::new(expr.strip_inserted_casts()),
+                dest_type: dest_type.clone(),
+            },
+            Expr::FunctionCall {
+                span,
+                id,
+                function,
+                generics,
+                args,
+            } => Expr::FunctionCall {
+                span: span.clone(),
+                id: id.clone(),
+                function: function.clone(),
+                generics: generics.clone(),
+                args: args
+                    .iter()
+                    .map(|(arg, prop)| (arg.strip_inserted_casts(), *prop))
+                    .collect(),
+            },
+            expr => expr.clone(),
+        }
+    }
+}
+
+impl RemoteExpr {
+    /// Rebuilds the functions from the registry. The arguments of a function
+    /// call are checked against the rebuilt signature, so that an expression
+    /// from a mismatched registry is an error rather than a panic when it is
+    /// evaluated. The types of the columns are only known at run time, so a
+    /// column passed directly to a function is not checked.
+    pub fn into_expr(self, fn_registry: &FunctionRegistry) -> Result<Expr, String> {
+        self.into_expr_typed(fn_registry).map(|(expr, _)| expr)
+    }
+
+    fn into_expr_typed(
+        self,
+        fn_registry: &FunctionRegistry,
+    ) -> Result<(Expr, Option<DataType>), String> {
+        Ok(match self {
+            RemoteExpr::Literal { span, lit } => {
+                let (ty, _) = check_literal(&lit);
+                (Expr::Literal { span, lit }, Some(ty))
+            }
+            RemoteExpr::ColumnRef { span, name } => (Expr::ColumnRef { span, name }, None),
+            RemoteExpr::Cast {
+                span,
+                expr,
+                dest_type,
+            } => (
+                Expr::Cast {
+                    span,
+                    expr: Box::new(expr.into_expr(fn_registry)?),
+                    dest_type: dest_type.clone(),
+                },
+                Some(dest_type),
+            ),
+            RemoteExpr::FunctionCall {
+                span,
+                id,
+                generics,
+                args,
+            } => {
+                let function = fn_registry
+                    .rebuild(&id)
+                    .ok_or_else(|| format!("unable to rebuild function `{}`: {id:?}", id.name()))?;
+                let signature = &function.signature;
+                let expected = signature.args_type.len();
+                if args.len() != expected {
+                    let plural = if expected == 1 { "" } else { "s" };
+                    return Err(format!(
+                        "function `{}` expects {expected} argument{plural}, but {} were given",
+                        id.name(),
+                        args.len()
+                    ));
+                }
+                let subst = Subsitution(generics.iter().cloned().enumerate().collect());
+                let instantiate = |ty: &DataType| {
+                    subst
+                        .apply(ty.clone())
+                        .map_err(|_| format!("missing generic types for function `{}`", id.name()))
+                };
+                let args = args
+                    .into_iter()
+                    .zip(&signature.args_type)
+                    .map(|((arg, prop), sig_type)| {
+                        let (arg, arg_type) = arg.into_expr_typed(fn_registry)?;
+                        let sig_type = instantiate(sig_type)?;
+                        match arg_type {
+                            Some(arg_type) if arg_type != sig_type => Err(format!(
+                                "function `{}` expects an argument of type {sig_type}, but {arg_type} was given",
+                                id.name()
+                            )),
+                            _ => Ok((arg, prop)),
+                        }
+                    })
+                    .collect::<Result<_, String>>()?;
+                let return_type = instantiate(&signature.return_type)?;
+                (
+                    Expr::FunctionCall {
+                        span,
+                        id,
+                        function,
+                        generics,
+                        args,
+                    },
+                    Some(return_type),
+                )
+            }
+        })
+    }
 }
@@ -1,11 +1,24 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Arc,
+};
 
+use arrow2::{bitmap::Bitmap, types::NativeType};
 use educe::Educe;
+use num_traits::{NumCast, ToPrimitive};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     property::FunctionProperty,
-    types::*,
-    values::{Value, ValueRef},
+    runtime::EvalError,
+    type_check::unify_signature,
+    types::{
+        decimal::{decimal_op_result_size, decimal_size, vectorize_decimal_op, DecimalOp},
+        number::{CheckedArithmetic, Number, NumberPromote, NumberSigned},
+        *,
+    },
+    util::constant_bitmap,
+    values::{Column, Value, ValueRef},
 };
 
 #[derive(Debug, Clone)]
@@ -18,27 +31,72 @@ pub struct FunctionSignature {
 
 /// `FunctionID` is a unique identifier for a function. It's used to construct
 /// the exactly same function from the remote execution nodes.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum FunctionID {
     Builtin {
-        name: &'static str,
+        name: String,
         id: usize,
     },
     Factory {
-        name: &'static str,
+        name: String,
         id: usize,
         params: Vec<usize>,
         args_type: Vec<DataType>,
     },
 }
 
+impl FunctionID {
+    pub fn name(&self) -> &str {
+        match self {
+            FunctionID::Builtin { name, .. } | FunctionID::Factory { name, .. } => name,
+        }
+    }
+}
+
+/// What a function is evaluated with besides its arguments.
+pub struct EvalContext<'a> {
+    pub generics: &'a GenericMap<'a>,
+    /// The rows to evaluate. The other rows are hidden, and the functions may
+    /// fill them with any value.
+    pub selection: Option<&'a Bitmap>,
+}
+
+impl<'a> EvalContext<'a> {
+    pub fn is_selected(&self, row: usize) -> bool {
+        self.selection
+            .map(|selection| selection.get_bit(row))
+            .unwrap_or(true)
+    }
+}
+
 #[derive(Educe)]
 #[educe(Debug)]
 pub struct Function {
     pub signature: FunctionSignature,
     #[educe(Debug(ignore))]
     #[allow(clippy::type_complexity)]
-    pub eval: Box<dyn Fn(&[ValueRef<AnyType>], &GenericMap) -> Value<AnyType>>,
+    pub eval: Box<dyn Fn(&[ValueRef<AnyType>], &EvalContext) -> Result<Value<AnyType>, EvalError>>,
+}
+
+/// Overloads of a function that accept the same argument types at the same
+/// cost, so that the type checker can not decide which one to call.
+#[derive(Debug, Clone)]
+pub struct AmbiguousOverload {
+    pub name: &'static str,
+    pub args_type: Vec<DataType>,
+    pub candidates: Vec<FunctionSignature>,
+}
+
+/// A binary arithmetic kernel that is generic over the number type both
+/// operands are promoted to. See `FunctionRegistry::register_arithmetic_2_arg`.
+/// An error, such as an overflow, is reported with the row that caused it.
+pub trait ArithmeticKernel: 'static {
+    /// Whether the result is the signed counterpart of the promoted type, for
+    /// kernels like subtraction whose result may be negative for unsigned
+    /// operands. See `NumberSigned`.
+    const SIGNED_OUTPUT: bool = false;
+
+    fn eval<T: NativeType + CheckedArithmetic>(lhs: T, rhs: T) -> Result<T, String>;
 }
 
 #[derive(Default)]
@@ -61,6 +119,8 @@ impl FunctionRegistry {
         params: &[usize],
         args_type: &[DataType],
     ) -> Vec<(FunctionID, Arc<Function>)> {
+        let mut candidates = Vec::new();
+
         if params.is_empty() {
             let builtin_funcs = self
                 .funcs
@@ -73,7 +133,13 @@ impl FunctionRegistry {
                             if func.signature.name == *name
                                 && func.signature.args_type.len() == args_type.len()
                             {
-                                Some((FunctionID::Builtin { name, id }, func.clone()))
+                                Some((
+                                    FunctionID::Builtin {
+                                        name: name.to_string(),
+                                        id,
+                                    },
+                                    func.clone(),
+                                ))
                             } else {
                                 None
                             }
@@ -81,13 +147,11 @@ impl FunctionRegistry {
                         .collect::<Vec<_>>()
                 })
                 .unwrap_or_default();
-
-            if !builtin_funcs.is_empty() {
-                return builtin_funcs;
-            }
+            candidates.extend(builtin_funcs);
         }
 
-        self.factories
+        let factory_funcs = self
+            .factories
             .get_key_value(name)
             .map(|(name, factories)| {
                 factories
@@ -97,7 +161,7 @@ impl FunctionRegistry {
                         factory(params, args_type).map(|func| {
                             (
                                 FunctionID::Factory {
-                                    name,
+                                    name: name.to_string(),
                                     id,
                                     params: params.to_vec(),
                                     args_type: args_type.to_vec(),
@@ -108,7 +172,111 @@ impl FunctionRegistry {
                     })
                     .collect::<Vec<_>>()
             })
-            .unwrap_or_default()
+            .unwrap_or_default();
+        candidates.extend(factory_funcs);
+
+        candidates
+    }
+
+    /// Reconstructs the function that `search_candidates` returned with this
+    /// `FunctionID`. The registry must have been built in the same way as the
+    /// one the id comes from.
+    pub fn rebuild(&self, id: &FunctionID) -> Option<Arc<Function>> {
+        match id {
+            FunctionID::Builtin { name, id } => self.funcs.get(name.as_str())?.get(*id).cloned(),
+            FunctionID::Factory {
+                name,
+                id,
+                params,
+                args_type,
+            } => self.factories.get(name.as_str())?.get(*id)?(params, args_type),
+        }
+    }
+
+    /// Whether a builtin overload with exactly these argument types has been
+    /// registered. Used to register the `Null` overloads only once when the
+    /// function is registered for several types.
+    fn has_signature(&self, name: &str, args_type: &[DataType]) -> bool {
+        self.funcs.get(name).is_some_and(|funcs| {
+            funcs
+                .iter()
+                .any(|func| func.signature.args_type == args_type)
+        })
+    }
+
+    /// Checks that the builtin overloads registered under the same name never
+    /// match the same argument types at the same cost.
+    ///
+    /// The overloads are probed with the primitive types, their nullable variants,
+    /// and the argument types of the signatures with generics instantiated to each
+    /// primitive type. Function factories are not checked because their overloads
+    /// are only known at type-check time.
+    pub fn validate(&self) -> Result<(), Vec<AmbiguousOverload>> {
+        let mut ambiguities: Vec<AmbiguousOverload> = Vec::new();
+
+        let mut names = self.funcs.keys().collect::<Vec<_>>();
+        names.sort();
+        for name in names {
+            let funcs = &self.funcs[name];
+            let arities = funcs
+                .iter()
+                .map(|func| func.signature.args_type.len())
+                .collect::<BTreeSet<_>>();
+            for arity in arities {
+                let sigs = funcs
+                    .iter()
+                    .map(|func| &func.signature)
+                    .filter(|sig| sig.args_type.len() == arity)
+                    .collect::<Vec<_>>();
+                if sigs.len() < 2 {
+                    continue;
+                }
+
+                let probes = (0..arity)
+                    .map(|i| probe_types(sigs.iter().map(|sig| &sig.args_type[i])))
+                    .collect::<Vec<_>>();
+                for args_type in cartesian_product(&probes) {
+                    let matches = sigs
+                        .iter()
+                        .filter_map(|sig| Some((*sig, unify_signature(&args_type, sig).ok()?.1)))
+                        .collect::<Vec<_>>();
+                    let min_cost = match matches.iter().map(|(_, cost)| *cost).min() {
+                        Some(min_cost) => min_cost,
+                        None => continue,
+                    };
+                    let candidates = matches
+                        .into_iter()
+                        .filter(|(_, cost)| *cost == min_cost)
+                        .map(|(sig, _)| sig.clone())
+                        .collect::<Vec<_>>();
+                    if candidates.len() < 2 {
+                        continue;
+                    }
+
+                    let reported = ambiguities.iter().any(|ambiguity| {
+                        ambiguity.name == *name
+                            && ambiguity
+                                .candidates
+                                .iter()
+                                .map(|sig| &sig.args_type)
+                                .eq(candidates.iter().map(|sig| &sig.args_type))
+                    });
+                    if !reported {
+                        ambiguities.push(AmbiguousOverload {
+                            name,
+                            args_type,
+                            candidates,
+                        });
+                    }
+                }
+            }
+        }
+
+        if ambiguities.is_empty() {
+            Ok(())
+        } else {
+            Err(ambiguities)
+        }
     }
 
     pub fn register_0_arg_core<O: ArgType, F>(
@@ -117,20 +285,17 @@ impl FunctionRegistry {
         property: FunctionProperty,
         func: F,
     ) where
-        F: Fn(&GenericMap) -> Value<O> + 'static + Clone + Copy,
+        F: Fn(&EvalContext) -> Result<Value<O>, EvalError> + 'static + Clone + Copy,
     {
-        self.funcs
-            .entry(name)
-            .or_insert_with(Vec::new)
-            .push(Arc::new(Function {
-                signature: FunctionSignature {
-                    name,
-                    args_type: vec![],
-                    return_type: O::data_type(),
-                    property,
-                },
-                eval: Box::new(erase_function_generic_0_arg(func)),
-            }));
+        self.funcs.entry(name).or_default().push(Arc::new(Function {
+            signature: FunctionSignature {
+                name,
+                args_type: vec![],
+                return_type: O::data_type(),
+                property,
+            },
+            eval: Box::new(erase_function_generic_0_arg(func)),
+        }));
     }
 
     pub fn register_1_arg<I1: ArgType, O: ArgType, F>(
@@ -153,28 +318,56 @@ impl FunctionRegistry {
 
         let property = property.preserve_not_null(true);
 
-        self.register_1_arg_core::<NullType, NullType, _>(name, property, move |_, _| {
-            Value::Scalar(())
-        });
+        if !self.has_signature(name, &[DataType::Null]) {
+            self.register_1_arg_core::<NullType, NullType, _>(name, property, move |_, _| {
+                Ok(Value::Scalar(()))
+            });
+        }
 
-        self.register_1_arg_core::<I1, O, _>(name, property, move |val, generics| {
-            vectorize_1_arg(val, generics, func)
+        self.register_1_arg_vectorized::<I1, O, _>(name, property, move |val, ctx| {
+            Ok(vectorize_1_arg(val, ctx, func))
         });
 
-        self.register_1_arg_core::<NullableType<I1>, NullableType<O>, _>(
+        self.register_1_arg_vectorized::<NullableType<I1>, NullableType<O>, _>(
             name,
             property,
-            move |val, generics| vectorize_passthrough_nullable_1_arg(val, generics, func),
+            move |val, ctx| Ok(vectorize_passthrough_nullable_1_arg(val, ctx, func)),
         );
     }
 
+    /// Same as `register_1_arg_core`, but for the functions that map every row
+    /// independently, which can take the dictionary fast path.
+    fn register_1_arg_vectorized<I1: ArgType, O: ArgType, F>(
+        &mut self,
+        name: &'static str,
+        property: FunctionProperty,
+        func: F,
+    ) where
+        F: Fn(ValueRef<I1>, &EvalContext) -> Result<Value<O>, EvalError> + 'static + Clone + Copy,
+    {
+        self.funcs.entry(name).or_default().push(Arc::new(Function {
+            signature: FunctionSignature {
+                name,
+                args_type: vec![I1::data_type()],
+                return_type: O::data_type(),
+                property,
+            },
+            eval: Box::new(vectorize_dictionary_1_arg(erase_function_generic_1_arg(
+                func,
+            ))),
+        }));
+    }
+
     pub fn register_with_writer_1_arg<I1: ArgType, O: ArgType, F>(
         &mut self,
         name: &'static str,
         property: FunctionProperty,
         func: F,
     ) where
-        F: for<'a> Fn(I1::ScalarRef<'a>, &mut O::ColumnBuilder) + 'static + Clone + Copy,
+        F: for<'a> Fn(I1::ScalarRef<'a>, &mut O::ColumnBuilder) -> Result<(), String>
+            + 'static
+            + Clone
+            + Copy,
     {
         let has_nullable = &[I1::data_type(), O::data_type()]
             .iter()
@@ -188,20 +381,20 @@ impl FunctionRegistry {
 
         let property = property.preserve_not_null(true);
 
-        self.register_1_arg_core::<NullType, NullType, _>(name, property, move |_, _| {
-            Value::Scalar(())
-        });
+        if !self.has_signature(name, &[DataType::Null]) {
+            self.register_1_arg_core::<NullType, NullType, _>(name, property, move |_, _| {
+                Ok(Value::Scalar(()))
+            });
+        }
 
-        self.register_1_arg_core::<I1, O, _>(name, property, move |val, generics| {
-            vectorize_with_writer_1_arg(val, generics, func)
+        self.register_1_arg_core::<I1, O, _>(name, property, move |val, ctx| {
+            vectorize_with_writer_1_arg(val, ctx, func)
         });
 
         self.register_1_arg_core::<NullableType<I1>, NullableType<O>, _>(
             name,
             property,
-            move |val, generics| {
-                vectorize_with_writer_passthrough_nullable_1_arg(val, generics, func)
-            },
+            move |val, ctx| vectorize_with_writer_passthrough_nullable_1_arg(val, ctx, func),
         );
     }
 
@@ -211,20 +404,17 @@ impl FunctionRegistry {
         property: FunctionProperty,
         func: F,
     ) where
-        F: Fn(ValueRef<I1>, &GenericMap) -> Value<O> + 'static + Clone + Copy,
+        F: Fn(ValueRef<I1>, &EvalContext) -> Result<Value<O>, EvalError> + 'static + Clone + Copy,
     {
-        self.funcs
-            .entry(name)
-            .or_insert_with(Vec::new)
-            .push(Arc::new(Function {
-                signature: FunctionSignature {
-                    name,
-                    args_type: vec![I1::data_type()],
-                    return_type: O::data_type(),
-                    property,
-                },
-                eval: Box::new(erase_function_generic_1_arg(func)),
-            }));
+        self.funcs.entry(name).or_default().push(Arc::new(Function {
+            signature: FunctionSignature {
+                name,
+                args_type: vec![I1::data_type()],
+                return_type: O::data_type(),
+                property,
+            },
+            eval: Box::new(erase_function_generic_1_arg(func)),
+        }));
     }
 
     pub fn register_2_arg<I1: ArgType, I2: ArgType, O: ArgType, F>(
@@ -251,38 +441,199 @@ impl FunctionRegistry {
 
         let property = property.preserve_not_null(true);
 
-        self.register_2_arg_core::<NullType, I2, NullType, _>(name, property, move |_, _, _| {
-            Value::Scalar(())
-        });
-        self.register_2_arg_core::<I1, NullType, NullType, _>(name, property, move |_, _, _| {
-            Value::Scalar(())
+        if !self.has_signature(name, &[DataType::Null, I2::data_type()]) {
+            self.register_2_arg_core::<NullType, I2, NullType, _>(
+                name,
+                property,
+                move |_, _, _| Ok(Value::Scalar(())),
+            );
+        }
+        if !self.has_signature(name, &[I1::data_type(), DataType::Null]) {
+            self.register_2_arg_core::<I1, NullType, NullType, _>(
+                name,
+                property,
+                move |_, _, _| Ok(Value::Scalar(())),
+            );
+        }
+        if !self.has_signature(name, &[DataType::Null, DataType::Null]) {
+            self.register_2_arg_core::<NullType, NullType, NullType, _>(
+                name,
+                property,
+                move |_, _, _| Ok(Value::Scalar(())),
+            );
+        }
+        if !self.has_signature(name, &[DataType::Null, NullableType::<I2>::data_type()]) {
+            self.register_2_arg_core::<NullType, NullableType<I2>, NullType, _>(
+                name,
+                property,
+                move |_, _, _| Ok(Value::Scalar(())),
+            );
+        }
+        if !self.has_signature(name, &[NullableType::<I1>::data_type(), DataType::Null]) {
+            self.register_2_arg_core::<NullableType<I1>, NullType, NullType, _>(
+                name,
+                property,
+                move |_, _, _| Ok(Value::Scalar(())),
+            );
+        }
+
+        self.register_2_arg_core::<I1, I2, O, _>(name, property, move |lhs, rhs, ctx| {
+            Ok(vectorize_2_arg(lhs, rhs, ctx, func))
         });
-        self.register_2_arg_core::<NullType, NullType, NullType, _>(
+
+        self.register_2_arg_core::<NullableType<I1>, NullableType<I2>, NullableType<O>, _>(
             name,
             property,
-            move |_, _, _| Value::Scalar(()),
+            move |lhs, rhs, ctx| Ok(vectorize_passthrough_nullable_2_arg(lhs, rhs, ctx, func)),
         );
+    }
 
-        self.register_2_arg_core::<I1, I2, O, _>(name, property, move |lhs, rhs, generics| {
-            vectorize_2_arg(lhs, rhs, generics, func)
-        });
+    /// Registers an arithmetic function for every pair of number types. Each
+    /// overload converts the operands to the promoted type (see `NumberPromote`),
+    /// or to its signed counterpart if `K::SIGNED_OUTPUT` is set, and runs the
+    /// kernel monomorphized for that type.
+    pub fn register_arithmetic_2_arg<K: ArithmeticKernel>(
+        &mut self,
+        name: &'static str,
+        property: FunctionProperty,
+    ) {
+        macro_rules! register_rhs {
+            ($lhs:ty; $($rhs:ty),*) => {
+                $(
+                    if K::SIGNED_OUTPUT {
+                        self.register_arithmetic_2_arg_typed::<
+                            $lhs,
+                            $rhs,
+                            <<$lhs as NumberPromote<$rhs>>::Output as NumberSigned>::Signed,
+                            K,
+                        >(name, property);
+                    } else {
+                        self.register_arithmetic_2_arg_typed::<
+                            $lhs,
+                            $rhs,
+                            <$lhs as NumberPromote<$rhs>>::Output,
+                            K,
+                        >(name, property);
+                    }
+                )*
+            };
+        }
+        macro_rules! register_lhs {
+            ($($lhs:ty),*) => {
+                $(
+                    register_rhs!($lhs; u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+                )*
+            };
+        }
+        register_lhs!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+    }
 
-        self.register_2_arg_core::<NullableType<I1>, NullableType<I2>, NullableType<O>, _>(
+    fn register_arithmetic_2_arg_typed<L, R, O, K>(
+        &mut self,
+        name: &'static str,
+        property: FunctionProperty,
+    ) where
+        L: Number,
+        R: Number,
+        O: Number,
+        K: ArithmeticKernel,
+        L::Storage: ToPrimitive + std::fmt::Display,
+        R::Storage: ToPrimitive + std::fmt::Display,
+        O::Storage: NumCast + CheckedArithmetic,
+    {
+        // `UInt64` is promoted to `Int64` when the other operand is signed, so
+        // the conversion is range-checked rather than wrapping.
+        fn promote<T: ToPrimitive + Copy + std::fmt::Display, O: Number>(
+            value: T,
+        ) -> Result<O::Storage, String>
+        where
+            O::Storage: NumCast,
+        {
+            NumCast::from(value)
+                .ok_or_else(|| format!("{value} is out of range for {}", O::data_type()))
+        }
+
+        self.register_with_writer_2_arg::<NumberType<L>, NumberType<R>, NumberType<O>, _>(
             name,
             property,
-            move |lhs, rhs, generics| {
-                vectorize_passthrough_nullable_2_arg(lhs, rhs, generics, func)
+            |lhs, rhs, output| {
+                let lhs = promote::<_, O>(lhs)?;
+                let rhs = promote::<_, O>(rhs)?;
+                output.push(K::eval(lhs, rhs)?);
+                Ok(())
             },
         );
     }
 
+    /// Registers a decimal arithmetic function. It accepts two arguments where
+    /// at least one is a decimal and the other is a decimal or an integer, and
+    /// infers the precision and scale of the result from the arguments.
+    pub fn register_decimal_arithmetic(&mut self, name: &'static str, op: DecimalOp) {
+        self.register_function_factory(name, move |params, args_type| {
+            if !params.is_empty() || args_type.len() != 2 {
+                return None;
+            }
+            if !args_type.iter().any(|ty| {
+                matches!(
+                    ty.as_nullable().map(Box::as_ref).unwrap_or(ty),
+                    DataType::Decimal { .. }
+                )
+            }) {
+                return None;
+            }
+
+            let nullable = args_type
+                .iter()
+                .any(|ty| ty.is_null() || ty.as_nullable().is_some());
+            let sizes = args_type
+                .iter()
+                .map(|ty| match ty {
+                    DataType::Null => Some(None),
+                    DataType::Nullable(ty) => decimal_size(ty).map(Some),
+                    ty => decimal_size(ty).map(Some),
+                })
+                .collect::<Option<Vec<_>>>()?;
+            // `NULL` takes the size of the other argument.
+            let sizes = [
+                sizes[0].or(sizes[1]).unwrap(),
+                sizes[1].or(sizes[0]).unwrap(),
+            ];
+            let result_size = decimal_op_result_size(op, sizes[0], sizes[1]);
+
+            let wrap_nullable = |(precision, scale): (u8, u8)| {
+                let ty = DataType::Decimal { precision, scale };
+                if nullable {
+                    DataType::Nullable(Box::new(ty))
+                } else {
+                    ty
+                }
+            };
+
+            Some(Arc::new(Function {
+                signature: FunctionSignature {
+                    name,
+                    args_type: sizes.iter().cloned().map(wrap_nullable).collect(),
+                    return_type: wrap_nullable(result_size),
+                    property: FunctionProperty::default().preserve_not_null(true),
+                },
+                eval: Box::new(move |args, ctx| {
+                    vectorize_decimal_op(op, args, &sizes, result_size, nullable, ctx)
+                }),
+            }))
+        });
+    }
+
     pub fn register_with_writer_2_arg<I1: ArgType, I2: ArgType, O: ArgType, F>(
         &mut self,
         name: &'static str,
         property: FunctionProperty,
         func: F,
     ) where
-        F: for<'a, 'b> Fn(I1::ScalarRef<'a>, I2::ScalarRef<'b>, &mut O::ColumnBuilder)
+        F: for<'a, 'b> Fn(
+                I1::ScalarRef<'a>,
+                I2::ScalarRef<'b>,
+                &mut O::ColumnBuilder,
+            ) -> Result<(), String>
             + Sized
             + 'static
             + Clone
@@ -300,27 +651,51 @@ impl FunctionRegistry {
 
         let property = property.preserve_not_null(true);
 
-        self.register_2_arg_core::<NullType, I2, NullType, _>(name, property, move |_, _, _| {
-            Value::Scalar(())
-        });
-        self.register_2_arg_core::<I1, NullType, NullType, _>(name, property, move |_, _, _| {
-            Value::Scalar(())
-        });
-        self.register_2_arg_core::<NullType, NullType, NullType, _>(
-            name,
-            property,
-            move |_, _, _| Value::Scalar(()),
-        );
+        if !self.has_signature(name, &[DataType::Null, I2::data_type()]) {
+            self.register_2_arg_core::<NullType, I2, NullType, _>(
+                name,
+                property,
+                move |_, _, _| Ok(Value::Scalar(())),
+            );
+        }
+        if !self.has_signature(name, &[I1::data_type(), DataType::Null]) {
+            self.register_2_arg_core::<I1, NullType, NullType, _>(
+                name,
+                property,
+                move |_, _, _| Ok(Value::Scalar(())),
+            );
+        }
+        if !self.has_signature(name, &[DataType::Null, DataType::Null]) {
+            self.register_2_arg_core::<NullType, NullType, NullType, _>(
+                name,
+                property,
+                move |_, _, _| Ok(Value::Scalar(())),
+            );
+        }
+        if !self.has_signature(name, &[DataType::Null, NullableType::<I2>::data_type()]) {
+            self.register_2_arg_core::<NullType, NullableType<I2>, NullType, _>(
+                name,
+                property,
+                move |_, _, _| Ok(Value::Scalar(())),
+            );
+        }
+        if !self.has_signature(name, &[NullableType::<I1>::data_type(), DataType::Null]) {
+            self.register_2_arg_core::<NullableType<I1>, NullType, NullType, _>(
+                name,
+                property,
+                move |_, _, _| Ok(Value::Scalar(())),
+            );
+        }
 
-        self.register_2_arg_core::<I1, I2, O, _>(name, property, move |lhs, rhs, generics| {
-            vectorize_with_writer_2_arg(lhs, rhs, generics, func)
+        self.register_2_arg_core::<I1, I2, O, _>(name, property, move |lhs, rhs, ctx| {
+            vectorize_with_writer_2_arg(lhs, rhs, ctx, func)
         });
 
         self.register_2_arg_core::<NullableType<I1>, NullableType<I2>, NullableType<O>, _>(
             name,
             property,
-            move |lhs, rhs, generics| {
-                vectorize_with_writer_passthrough_nullable_2_arg(lhs, rhs, generics, func)
+            move |lhs, rhs, ctx| {
+                vectorize_with_writer_passthrough_nullable_2_arg(lhs, rhs, ctx, func)
             },
         );
     }
@@ -331,24 +706,25 @@ impl FunctionRegistry {
         property: FunctionProperty,
         func: F,
     ) where
-        F: for<'a> Fn(ValueRef<'a, I1>, ValueRef<'a, I2>, &GenericMap) -> Value<O>
+        F: for<'a> Fn(
+                ValueRef<'a, I1>,
+                ValueRef<'a, I2>,
+                &EvalContext,
+            ) -> Result<Value<O>, EvalError>
             + Sized
             + 'static
             + Clone
             + Copy,
     {
-        self.funcs
-            .entry(name)
-            .or_insert_with(Vec::new)
-            .push(Arc::new(Function {
-                signature: FunctionSignature {
-                    name,
-                    args_type: vec![I1::data_type(), I2::data_type()],
-                    return_type: O::data_type(),
-                    property,
-                },
-                eval: Box::new(erase_function_generic_2_arg(func)),
-            }));
+        self.funcs.entry(name).or_default().push(Arc::new(Function {
+            signature: FunctionSignature {
+                name,
+                args_type: vec![I1::data_type(), I2::data_type()],
+                return_type: O::data_type(),
+                property,
+            },
+            eval: Box::new(erase_function_generic_2_arg(func)),
+        }));
     }
 
     pub fn register_function_factory(
@@ -358,112 +734,267 @@ impl FunctionRegistry {
     ) {
         self.factories
             .entry(name)
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(Box::new(factory));
     }
 }
 
-fn erase_function_generic_0_arg<O: ArgType>(
-    func: impl for<'a> Fn(&GenericMap) -> Value<O>,
-) -> impl Fn(&[ValueRef<AnyType>], &GenericMap) -> Value<AnyType> {
-    move |_args, generics| {
-        let result = func(generics);
+fn probe_types<'a>(sig_types: impl Iterator<Item = &'a DataType>) -> Vec<DataType> {
+    let primitives = [
+        DataType::Boolean,
+        DataType::String,
+        DataType::Binary,
+        DataType::UInt8,
+        DataType::UInt16,
+        DataType::UInt32,
+        DataType::UInt64,
+        DataType::Int8,
+        DataType::Int16,
+        DataType::Int32,
+        DataType::Int64,
+        DataType::Float32,
+        DataType::Float64,
+        DataType::Date,
+        DataType::Timestamp,
+        DataType::Interval,
+        DataType::Variant,
+    ];
 
-        match result {
-            Value::Scalar(scalar) => Value::Scalar(O::upcast_scalar(scalar)),
-            Value::Column(col) => Value::Column(O::upcast_column(col)),
+    let mut probes = vec![DataType::Null, DataType::EmptyArray];
+    for ty in &primitives {
+        probes.push(ty.clone());
+        probes.push(DataType::Nullable(Box::new(ty.clone())));
+    }
+    for sig_ty in sig_types {
+        for ty in &primitives {
+            let probe = instantiate_generics(sig_ty, ty);
+            if !probes.contains(&probe) {
+                probes.push(probe);
+            }
         }
     }
+    probes
+}
+
+fn instantiate_generics(ty: &DataType, instance: &DataType) -> DataType {
+    match ty {
+        DataType::Generic(_) => instance.clone(),
+        DataType::Nullable(ty) => DataType::Nullable(Box::new(instantiate_generics(ty, instance))),
+        DataType::Array(ty) => DataType::Array(Box::new(instantiate_generics(ty, instance))),
+        DataType::Map(key, value) => DataType::Map(
+            Box::new(instantiate_generics(key, instance)),
+            Box::new(instantiate_generics(value, instance)),
+        ),
+        DataType::Tuple {
+            fields_name,
+            fields_type,
+        } => DataType::Tuple {
+            fields_name: fields_name.clone(),
+            fields_type: fields_type
+                .iter()
+                .map(|ty| instantiate_generics(ty, instance))
+                .collect(),
+        },
+        ty => ty.clone(),
+    }
+}
+
+fn cartesian_product(sets: &[Vec<DataType>]) -> Vec<Vec<DataType>> {
+    sets.iter().fold(vec![vec![]], |products, set| {
+        products
+            .iter()
+            .flat_map(|product| {
+                set.iter().map(move |ty| {
+                    let mut product = product.clone();
+                    product.push(ty.clone());
+                    product
+                })
+            })
+            .collect()
+    })
+}
+
+fn erase_function_generic_0_arg<O: ArgType>(
+    func: impl for<'a> Fn(&EvalContext) -> Result<Value<O>, EvalError>,
+) -> impl Fn(&[ValueRef<AnyType>], &EvalContext) -> Result<Value<AnyType>, EvalError> {
+    move |_args, ctx| {
+        let result = func(ctx)?;
+
+        Ok(O::upcast_value(result))
+    }
 }
 
 fn erase_function_generic_1_arg<I1: ArgType, O: ArgType>(
-    func: impl for<'a> Fn(ValueRef<'a, I1>, &GenericMap) -> Value<O>,
-) -> impl Fn(&[ValueRef<AnyType>], &GenericMap) -> Value<AnyType> {
-    move |args, generics| {
-        let arg1 = match &args[0] {
-            ValueRef::Scalar(scalar) => ValueRef::Scalar(I1::try_downcast_scalar(scalar).unwrap()),
-            ValueRef::Column(col) => ValueRef::Column(I1::try_downcast_column(col).unwrap()),
-        };
+    func: impl for<'a> Fn(ValueRef<'a, I1>, &EvalContext) -> Result<Value<O>, EvalError>,
+) -> impl Fn(&[ValueRef<AnyType>], &EvalContext) -> Result<Value<AnyType>, EvalError> {
+    move |args, ctx| {
+        let arg1 = downcast_arg::<I1>(&args[0])?;
 
-        let result = func(arg1, generics);
+        let result = func(arg1, ctx)?;
 
-        match result {
-            Value::Scalar(scalar) => Value::Scalar(O::upcast_scalar(scalar)),
-            Value::Column(col) => Value::Column(O::upcast_column(col)),
-        }
+        Ok(broadcast_const(args, O::upcast_value(result)))
     }
 }
 
 fn erase_function_generic_2_arg<I1: ArgType, I2: ArgType, O: ArgType>(
-    func: impl for<'a> Fn(ValueRef<'a, I1>, ValueRef<'a, I2>, &GenericMap) -> Value<O>,
-) -> impl Fn(&[ValueRef<AnyType>], &GenericMap) -> Value<AnyType> {
-    move |args, generics| {
-        let arg1 = match &args[0] {
-            ValueRef::Scalar(scalar) => ValueRef::Scalar(I1::try_downcast_scalar(scalar).unwrap()),
-            ValueRef::Column(col) => ValueRef::Column(I1::try_downcast_column(col).unwrap()),
-        };
-        let arg2 = match &args[1] {
-            ValueRef::Scalar(scalar) => ValueRef::Scalar(I2::try_downcast_scalar(scalar).unwrap()),
-            ValueRef::Column(col) => ValueRef::Column(I2::try_downcast_column(col).unwrap()),
-        };
+    func: impl for<'a> Fn(
+        ValueRef<'a, I1>,
+        ValueRef<'a, I2>,
+        &EvalContext,
+    ) -> Result<Value<O>, EvalError>,
+) -> impl Fn(&[ValueRef<AnyType>], &EvalContext) -> Result<Value<AnyType>, EvalError> {
+    move |args, ctx| {
+        let arg1 = downcast_arg::<I1>(&args[0])?;
+        let arg2 = downcast_arg::<I2>(&args[1])?;
+
+        let result = func(arg1, arg2, ctx)?;
 
-        let result = func(arg1, arg2, generics);
+        Ok(broadcast_const(args, O::upcast_value(result)))
+    }
+}
+
+/// Constant columns reach the typed functions as scalars, so a scalar result
+/// is turned back into a constant column of the arguments' length.
+fn broadcast_const(args: &[ValueRef<AnyType>], result: Value<AnyType>) -> Value<AnyType> {
+    let len = args.iter().find_map(|arg| match arg {
+        ValueRef::Column(col) => Some(col.len()),
+        _ => None,
+    });
+    match (result, len) {
+        (Value::Scalar(scalar), Some(len)) => Value::Column(Column::Const {
+            scalar: Box::new(scalar),
+            len,
+        }),
+        (result, _) => result,
+    }
+}
 
-        match result {
-            Value::Scalar(scalar) => Value::Scalar(O::upcast_scalar(scalar)),
-            Value::Column(col) => Value::Column(O::upcast_column(col)),
+pub fn downcast_arg<'a, T: ArgType>(
+    arg: &'a ValueRef<AnyType>,
+) -> Result<ValueRef<'a, T>, EvalError> {
+    T::try_downcast_value(arg).ok_or_else(|| {
+        EvalError::new(format!(
+            "argument {arg} does not match the expected type {}",
+            T::data_type()
+        ))
+    })
+}
+
+/// Maps the rows of a column with `func`. Under a selection, `func` is only
+/// called for the selected rows, and the hidden rows get the default value.
+fn map_selected<T, O: ArgType>(
+    iter: impl Iterator<Item = T>,
+    ctx: &EvalContext,
+    mut func: impl FnMut(T) -> O::Scalar,
+) -> O::Column {
+    match ctx.selection {
+        None => O::column_from_iter(iter.map(func), ctx.generics),
+        Some(selection) => {
+            let mut builder = O::create_builder(selection.len(), ctx.generics);
+            for (item, selected) in iter.zip(selection) {
+                if selected {
+                    O::push_item(&mut builder, O::to_scalar_ref(&func(item)));
+                } else {
+                    O::push_default(&mut builder);
+                }
+            }
+            O::build_column(builder)
         }
     }
 }
 
 pub fn vectorize_1_arg<'a, I1: ArgType, O: ArgType>(
     val: ValueRef<'a, I1>,
-    generics: &GenericMap,
+    ctx: &EvalContext,
     func: impl Fn(I1::ScalarRef<'_>) -> O::Scalar,
 ) -> Value<O> {
     match val {
         ValueRef::Scalar(val) => Value::Scalar(func(val)),
         ValueRef::Column(col) => {
-            let iter = I1::iter_column(&col).map(func);
-            let col = O::column_from_iter(iter, generics);
+            let col = map_selected::<_, O>(I1::iter_column(&col), ctx, func);
             Value::Column(col)
         }
     }
 }
 
+/// The fast path of `vectorize_1_arg` for dictionary columns: the function is
+/// evaluated once per dictionary entry instead of once per row, and the result
+/// is encoded with the same keys.
+///
+/// Under a selection, only the entries referenced by the selected rows are
+/// evaluated.
+fn vectorize_dictionary_1_arg(
+    eval: impl Fn(&[ValueRef<AnyType>], &EvalContext) -> Result<Value<AnyType>, EvalError>,
+) -> impl Fn(&[ValueRef<AnyType>], &EvalContext) -> Result<Value<AnyType>, EvalError> {
+    move |args, ctx| match &args[0] {
+        ValueRef::Column(Column::Dictionary { keys, values }) => {
+            let selection = ctx.selection.map(|selection| {
+                let mut referenced = constant_bitmap(false, values.len());
+                for (key, selected) in keys.iter().zip(selection) {
+                    if selected {
+                        referenced.set(*key as usize, true);
+                    }
+                }
+                Bitmap::from(referenced)
+            });
+            let values_ctx = EvalContext {
+                generics: ctx.generics,
+                selection: selection.as_ref(),
+            };
+            let values = match eval(&[ValueRef::Column((**values).clone())], &values_ctx)? {
+                Value::Scalar(scalar) => scalar.as_ref().repeat(values.len()).build(),
+                Value::Column(col) => col,
+            };
+            Ok(Value::Column(Column::Dictionary {
+                keys: keys.clone(),
+                values: Box::new(values),
+            }))
+        }
+        _ => eval(args, ctx),
+    }
+}
+
 pub fn vectorize_with_writer_1_arg<'a, I1: ArgType, O: ArgType>(
     val: ValueRef<'a, I1>,
-    generics: &GenericMap,
-    func: impl Fn(I1::ScalarRef<'_>, &mut O::ColumnBuilder),
-) -> Value<O> {
+    ctx: &EvalContext,
+    func: impl Fn(I1::ScalarRef<'_>, &mut O::ColumnBuilder) -> Result<(), String>,
+) -> Result<Value<O>, EvalError> {
     match val {
         ValueRef::Scalar(val) => {
-            let mut builder = O::create_builder(1, generics);
-            func(val, &mut builder);
-            Value::Scalar(O::build_scalar(builder))
+            let mut builder = O::create_builder(1, ctx.generics);
+            func(val.clone(), &mut builder)
+                .map_err(|msg| EvalError::new(msg).value(I1::display_scalar(&val)))?;
+            Ok(Value::Scalar(O::build_scalar(builder)))
         }
         ValueRef::Column(col) => {
             let iter = I1::iter_column(&col);
-            let mut builder = O::create_builder(iter.size_hint().0, generics);
-            for val in I1::iter_column(&col) {
-                func(val, &mut builder);
+            let mut builder = O::create_builder(iter.size_hint().0, ctx.generics);
+            for (row, val) in iter.enumerate() {
+                if !ctx.is_selected(row) {
+                    O::push_default(&mut builder);
+                    continue;
+                }
+                func(val, &mut builder).map_err(|msg| {
+                    EvalError::new(msg)
+                        .row(row)
+                        .value(I1::display_scalar(&I1::index_column(&col, row)))
+                })?;
             }
-            Value::Column(O::build_column(builder))
+            Ok(Value::Column(O::build_column(builder)))
         }
     }
 }
 
 pub fn vectorize_passthrough_nullable_1_arg<'a, I1: ArgType, O: ArgType>(
     val: ValueRef<'a, NullableType<I1>>,
-    generics: &GenericMap,
+    ctx: &EvalContext,
     func: impl for<'for_a> Fn(I1::ScalarRef<'for_a>) -> O::Scalar,
 ) -> Value<NullableType<O>> {
     match val {
         ValueRef::Scalar(None) => Value::Scalar(None),
         ValueRef::Scalar(Some(val)) => Value::Scalar(Some(func(val))),
         ValueRef::Column((col, validity)) => {
-            let iter = I1::iter_column(&col).map(func);
-            let col = O::column_from_iter(iter, generics);
+            let col = map_selected::<_, O>(I1::iter_column(&col), ctx, func);
             Value::Column((col, validity))
         }
     }
@@ -471,23 +1002,32 @@ pub fn vectorize_passthrough_nullable_1_arg<'a, I1: ArgType, O: ArgType>(
 
 pub fn vectorize_with_writer_passthrough_nullable_1_arg<'a, I1: ArgType, O: ArgType>(
     val: ValueRef<'a, NullableType<I1>>,
-    generics: &GenericMap,
-    func: impl Fn(I1::ScalarRef<'_>, &mut O::ColumnBuilder),
-) -> Value<NullableType<O>> {
+    ctx: &EvalContext,
+    func: impl Fn(I1::ScalarRef<'_>, &mut O::ColumnBuilder) -> Result<(), String>,
+) -> Result<Value<NullableType<O>>, EvalError> {
     match val {
-        ValueRef::Scalar(None) => Value::Scalar(None),
+        ValueRef::Scalar(None) => Ok(Value::Scalar(None)),
         ValueRef::Scalar(Some(val)) => {
-            let mut builder = O::create_builder(1, generics);
-            func(val, &mut builder);
-            Value::Scalar(Some(O::build_scalar(builder)))
+            let mut builder = O::create_builder(1, ctx.generics);
+            func(val.clone(), &mut builder)
+                .map_err(|msg| EvalError::new(msg).value(I1::display_scalar(&val)))?;
+            Ok(Value::Scalar(Some(O::build_scalar(builder))))
         }
         ValueRef::Column((col, validity)) => {
-            let iter = I1::iter_column(&col);
-            let mut builder = O::create_builder(iter.size_hint().0, generics);
-            for val in I1::iter_column(&col) {
-                func(val, &mut builder);
+            let iter = I1::iter_column(&col).zip(&validity);
+            let mut builder = O::create_builder(iter.size_hint().0, ctx.generics);
+            for (row, (val, valid)) in iter.enumerate() {
+                if valid && ctx.is_selected(row) {
+                    func(val, &mut builder).map_err(|msg| {
+                        EvalError::new(msg)
+                            .row(row)
+                            .value(I1::display_scalar(&I1::index_column(&col, row)))
+                    })?;
+                } else {
+                    O::push_default(&mut builder);
+                }
             }
-            Value::Column((O::build_column(builder), validity))
+            Ok(Value::Column((O::build_column(builder), validity)))
         }
     }
 }
@@ -495,26 +1035,24 @@ pub fn vectorize_with_writer_passthrough_nullable_1_arg<'a, I1: ArgType, O: ArgT
 pub fn vectorize_2_arg<'a, 'b, I1: ArgType, I2: ArgType, O: ArgType>(
     lhs: ValueRef<'a, I1>,
     rhs: ValueRef<'b, I2>,
-    generics: &GenericMap,
+    ctx: &EvalContext,
     func: impl Fn(I1::ScalarRef<'_>, I2::ScalarRef<'_>) -> O::Scalar,
 ) -> Value<O> {
     match (lhs, rhs) {
         (ValueRef::Scalar(lhs), ValueRef::Scalar(rhs)) => Value::Scalar(func(lhs, rhs)),
         (ValueRef::Scalar(lhs), ValueRef::Column(rhs)) => {
-            let iter = I2::iter_column(&rhs).map(|rhs| func(lhs.clone(), rhs));
-            let col = O::column_from_iter(iter, generics);
+            let col =
+                map_selected::<_, O>(I2::iter_column(&rhs), ctx, |rhs| func(lhs.clone(), rhs));
             Value::Column(col)
         }
         (ValueRef::Column(lhs), ValueRef::Scalar(rhs)) => {
-            let iter = I1::iter_column(&lhs).map(|lhs| func(lhs, rhs.clone()));
-            let col = O::column_from_iter(iter, generics);
+            let col =
+                map_selected::<_, O>(I1::iter_column(&lhs), ctx, |lhs| func(lhs, rhs.clone()));
             Value::Column(col)
         }
         (ValueRef::Column(lhs), ValueRef::Column(rhs)) => {
-            let iter = I1::iter_column(&lhs)
-                .zip(I2::iter_column(&rhs))
-                .map(|(lhs, rhs)| func(lhs, rhs));
-            let col = O::column_from_iter(iter, generics);
+            let iter = I1::iter_column(&lhs).zip(I2::iter_column(&rhs));
+            let col = map_selected::<_, O>(iter, ctx, |(lhs, rhs)| func(lhs, rhs));
             Value::Column(col)
         }
     }
@@ -523,38 +1061,74 @@ pub fn vectorize_2_arg<'a, 'b, I1: ArgType, I2: ArgType, O: ArgType>(
 pub fn vectorize_with_writer_2_arg<'a, 'b, I1: ArgType, I2: ArgType, O: ArgType>(
     lhs: ValueRef<'a, I1>,
     rhs: ValueRef<'b, I2>,
-    generics: &GenericMap,
-    func: impl Fn(I1::ScalarRef<'_>, I2::ScalarRef<'_>, &mut O::ColumnBuilder),
-) -> Value<O> {
+    ctx: &EvalContext,
+    func: impl Fn(I1::ScalarRef<'_>, I2::ScalarRef<'_>, &mut O::ColumnBuilder) -> Result<(), String>,
+) -> Result<Value<O>, EvalError> {
     match (lhs, rhs) {
         (ValueRef::Scalar(lhs), ValueRef::Scalar(rhs)) => {
-            let mut builder = O::create_builder(1, generics);
-            func(lhs, rhs, &mut builder);
-            Value::Scalar(O::build_scalar(builder))
+            let mut builder = O::create_builder(1, ctx.generics);
+            func(lhs.clone(), rhs.clone(), &mut builder).map_err(|msg| {
+                EvalError::new(msg).value(format!(
+                    "({}, {})",
+                    I1::display_scalar(&lhs),
+                    I2::display_scalar(&rhs)
+                ))
+            })?;
+            Ok(Value::Scalar(O::build_scalar(builder)))
         }
         (ValueRef::Scalar(lhs), ValueRef::Column(rhs)) => {
             let iter = I2::iter_column(&rhs);
-            let mut builder = O::create_builder(iter.size_hint().0, generics);
-            for rhs in iter {
-                func(lhs.clone(), rhs, &mut builder);
+            let mut builder = O::create_builder(iter.size_hint().0, ctx.generics);
+            for (row, val) in iter.enumerate() {
+                if !ctx.is_selected(row) {
+                    O::push_default(&mut builder);
+                    continue;
+                }
+                func(lhs.clone(), val, &mut builder).map_err(|msg| {
+                    EvalError::new(msg).row(row).value(format!(
+                        "({}, {})",
+                        I1::display_scalar(&lhs),
+                        I2::display_scalar(&I2::index_column(&rhs, row))
+                    ))
+                })?;
             }
-            Value::Column(O::build_column(builder))
+            Ok(Value::Column(O::build_column(builder)))
         }
         (ValueRef::Column(lhs), ValueRef::Scalar(rhs)) => {
             let iter = I1::iter_column(&lhs);
-            let mut builder = O::create_builder(iter.size_hint().0, generics);
-            for lhs in iter {
-                func(lhs, rhs.clone(), &mut builder);
+            let mut builder = O::create_builder(iter.size_hint().0, ctx.generics);
+            for (row, val) in iter.enumerate() {
+                if !ctx.is_selected(row) {
+                    O::push_default(&mut builder);
+                    continue;
+                }
+                func(val, rhs.clone(), &mut builder).map_err(|msg| {
+                    EvalError::new(msg).row(row).value(format!(
+                        "({}, {})",
+                        I1::display_scalar(&I1::index_column(&lhs, row)),
+                        I2::display_scalar(&rhs)
+                    ))
+                })?;
             }
-            Value::Column(O::build_column(builder))
+            Ok(Value::Column(O::build_column(builder)))
         }
         (ValueRef::Column(lhs), ValueRef::Column(rhs)) => {
             let iter = I1::iter_column(&lhs).zip(I2::iter_column(&rhs));
-            let mut builder = O::create_builder(iter.size_hint().0, generics);
-            for (lhs, rhs) in iter {
-                func(lhs, rhs, &mut builder);
+            let mut builder = O::create_builder(iter.size_hint().0, ctx.generics);
+            for (row, (lhs_val, rhs_val)) in iter.enumerate() {
+                if !ctx.is_selected(row) {
+                    O::push_default(&mut builder);
+                    continue;
+                }
+                func(lhs_val, rhs_val, &mut builder).map_err(|msg| {
+                    EvalError::new(msg).row(row).value(format!(
+                        "({}, {})",
+                        I1::display_scalar(&I1::index_column(&lhs, row)),
+                        I2::display_scalar(&I2::index_column(&rhs, row))
+                    ))
+                })?;
             }
-            Value::Column(O::build_column(builder))
+            Ok(Value::Column(O::build_column(builder)))
         }
     }
 }
@@ -562,7 +1136,7 @@ pub fn vectorize_with_writer_2_arg<'a, 'b, I1: ArgType, I2: ArgType, O: ArgType>
 pub fn vectorize_passthrough_nullable_2_arg<'a, 'b, I1: ArgType, I2: ArgType, O: ArgType>(
     lhs: ValueRef<'a, NullableType<I1>>,
     rhs: ValueRef<'b, NullableType<I2>>,
-    generics: &GenericMap,
+    ctx: &EvalContext,
     func: impl Fn(I1::ScalarRef<'_>, I2::ScalarRef<'_>) -> O::Scalar,
 ) -> Value<NullableType<O>> {
     match (lhs, rhs) {
@@ -571,21 +1145,19 @@ pub fn vectorize_passthrough_nullable_2_arg<'a, 'b, I1: ArgType, I2: ArgType, O:
             Value::Scalar(Some(func(lhs, rhs)))
         }
         (ValueRef::Scalar(Some(lhs)), ValueRef::Column((rhs, rhs_validity))) => {
-            let iter = I2::iter_column(&rhs).map(|rhs| func(lhs.clone(), rhs));
-            let col = O::column_from_iter(iter, generics);
+            let col =
+                map_selected::<_, O>(I2::iter_column(&rhs), ctx, |rhs| func(lhs.clone(), rhs));
             Value::Column((col, rhs_validity))
         }
         (ValueRef::Column((lhs, lhs_validity)), ValueRef::Scalar(Some(rhs))) => {
-            let iter = I1::iter_column(&lhs).map(|lhs| func(lhs, rhs.clone()));
-            let col = O::column_from_iter(iter, generics);
+            let col =
+                map_selected::<_, O>(I1::iter_column(&lhs), ctx, |lhs| func(lhs, rhs.clone()));
             Value::Column((col, lhs_validity))
         }
         (ValueRef::Column((lhs, lhs_validity)), ValueRef::Column((rhs, rhs_validity))) => {
-            let iter = I1::iter_column(&lhs)
-                .zip(I2::iter_column(&rhs))
-                .map(|(lhs, rhs)| func(lhs, rhs));
-            let col = O::column_from_iter(iter, generics);
-            let validity = arrow2::bitmap::or(&lhs_validity, &rhs_validity);
+            let iter = I1::iter_column(&lhs).zip(I2::iter_column(&rhs));
+            let col = map_selected::<_, O>(iter, ctx, |(lhs, rhs)| func(lhs, rhs));
+            let validity = arrow2::bitmap::and(&lhs_validity, &rhs_validity);
             Value::Column((col, validity))
         }
     }
@@ -600,54 +1172,79 @@ pub fn vectorize_with_writer_passthrough_nullable_2_arg<
 >(
     lhs: ValueRef<'a, NullableType<I1>>,
     rhs: ValueRef<'b, NullableType<I2>>,
-    generics: &GenericMap,
-    func: impl Fn(I1::ScalarRef<'_>, I2::ScalarRef<'_>, &mut O::ColumnBuilder),
-) -> Value<NullableType<O>> {
+    ctx: &EvalContext,
+    func: impl Fn(I1::ScalarRef<'_>, I2::ScalarRef<'_>, &mut O::ColumnBuilder) -> Result<(), String>,
+) -> Result<Value<NullableType<O>>, EvalError> {
     match (lhs, rhs) {
-        (ValueRef::Scalar(None), _) | (_, ValueRef::Scalar(None)) => Value::Scalar(None),
+        (ValueRef::Scalar(None), _) | (_, ValueRef::Scalar(None)) => Ok(Value::Scalar(None)),
         (ValueRef::Scalar(Some(lhs)), ValueRef::Scalar(Some(rhs))) => {
-            let mut builder = O::create_builder(1, generics);
-            func(lhs, rhs, &mut builder);
-            Value::Scalar(Some(O::build_scalar(builder)))
+            let mut builder = O::create_builder(1, ctx.generics);
+            func(lhs.clone(), rhs.clone(), &mut builder).map_err(|msg| {
+                EvalError::new(msg).value(format!(
+                    "({}, {})",
+                    I1::display_scalar(&lhs),
+                    I2::display_scalar(&rhs)
+                ))
+            })?;
+            Ok(Value::Scalar(Some(O::build_scalar(builder))))
         }
         (ValueRef::Scalar(Some(lhs)), ValueRef::Column((rhs, rhs_validity))) => {
             let iter = I2::iter_column(&rhs).zip(&rhs_validity);
-            let mut builder = O::create_builder(iter.size_hint().0, generics);
-            for (rhs, rhs_validity) in iter {
-                if rhs_validity {
-                    func(lhs.clone(), rhs, &mut builder);
+            let mut builder = O::create_builder(iter.size_hint().0, ctx.generics);
+            for (row, (rhs_val, rhs_valid)) in iter.enumerate() {
+                if rhs_valid && ctx.is_selected(row) {
+                    func(lhs.clone(), rhs_val, &mut builder).map_err(|msg| {
+                        EvalError::new(msg).row(row).value(format!(
+                            "({}, {})",
+                            I1::display_scalar(&lhs),
+                            I2::display_scalar(&I2::index_column(&rhs, row))
+                        ))
+                    })?;
                 } else {
                     O::push_default(&mut builder);
                 }
             }
-            Value::Column((O::build_column(builder), rhs_validity))
+            Ok(Value::Column((O::build_column(builder), rhs_validity)))
         }
         (ValueRef::Column((lhs, lhs_validity)), ValueRef::Scalar(Some(rhs))) => {
             let iter = I1::iter_column(&lhs).zip(&lhs_validity);
-            let mut builder = O::create_builder(iter.size_hint().0, generics);
-            for (lhs, lhs_validity) in iter {
-                if lhs_validity {
-                    func(lhs, rhs.clone(), &mut builder);
+            let mut builder = O::create_builder(iter.size_hint().0, ctx.generics);
+            for (row, (lhs_val, lhs_valid)) in iter.enumerate() {
+                if lhs_valid && ctx.is_selected(row) {
+                    func(lhs_val, rhs.clone(), &mut builder).map_err(|msg| {
+                        EvalError::new(msg).row(row).value(format!(
+                            "({}, {})",
+                            I1::display_scalar(&I1::index_column(&lhs, row)),
+                            I2::display_scalar(&rhs)
+                        ))
+                    })?;
                 } else {
                     O::push_default(&mut builder);
                 }
             }
-            Value::Column((O::build_column(builder), lhs_validity))
+            Ok(Value::Column((O::build_column(builder), lhs_validity)))
         }
         (ValueRef::Column((lhs, lhs_validity)), ValueRef::Column((rhs, rhs_validity))) => {
             let iter = I1::iter_column(&lhs)
                 .zip(&lhs_validity)
                 .zip(I2::iter_column(&rhs))
                 .zip(&rhs_validity);
-            let mut builder = O::create_builder(iter.size_hint().0, generics);
-            for (((lhs, lhs_validity), rhs), rhs_validity) in iter {
-                if lhs_validity && rhs_validity {
-                    func(lhs, rhs, &mut builder);
+            let mut builder = O::create_builder(iter.size_hint().0, ctx.generics);
+            for (row, (((lhs_val, lhs_valid), rhs_val), rhs_valid)) in iter.enumerate() {
+                if lhs_valid && rhs_valid && ctx.is_selected(row) {
+                    func(lhs_val, rhs_val, &mut builder).map_err(|msg| {
+                        EvalError::new(msg).row(row).value(format!(
+                            "({}, {})",
+                            I1::display_scalar(&I1::index_column(&lhs, row)),
+                            I2::display_scalar(&I2::index_column(&rhs, row))
+                        ))
+                    })?;
                 } else {
                     O::push_default(&mut builder);
                 }
             }
-            Value::Column((O::build_column(builder), lhs_validity))
+            let validity = arrow2::bitmap::and(&lhs_validity, &rhs_validity);
+            Ok(Value::Column((O::build_column(builder), validity)))
         }
     }
 }
@@ -0,0 +1,107 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    io::{Read, Seek, Write},
+    sync::Arc,
+};
+
+use arrow2::{
+    array::{new_empty_array, Array},
+    chunk::Chunk,
+    datatypes::{DataType as ArrowDataType, Field, Schema},
+    io::ipc::{
+        read::{read_file_metadata, FileReader},
+        write::{FileWriter, WriteOptions},
+    },
+};
+
+use crate::{
+    arrow::{accepts_null, data_type_from_field},
+    values::Column,
+};
+
+/// Reads an Arrow IPC file into columns named by the fields of its schema.
+/// The record batches of the file are concatenated into one column per field.
+/// Variant values and decimal types are validated like in `Column::from_arrow`.
+pub fn read_ipc(reader: &mut (impl Read + Seek)) -> Result<HashMap<String, Column>, String> {
+    let metadata = read_file_metadata(reader).map_err(|err| err.to_string())?;
+    let fields = metadata.schema.fields.clone();
+    let types = fields
+        .iter()
+        .map(data_type_from_field)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut chunks = vec![Vec::new(); fields.len()];
+    for chunk in FileReader::new(reader, metadata, None) {
+        let chunk = chunk.map_err(|err| err.to_string())?;
+        for ((array, ty), columns) in chunk.arrays().iter().zip(&types).zip(&mut chunks) {
+            columns.push(Column::from_arrow(array.as_ref(), ty)?);
+        }
+    }
+
+    fields
+        .into_iter()
+        .zip(types)
+        .zip(chunks)
+        .map(|((field, ty), columns)| {
+            let column = if columns.is_empty() {
+                Column::from_arrow(new_empty_array(ty.to_arrow()).as_ref(), &ty)?
+            } else {
+                Column::concat(&columns)
+            };
+            Ok((field.name, column))
+        })
+        .collect()
+}
+
+/// Writes the columns as a single record batch of an Arrow IPC file. The
+/// columns must have the same length. Tuple fields are named by their
+/// position, since columns do not carry the field names.
+pub fn write_ipc(writer: impl Write, columns: &[(&str, Column)]) -> Result<(), String> {
+    if let Some((_, first)) = columns.first() {
+        if let Some((name, _)) = columns.iter().find(|(_, col)| col.len() != first.len()) {
+            return Err(format!(
+                "column `{name}` has a different length from the other columns"
+            ));
+        }
+    }
+
+    let arrays = columns
+        .iter()
+        .map(|(_, col)| Arc::from(ipc_column(col).to_arrow()))
+        .collect::<Vec<Arc<dyn Array>>>();
+    let schema = Schema::from(
+        columns
+            .iter()
+            .zip(&arrays)
+            .map(|((name, col), array)| {
+                Field::new(
+                    *name,
+                    array.data_type().clone(),
+                    accepts_null(&col.data_type()),
+                )
+            })
+            .collect::<Vec<_>>(),
+    );
+    let chunk = Chunk::new(arrays);
+
+    let mut writer = FileWriter::try_new(writer, &schema, None, WriteOptions { compression: None })
+        .map_err(|err| err.to_string())?;
+    writer.write(&chunk, None).map_err(|err| err.to_string())?;
+    writer.finish().map_err(|err| err.to_string())
+}
+
+/// arrow2 can not write dictionaries of nested values, so those are decoded.
+fn ipc_column(col: &Column) -> Cow<'_, Column> {
+    match col {
+        Column::Dictionary { values, .. }
+            if matches!(
+                values.data_type().to_arrow(),
+                ArrowDataType::LargeList(_) | ArrowDataType::Map(_, _) | ArrowDataType::Struct(_)
+            ) =>
+        {
+            col.flatten()
+        }
+        col => Cow::Borrowed(col),
+    }
+}
@@ -0,0 +1,384 @@
+use arrow2::{
+    bitmap::{Bitmap, MutableBitmap},
+    buffer::Buffer,
+    types::NativeType,
+};
+
+use crate::{
+    type_check::common_super_type,
+    types::DataType,
+    values::{Column, ColumnBuilder, Scalar},
+};
+
+impl Column {
+    /// Keeps the rows whose bit is set in `filter`.
+    pub fn filter(&self, filter: &Bitmap) -> Column {
+        assert_eq!(
+            filter.len(),
+            self.len(),
+            "filter of len {} on column of len {}",
+            filter.len(),
+            self.len()
+        );
+        if filter.null_count() == 0 {
+            return self.clone();
+        }
+        let indices = filter
+            .iter()
+            .enumerate()
+            .filter(|(_, keep)| *keep)
+            .map(|(row, _)| row as u32)
+            .collect::<Vec<_>>();
+        self.take(&indices)
+    }
+
+    /// Gathers the rows at `indices`, which may repeat or be in any order.
+    pub fn take(&self, indices: &[u32]) -> Column {
+        match self {
+            Column::Null { .. } => Column::Null { len: indices.len() },
+            Column::EmptyArray { .. } => Column::EmptyArray { len: indices.len() },
+            Column::Int8(col) => Column::Int8(take_primitive(col, indices)),
+            Column::Int16(col) => Column::Int16(take_primitive(col, indices)),
+            Column::Int32(col) => Column::Int32(take_primitive(col, indices)),
+            Column::Int64(col) => Column::Int64(take_primitive(col, indices)),
+            Column::Date(col) => Column::Date(take_primitive(col, indices)),
+            Column::Timestamp(col) => Column::Timestamp(take_primitive(col, indices)),
+            Column::Interval(col) => Column::Interval(take_primitive(col, indices)),
+            Column::Float32(col) => Column::Float32(take_primitive(col, indices)),
+            Column::Float64(col) => Column::Float64(take_primitive(col, indices)),
+            Column::Decimal {
+                values,
+                precision,
+                scale,
+            } => Column::Decimal {
+                values: take_primitive(values, indices),
+                precision: *precision,
+                scale: *scale,
+            },
+            Column::UInt8(col) => Column::UInt8(take_primitive(col, indices)),
+            Column::UInt16(col) => Column::UInt16(take_primitive(col, indices)),
+            Column::UInt32(col) => Column::UInt32(take_primitive(col, indices)),
+            Column::UInt64(col) => Column::UInt64(take_primitive(col, indices)),
+            Column::Boolean(col) => Column::Boolean(take_bitmap(col, indices)),
+            Column::String { data, offsets } => {
+                let (data, offsets) = take_bytes(data, offsets, indices);
+                Column::String { data, offsets }
+            }
+            Column::Binary { data, offsets } => {
+                let (data, offsets) = take_bytes(data, offsets, indices);
+                Column::Binary { data, offsets }
+            }
+            Column::Variant { data, offsets } => {
+                let (data, offsets) = take_bytes(data, offsets, indices);
+                Column::Variant { data, offsets }
+            }
+            Column::Array { array, offsets } => {
+                let mut items = Vec::new();
+                let mut new_offsets = Vec::with_capacity(indices.len() + 1);
+                new_offsets.push(0);
+                for index in indices {
+                    let index = *index as usize;
+                    items.extend(offsets[index] as u32..offsets[index + 1] as u32);
+                    new_offsets.push(items.len());
+                }
+                Column::Array {
+                    array: Box::new(array.take(&items)),
+                    offsets: new_offsets,
+                }
+            }
+            Column::Nullable { column, validity } => Column::Nullable {
+                column: Box::new(column.take(indices)),
+                validity: take_bitmap(validity, indices),
+            },
+            Column::Tuple { fields, .. } => Column::Tuple {
+                fields: fields.iter().map(|field| field.take(indices)).collect(),
+                len: indices.len(),
+            },
+            Column::Dictionary { keys, values } => Column::Dictionary {
+                keys: take_primitive(keys, indices),
+                values: values.clone(),
+            },
+            Column::Const { scalar, .. } => Column::Const {
+                scalar: scalar.clone(),
+                len: indices.len(),
+            },
+        }
+    }
+
+    /// Appends the columns one after another. They must have the same data
+    /// type.
+    pub fn concat(columns: &[Column]) -> Column {
+        assert!(!columns.is_empty(), "can not concat zero columns");
+        if columns.len() == 1 {
+            return columns[0].clone();
+        }
+        let len = columns.iter().map(Column::len).sum();
+
+        // Keep the encoding if all the columns share it, otherwise decode them.
+        if let Some(column) = concat_encoded(columns, len) {
+            return column;
+        }
+        if columns
+            .iter()
+            .any(|col| matches!(col, Column::Dictionary { .. } | Column::Const { .. }))
+        {
+            let ty = concat_data_type(columns);
+            let columns = columns
+                .iter()
+                .map(|col| match col {
+                    Column::Const { scalar, len } => {
+                        let mut builder = ColumnBuilder::with_capacity(&ty, *len);
+                        for _ in 0..*len {
+                            builder.push(Scalar::as_ref(scalar));
+                        }
+                        builder.build()
+                    }
+                    col => col.flatten().into_owned(),
+                })
+                .collect::<Vec<_>>();
+            return Column::concat(&columns);
+        }
+
+        match &columns[0] {
+            Column::Null { .. } => Column::Null { len },
+            Column::EmptyArray { .. } => Column::EmptyArray { len },
+            Column::Int8(_) => Column::Int8(concat_primitive(columns, len, Column::as_int8)),
+            Column::Int16(_) => Column::Int16(concat_primitive(columns, len, Column::as_int16)),
+            Column::Int32(_) => Column::Int32(concat_primitive(columns, len, Column::as_int32)),
+            Column::Int64(_) => Column::Int64(concat_primitive(columns, len, Column::as_int64)),
+            Column::Date(_) => Column::Date(concat_primitive(columns, len, Column::as_date)),
+            Column::Timestamp(_) => {
+                Column::Timestamp(concat_primitive(columns, len, Column::as_timestamp))
+            }
+            Column::Interval(_) => {
+                Column::Interval(concat_primitive(columns, len, Column::as_interval))
+            }
+            Column::Float32(_) => {
+                Column::Float32(concat_primitive(columns, len, Column::as_float32))
+            }
+            Column::Float64(_) => {
+                Column::Float64(concat_primitive(columns, len, Column::as_float64))
+            }
+            Column::Decimal {
+                precision, scale, ..
+            } => Column::Decimal {
+                values: concat_primitive(columns, len, |col| col.as_decimal().map(|(v, _, _)| v)),
+                precision: *precision,
+                scale: *scale,
+            },
+            Column::UInt8(_) => Column::UInt8(concat_primitive(columns, len, Column::as_u_int8)),
+            Column::UInt16(_) => Column::UInt16(concat_primitive(columns, len, Column::as_u_int16)),
+            Column::UInt32(_) => Column::UInt32(concat_primitive(columns, len, Column::as_u_int32)),
+            Column::UInt64(_) => Column::UInt64(concat_primitive(columns, len, Column::as_u_int64)),
+            Column::Boolean(_) => Column::Boolean(concat_bitmap(columns, len, Column::as_boolean)),
+            Column::String { .. } => {
+                let (data, offsets) = concat_bytes(columns, len, Column::as_string);
+                Column::String { data, offsets }
+            }
+            Column::Binary { .. } => {
+                let (data, offsets) = concat_bytes(columns, len, Column::as_binary);
+                Column::Binary { data, offsets }
+            }
+            Column::Variant { .. } => {
+                let (data, offsets) = concat_bytes(columns, len, Column::as_variant);
+                Column::Variant { data, offsets }
+            }
+            Column::Array { .. } => {
+                let mut arrays = Vec::with_capacity(columns.len());
+                let mut new_offsets = Vec::with_capacity(len + 1);
+                new_offsets.push(0);
+                for col in columns {
+                    let (array, offsets) = col.as_array().unwrap_or_else(|| mismatch());
+                    let (start, end) = (offsets[0], offsets[offsets.len() - 1]);
+                    let base = new_offsets[new_offsets.len() - 1];
+                    new_offsets.extend(offsets[1..].iter().map(|offset| offset - start + base));
+                    arrays.push(array.slice(start..end));
+                }
+                Column::Array {
+                    array: Box::new(Column::concat(&arrays)),
+                    offsets: new_offsets,
+                }
+            }
+            Column::Nullable { .. } => {
+                let inner = columns
+                    .iter()
+                    .map(|col| {
+                        let (column, _) = col.as_nullable().unwrap_or_else(|| mismatch());
+                        (**column).clone()
+                    })
+                    .collect::<Vec<_>>();
+                Column::Nullable {
+                    column: Box::new(Column::concat(&inner)),
+                    validity: concat_bitmap(columns, len, |col| col.as_nullable().map(|(_, v)| v)),
+                }
+            }
+            Column::Tuple { fields, .. } => {
+                let fields = (0..fields.len())
+                    .map(|idx| {
+                        let field = columns
+                            .iter()
+                            .map(|col| {
+                                let (fields, _) = col.as_tuple().unwrap_or_else(|| mismatch());
+                                fields[idx].clone()
+                            })
+                            .collect::<Vec<_>>();
+                        Column::concat(&field)
+                    })
+                    .collect();
+                Column::Tuple { fields, len }
+            }
+            Column::Dictionary { .. } | Column::Const { .. } => unreachable!(),
+        }
+    }
+
+    /// Distributes the rows into `scatter_size` columns, sending row `i` to
+    /// the column `indices[i]`. The rows keep their order within a column.
+    ///
+    /// Panics if `indices` is not as long as the column, or if an index is not
+    /// less than `scatter_size`.
+    pub fn scatter(&self, indices: &[u32], scatter_size: usize) -> Vec<Column> {
+        assert_eq!(
+            indices.len(),
+            self.len(),
+            "scatter indices of len {} on column of len {}",
+            indices.len(),
+            self.len()
+        );
+        let mut rows = vec![Vec::new(); scatter_size];
+        for (row, index) in indices.iter().enumerate() {
+            assert!(
+                (*index as usize) < scatter_size,
+                "scatter index {index} at row {row} is out of bounds for scatter size {scatter_size}"
+            );
+            rows[*index as usize].push(row as u32);
+        }
+        rows.iter().map(|rows| self.take(rows)).collect()
+    }
+}
+
+/// Concatenates dictionary columns by rebasing the keys onto the concatenated
+/// values, and constant columns of the same scalar by adding up the lengths.
+fn concat_encoded(columns: &[Column], len: usize) -> Option<Column> {
+    match &columns[0] {
+        Column::Dictionary { .. } => {
+            let mut keys = Vec::with_capacity(len);
+            let mut values = Vec::with_capacity(columns.len());
+            for col in columns {
+                let (col_keys, col_values) = col.as_dictionary()?;
+                let base = values.iter().map(Column::len).sum::<usize>() as u32;
+                keys.extend(col_keys.iter().map(|key| key + base));
+                values.push((**col_values).clone());
+            }
+            Some(Column::Dictionary {
+                keys: keys.into(),
+                values: Box::new(Column::concat(&values)),
+            })
+        }
+        Column::Const { scalar, .. } => {
+            let all_same = columns.iter().all(|col| match col {
+                Column::Const { scalar: other, .. } => {
+                    Scalar::as_ref(other) == Scalar::as_ref(scalar)
+                }
+                _ => false,
+            });
+            all_same.then(|| Column::Const {
+                scalar: scalar.clone(),
+                len,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The data type of the concatenated columns. A constant column only knows
+/// the type of its scalar, which is `Null` for a nullable column.
+fn concat_data_type(columns: &[Column]) -> DataType {
+    if let Some(col) = columns
+        .iter()
+        .find(|col| !matches!(col, Column::Const { .. }))
+    {
+        return col.data_type();
+    }
+    columns
+        .iter()
+        .filter_map(|col| col.as_const())
+        .map(|(scalar, _)| Scalar::as_ref(scalar).repeat(0).build().data_type())
+        .reduce(|ty1, ty2| common_super_type(ty1, ty2).unwrap_or_else(|| mismatch()))
+        .unwrap_or(DataType::Null)
+}
+
+fn mismatch() -> ! {
+    panic!("can not concat columns of different types")
+}
+
+fn take_primitive<T: NativeType>(col: &Buffer<T>, indices: &[u32]) -> Buffer<T> {
+    indices
+        .iter()
+        .map(|index| col[*index as usize])
+        .collect::<Vec<_>>()
+        .into()
+}
+
+fn take_bitmap(bitmap: &Bitmap, indices: &[u32]) -> Bitmap {
+    indices
+        .iter()
+        .map(|index| bitmap.get_bit(*index as usize))
+        .collect::<MutableBitmap>()
+        .into()
+}
+
+fn take_bytes(data: &Buffer<u8>, offsets: &[usize], indices: &[u32]) -> (Buffer<u8>, Vec<usize>) {
+    let mut new_data = Vec::new();
+    let mut new_offsets = Vec::with_capacity(indices.len() + 1);
+    new_offsets.push(0);
+    for index in indices {
+        let index = *index as usize;
+        new_data.extend_from_slice(&data[offsets[index]..offsets[index + 1]]);
+        new_offsets.push(new_data.len());
+    }
+    (new_data.into(), new_offsets)
+}
+
+fn concat_primitive<T: NativeType>(
+    columns: &[Column],
+    len: usize,
+    downcast: impl Fn(&Column) -> Option<&Buffer<T>>,
+) -> Buffer<T> {
+    let mut values = Vec::with_capacity(len);
+    for col in columns {
+        values.extend_from_slice(downcast(col).unwrap_or_else(|| mismatch()));
+    }
+    values.into()
+}
+
+fn concat_bitmap(
+    columns: &[Column],
+    len: usize,
+    downcast: impl Fn(&Column) -> Option<&Bitmap>,
+) -> Bitmap {
+    let mut bitmap = MutableBitmap::with_capacity(len);
+    for col in columns {
+        bitmap.extend_from_bitmap(downcast(col).unwrap_or_else(|| mismatch()));
+    }
+    bitmap.into()
+}
+
+/// Concatenates the bytes referenced by the offsets, which may not start at
+/// zero in a sliced column.
+fn concat_bytes(
+    columns: &[Column],
+    len: usize,
+    downcast: impl Fn(&Column) -> Option<(&Buffer<u8>, &Vec<usize>)>,
+) -> (Buffer<u8>, Vec<usize>) {
+    let mut data = Vec::new();
+    let mut new_offsets = Vec::with_capacity(len + 1);
+    new_offsets.push(0);
+    for col in columns {
+        let (col_data, offsets) = downcast(col).unwrap_or_else(|| mismatch());
+        let (start, end) = (offsets[0], offsets[offsets.len() - 1]);
+        let base = data.len();
+        data.extend_from_slice(&col_data[start..end]);
+        new_offsets.extend(offsets[1..].iter().map(|offset| offset - start + base));
+    }
+    (data.into(), new_offsets)
+}
@@ -0,0 +1,23 @@
+#![feature(iterator_try_reduce)]
+#![feature(box_patterns)]
+#![allow(clippy::len_without_is_empty)]
+#![allow(clippy::needless_lifetimes)]
+#![allow(clippy::arc_with_non_send_sync)]
+#![allow(clippy::manual_repeat_n)]
+#![allow(clippy::result_large_err)]
+
+pub mod arrow;
+pub mod builtins;
+pub mod display;
+pub mod expr;
+pub mod function;
+pub mod ipc;
+pub mod kernels;
+pub mod parser;
+pub mod property;
+pub mod runtime;
+pub mod type_check;
+pub mod types;
+pub mod util;
+pub mod values;
+pub mod wire;
@@ -1,645 +1,1366 @@
-#![feature(generic_associated_types)]
-#![feature(iterator_try_reduce)]
-#![feature(box_patterns)]
-#![feature(associated_type_defaults)]
-#![allow(clippy::len_without_is_empty)]
-#![allow(clippy::needless_lifetimes)]
-
 use std::collections::HashMap;
 use std::io::Write;
-use std::iter::once;
-use std::sync::Arc;
-
-use crate::expr::{Literal, AST};
-use crate::function::FunctionRegistry;
-use crate::function::{vectorize_2_arg, Function, FunctionSignature};
-use crate::property::{FunctionProperty, ValueProperty};
-use crate::runtime::Runtime;
-use crate::types::DataType;
-use crate::types::*;
-use crate::types::{ArgType, ArrayType};
-use crate::values::{Column, ColumnBuilder, ValueRef};
-use crate::values::{Scalar, Value};
-
-pub mod display;
-pub mod expr;
-pub mod function;
-pub mod property;
-pub mod runtime;
-pub mod type_check;
-pub mod types;
-pub mod util;
-pub mod values;
+use std::ops::Range;
+
+use arrow2::bitmap::Bitmap;
+#[cfg(test)]
+use proptest::prelude::*;
+use typed_type_exercise::builtins::builtin_functions;
+use typed_type_exercise::display::display_span;
+#[cfg(test)]
+use typed_type_exercise::expr::RemoteExpr;
+use typed_type_exercise::expr::{Literal, AST};
+#[cfg(test)]
+use typed_type_exercise::function::FunctionRegistry;
+#[cfg(test)]
+use typed_type_exercise::ipc;
+use typed_type_exercise::parser::{parse, parse_data_type};
+#[cfg(test)]
+use typed_type_exercise::property::{FunctionProperty, ValueProperty};
+use typed_type_exercise::runtime::Runtime;
+use typed_type_exercise::type_check;
+#[cfg(test)]
+use typed_type_exercise::types::decimal::DecimalOp;
+use typed_type_exercise::types::*;
+use typed_type_exercise::values::{Column, ColumnBuilder, Scalar};
+#[cfg(test)]
+use typed_type_exercise::values::{ScalarRef, Value};
 
 pub fn main() {
-    run_cases(&mut std::io::stdout());
+    for path in case_files() {
+        let text = std::fs::read_to_string(&path).unwrap();
+        let name = path.file_name().unwrap().to_str().unwrap();
+        run_case_file(&mut std::io::stdout(), name, &text);
+    }
 }
 
 #[test]
 pub fn test() {
     use goldenfile::Mint;
 
-    let mut mint = Mint::new("tests");
-    let mut file = mint.new_goldenfile("run-output").unwrap();
-    run_cases(&mut file);
+    let mut mint = Mint::new(std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/cases"));
+    for path in case_files() {
+        let text = std::fs::read_to_string(&path).unwrap();
+        let name = path.file_name().unwrap().to_str().unwrap();
+        let mut file = mint.new_goldenfile(name).unwrap();
+        run_case_file(&mut file, name, &text);
+    }
 }
 
-pub fn run_ast(output: &mut impl Write, ast: &AST, columns: HashMap<String, Column>) {
-    writeln!(output, "ast: {ast}").unwrap();
+/// Every expression of the case files evaluates to the same result after it
+/// is sent to another node, which rebuilds the functions from its registry.
+#[test]
+pub fn test_remote_expr() {
     let fn_registry = builtin_functions();
-    let (expr, ty, prop) = type_check::check(ast, &fn_registry).unwrap();
-    writeln!(output, "expr: {expr}").unwrap();
-    writeln!(output, "type: {ty}").unwrap();
-    writeln!(output, "property: {prop}").unwrap();
-    let runtime = Runtime { columns };
-    let result = runtime.run(&expr);
-    writeln!(output, "result: {result}\n").unwrap();
+    let remote_registry = builtin_functions();
+    for path in case_files() {
+        let text = std::fs::read_to_string(&path).unwrap();
+        let name = path.file_name().unwrap().to_str().unwrap();
+        for case in read_cases(name, &text) {
+            let Ok(ast) = parse(case.source, &case.schema) else {
+                continue;
+            };
+            let Ok((expr, _, _)) = type_check::check(&ast, &fn_registry) else {
+                continue;
+            };
+            let bytes = bincode::serialize(&expr.to_remote()).unwrap();
+            let remote_expr = bincode::deserialize::<RemoteExpr>(&bytes)
+                .unwrap()
+                .into_expr(&remote_registry)
+                .unwrap();
+            assert_eq!(remote_expr.to_string(), expr.to_string(), "{name}");
+
+            let runtime = Runtime {
+                columns: case.columns,
+                selection: case.selection,
+            };
+            let run = |expr| match runtime.run(expr) {
+                Ok(result) => runtime.compact(result).to_string(),
+                Err(err) => err.to_string(),
+            };
+            assert_eq!(run(&remote_expr), run(&expr), "{name}: {}", case.source);
+        }
+    }
 }
 
-fn run_cases(output: &mut impl Write) {
-    run_ast(
-        output,
-        &AST::FunctionCall {
-            name: "and".to_string(),
-            args: vec![
-                AST::Literal(Literal::Boolean(true)),
-                AST::Literal(Literal::Boolean(false)),
-            ],
-            params: vec![],
-        },
-        HashMap::new(),
-    );
-
-    run_ast(
-        output,
-        &AST::FunctionCall {
-            name: "and".to_string(),
-            args: vec![
-                AST::Literal(Literal::Null),
-                AST::Literal(Literal::Boolean(false)),
-            ],
-            params: vec![],
-        },
-        HashMap::new(),
-    );
-
-    run_ast(
-        output,
-        &AST::FunctionCall {
-            name: "plus".to_string(),
-            args: vec![
-                AST::ColumnRef {
-                    name: "a".to_string(),
-                    data_type: DataType::Nullable(Box::new(DataType::UInt8)),
-                    property: ValueProperty::default().not_null(false),
-                },
-                AST::Literal(Literal::Int8(-10)),
-            ],
-            params: vec![],
-        },
-        [(
-            "a".to_string(),
-            Column::Nullable {
-                column: Box::new(Column::UInt8(vec![10, 11, 12].into())),
-                validity: vec![false, true, false].into(),
-            },
-        )]
-        .into_iter()
-        .collect(),
-    );
-
-    run_ast(
-        output,
-        &AST::FunctionCall {
-            name: "plus".to_string(),
-            args: vec![
-                AST::ColumnRef {
-                    name: "a".to_string(),
-                    data_type: DataType::Nullable(Box::new(DataType::UInt8)),
-                    property: ValueProperty::default().not_null(false),
-                },
-                AST::ColumnRef {
-                    name: "b".to_string(),
-                    data_type: DataType::Nullable(Box::new(DataType::UInt8)),
-                    property: ValueProperty::default().not_null(false),
-                },
-            ],
-            params: vec![],
-        },
-        [
-            (
-                "a".to_string(),
-                Column::Nullable {
-                    column: Box::new(Column::UInt8(vec![10, 11, 12].into())),
-                    validity: vec![false, true, false].into(),
-                },
-            ),
+#[test]
+pub fn test_remote_expr_mismatch() {
+    let fn_registry = builtin_functions();
+    let ast = parse("plus(1::UInt8, 2::UInt8)", &HashMap::new()).unwrap();
+    let (expr, _, _) = type_check::check(&ast, &fn_registry).unwrap();
+    let RemoteExpr::FunctionCall {
+        span,
+        id,
+        generics,
+        args,
+    } = expr.to_remote()
+    else {
+        unreachable!()
+    };
+
+    let missing_arg = RemoteExpr::FunctionCall {
+        span: span.clone(),
+        id: id.clone(),
+        generics: generics.clone(),
+        args: args[..1].to_vec(),
+    };
+    assert_eq!(
+        missing_arg.into_expr(&fn_registry).unwrap_err(),
+        "function `plus` expects 2 arguments, but 1 were given"
+    );
+
+    let wrong_type = RemoteExpr::FunctionCall {
+        span,
+        id,
+        generics,
+        args: vec![
+            args[0].clone(),
             (
-                "b".to_string(),
-                Column::Nullable {
-                    column: Box::new(Column::UInt8(vec![1, 2, 3].into())),
-                    validity: vec![false, true, true].into(),
+                RemoteExpr::Literal {
+                    span: None,
+                    lit: Literal::String("2".to_string()),
                 },
+                args[1].1,
             ),
-        ]
-        .into_iter()
-        .collect(),
-    );
-
-    run_ast(
-        output,
-        &AST::FunctionCall {
-            name: "not".to_string(),
-            args: vec![AST::ColumnRef {
-                name: "a".to_string(),
-                data_type: DataType::Nullable(Box::new(DataType::Boolean)),
-                property: ValueProperty::default().not_null(false),
-            }],
-            params: vec![],
-        },
-        [(
-            "a".to_string(),
-            Column::Nullable {
-                column: Box::new(Column::Boolean(vec![true, false, true].into())),
-                validity: vec![false, true, false].into(),
+        ],
+    };
+    assert_eq!(
+        wrong_type.into_expr(&fn_registry).unwrap_err(),
+        "function `plus` expects an argument of type UInt8, but String was given"
+    );
+}
+
+#[test]
+pub fn test_display_span() {
+    use typed_type_exercise::display::display_span;
+
+    let source = "and(true, plus(true, 1))";
+    assert_eq!(
+        display_span(source, 10..23),
+        "1 | and(true, plus(true, 1))\n  |           ^^^^^^^^^^^^^"
+    );
+
+    let source = "create_array(\n  a,\n  true\n)";
+    assert_eq!(display_span(source, 21..25), "3 |   true\n  |   ^^^^");
+    assert_eq!(
+        display_span(source, 0..source.len()),
+        "1 | create_array(\n  | ^^^^^^^^^^^^^"
+    );
+}
+
+#[test]
+pub fn test_parser() {
+    use typed_type_exercise::parser::parse;
+
+    let schema = HashMap::from([
+        ("a".to_string(), DataType::Int32),
+        (
+            "b".to_string(),
+            DataType::Nullable(Box::new(DataType::UInt8)),
+        ),
+        ("x".to_string(), DataType::Boolean),
+        ("y".to_string(), DataType::Null),
+        (
+            "t".to_string(),
+            DataType::Tuple {
+                fields_name: vec![Some("k".to_string()), None],
+                fields_type: vec![DataType::String, DataType::Int64],
             },
-        )]
-        .into_iter()
-        .collect(),
-    );
-
-    run_ast(
-        output,
-        &AST::FunctionCall {
-            name: "least".to_string(),
-            args: vec![
-                AST::Literal(Literal::UInt8(10)),
-                AST::Literal(Literal::UInt8(20)),
-                AST::Literal(Literal::UInt8(30)),
-                AST::Literal(Literal::UInt8(40)),
-            ],
-            params: vec![],
-        },
-        HashMap::new(),
-    );
-
-    run_ast(
-        output,
-        &AST::FunctionCall {
-            name: "create_tuple".to_string(),
-            args: vec![
-                AST::Literal(Literal::Null),
-                AST::Literal(Literal::Boolean(true)),
-            ],
-            params: vec![],
-        },
-        [].into_iter().collect(),
-    );
-
-    run_ast(
-        output,
-        &AST::FunctionCall {
-            name: "get_tuple".to_string(),
-            args: vec![AST::FunctionCall {
-                name: "create_tuple".to_string(),
-                args: vec![
-                    AST::ColumnRef {
-                        name: "a".to_string(),
-                        data_type: DataType::Int16,
-                        property: ValueProperty::default().not_null(true),
-                    },
-                    AST::ColumnRef {
-                        name: "b".to_string(),
-                        data_type: DataType::Nullable(Box::new(DataType::String)),
-                        property: ValueProperty::default().not_null(false),
-                    },
-                ],
-                params: vec![],
-            }],
-            params: vec![1],
-        },
-        [
-            ("a".to_string(), Column::Int16(vec![0, 1, 2, 3, 4].into())),
-            (
-                "b".to_string(),
-                Column::Nullable {
-                    column: Box::new(Column::String {
-                        data: "abcde".as_bytes().to_vec().into(),
-                        offsets: vec![0, 1, 2, 3, 4, 5],
-                    }),
-                    validity: vec![true, true, false, false, false].into(),
-                },
-            ),
-        ]
-        .into_iter()
-        .collect(),
-    );
-
-    run_ast(
-        output,
-        &AST::FunctionCall {
-            name: "get_tuple".to_string(),
-            args: vec![AST::ColumnRef {
-                name: "a".to_string(),
-                data_type: DataType::Nullable(Box::new(DataType::Tuple(vec![
-                    DataType::Boolean,
-                    DataType::String,
-                ]))),
-                property: ValueProperty::default().not_null(true),
-            }],
-            params: vec![1],
-        },
-        [(
-            "a".to_string(),
-            Column::Nullable {
-                column: Box::new(Column::Tuple {
-                    fields: vec![
-                        Column::Boolean(vec![false; 5].into()),
-                        Column::String {
-                            data: "abcde".as_bytes().to_vec().into(),
-                            offsets: vec![0, 1, 2, 3, 4, 5],
-                        },
-                    ],
-                    len: 5,
-                }),
-                validity: vec![true, true, false, false, false].into(),
+        ),
+    ]);
+    let cases = [
+        (
+            "a + b * 2",
+            "plus(a::Int32{not_null}, multiply(b::Nullable<UInt8>{}, 2::UInt8))",
+        ),
+        (
+            "a - 1 - -2",
+            "minus(minus(a::Int32{not_null}, 1::UInt8), -2::Int8)",
+        ),
+        (
+            "(a + b) / 300",
+            "divide(plus(a::Int32{not_null}, b::Nullable<UInt8>{}), 300::UInt16)",
+        ),
+        (
+            "NOT x AND y OR NOT NOT x",
+            "or(and(not(x::Boolean{not_null}), y::Nullable<Nothing>{}), not(not(x::Boolean{not_null})))",
+        ),
+        (
+            "get_tuple(1)(t)",
+            "get_tuple(1)(t::(k String, Int64){not_null})",
+        ),
+        ("10::UInt8 + -1::Int64", "plus(10::UInt8, -1::Int64)"),
+        ("a - -1", "minus(a::Int32{not_null}, -1::Int8)"),
+        (
+            "[NaN::Float64, -inf::Float32]",
+            "create_array(NaN::Float64, -inf::Float32)",
+        ),
+        (
+            "1.50::Decimal(5, 2) * 0.5",
+            "multiply(1.50::Decimal(5, 2), 0.5::Float64)",
+        ),
+        (
+            "a::Nullable<Float32>::String",
+            "cast(cast(a::Int32{not_null} as Nullable<Float32>) as String)",
+        ),
+        (
+            "CAST(NULL AS Array<(a Int8, String,)>)",
+            "cast(NULL as Array<(a Int8, String)>)",
+        ),
+        ("'it''s\\n\\'ok\\''", "'it\\'s\\n\\'ok\\''::String"),
+        (
+            "[1, 2, x'c328']",
+            "create_array(1::UInt8, 2::UInt8, x'c328'::Binary)",
+        ),
+        ("[]", "create_array()"),
+        (
+            "(a, TRUE, 'b',)",
+            "create_tuple(a::Int32{not_null}, true::Boolean, 'b'::String)",
+        ),
+        ("(a,)", "create_tuple(a::Int32{not_null})"),
+        ("((a))", "a::Int32{not_null}"),
+    ];
+    for (text, expected) in cases {
+        match parse(text, &schema) {
+            Ok(ast) => assert_eq!(ast.to_string(), expected, "{text}"),
+            Err(err) => panic!("{text}: {err}"),
+        }
+    }
+
+    let ast = parse("plus(a, 1)", &schema).unwrap();
+    assert_eq!(ast.span(), Some(0..10));
+    let AST::FunctionCall { args, .. } = ast else {
+        unreachable!()
+    };
+    assert_eq!(args[0].span(), Some(5..6));
+    assert_eq!(args[1].span(), Some(8..9));
+
+    let errors = [
+        ("a +", "unexpected end of the expression at 3..3"),
+        ("a + c", "column `c` does not exist at 4..5"),
+        (
+            "f(a",
+            "expected `)`, found the end of the expression at 3..3",
+        ),
+        (
+            "get_tuple(a)(t)",
+            "parameter of function `get_tuple` must be a non-negative integer at 10..11",
+        ),
+        (
+            "300::UInt8",
+            "number `300` is out of range for UInt8 at 0..10",
+        ),
+        ("a::Int", "type `Int` does not exist at 3..6"),
+        ("'abc", "unterminated string literal at 0..4"),
+        ("a ? b", "unexpected character `?` at 2..3"),
+        ("a b", "unexpected `b` at 2..3"),
+        (
+            "-a",
+            "unary `-` is only supported directly before a number at 0..1",
+        ),
+        (
+            "- 5::Int8",
+            "unary `-` is only supported directly before a number at 0..1",
+        ),
+        ("1.5::Int8", "invalid Int8 literal `1.5` at 0..9"),
+        (
+            "1::Decimal(2, 5)",
+            "invalid decimal type Decimal(2, 5) at 3..16",
+        ),
+    ];
+    for (text, expected) in errors {
+        match parse(text, &schema) {
+            Ok(ast) => panic!("{text}: expected an error, got {ast}"),
+            Err(err) => assert_eq!(err.to_string(), expected, "{text}"),
+        }
+    }
+}
+
+#[test]
+pub fn test_ambiguous_overload() {
+    use typed_type_exercise::type_check::TypeCheckError;
+
+    if let Err(ambiguities) = builtin_functions().validate() {
+        panic!(
+            "builtin functions have ambiguous overloads:\n{}",
+            ambiguities
+                .iter()
+                .map(|ambiguity| ambiguity.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+
+    let mut registry = FunctionRegistry::default();
+    registry.register_2_arg::<NumberType<u16>, NumberType<u8>, NumberType<u16>, _>(
+        "f",
+        FunctionProperty::default(),
+        |lhs, rhs| lhs + rhs as u16,
+    );
+    registry.register_2_arg::<NumberType<u8>, NumberType<u16>, NumberType<u16>, _>(
+        "f",
+        FunctionProperty::default(),
+        |lhs, rhs| lhs as u16 + rhs,
+    );
+
+    let ambiguities = registry.validate().unwrap_err();
+    assert!(ambiguities.iter().any(|ambiguity| ambiguity.name == "f"
+        && ambiguity.args_type == [DataType::UInt8, DataType::UInt8]));
+
+    let ast = AST::FunctionCall {
+        span: None,
+        name: "f".to_string(),
+        params: vec![],
+        args: vec![
+            AST::Literal {
+                span: None,
+                lit: Literal::UInt8(1),
             },
-        )]
-        .into_iter()
-        .collect(),
+            AST::Literal {
+                span: None,
+                lit: Literal::UInt8(2),
+            },
+        ],
+    };
+    assert!(matches!(
+        type_check::check(&ast, &registry),
+        Err(TypeCheckError::AmbiguousOverload { .. })
+    ));
+}
+
+#[test]
+pub fn test_float_total_order() {
+    use std::collections::HashSet;
+
+    let mut values = vec![
+        F64(f64::NAN),
+        F64(1.0),
+        F64(f64::INFINITY),
+        F64(-0.0),
+        F64(f64::NEG_INFINITY),
+        F64(-f64::NAN),
+        F64(0.0),
+    ];
+    values.sort();
+    assert_eq!(
+        format!("{values:?}"),
+        "[-inf, -0.0, 0.0, 1.0, inf, NaN, NaN]"
     );
 
-    run_ast(
-        output,
-        &AST::FunctionCall {
-            name: "create_array".to_string(),
-            args: vec![],
-            params: vec![],
-        },
-        [].into_iter().collect(),
-    );
-
-    run_ast(
-        output,
-        &AST::FunctionCall {
-            name: "create_array".to_string(),
-            args: vec![
-                AST::Literal(Literal::Null),
-                AST::Literal(Literal::Boolean(true)),
-            ],
-            params: vec![],
-        },
-        [].into_iter().collect(),
-    );
-
-    run_ast(
-        output,
-        &AST::FunctionCall {
-            name: "create_array".to_string(),
-            args: vec![
-                AST::ColumnRef {
-                    name: "a".to_string(),
-                    data_type: DataType::Int16,
-                    property: ValueProperty::default().not_null(true),
-                },
-                AST::ColumnRef {
-                    name: "b".to_string(),
-                    data_type: DataType::Int16,
-                    property: ValueProperty::default().not_null(true),
-                },
-            ],
-            params: vec![],
-        },
-        [
-            ("a".to_string(), Column::Int16(vec![0, 1, 2, 3, 4].into())),
-            ("b".to_string(), Column::Int16(vec![5, 6, 7, 8, 9].into())),
-        ]
-        .into_iter()
-        .collect(),
-    );
-
-    run_ast(
-        output,
-        &AST::FunctionCall {
-            name: "create_array".to_string(),
-            args: vec![
-                AST::FunctionCall {
-                    name: "create_array".to_string(),
-                    args: vec![
-                        AST::ColumnRef {
-                            name: "a".to_string(),
-                            data_type: DataType::Int16,
-                            property: ValueProperty::default().not_null(true),
-                        },
-                        AST::ColumnRef {
-                            name: "b".to_string(),
-                            data_type: DataType::Int16,
-                            property: ValueProperty::default().not_null(true),
-                        },
-                    ],
-                    params: vec![],
-                },
-                AST::Literal(Literal::Null),
-                AST::Literal(Literal::Null),
-            ],
-            params: vec![],
-        },
-        [
-            ("a".to_string(), Column::Int16(vec![0, 1, 2, 3, 4].into())),
-            ("b".to_string(), Column::Int16(vec![5, 6, 7, 8, 9].into())),
-        ]
-        .into_iter()
-        .collect(),
-    );
-
-    run_ast(
-        output,
-        &AST::FunctionCall {
-            name: "get".to_string(),
-            args: vec![
-                AST::ColumnRef {
-                    name: "array".to_string(),
-                    data_type: DataType::Array(Box::new(DataType::Int16)),
-                    property: ValueProperty::default().not_null(true),
-                },
-                AST::ColumnRef {
-                    name: "idx".to_string(),
-                    data_type: DataType::UInt8,
-                    property: ValueProperty::default().not_null(true),
-                },
-            ],
-            params: vec![],
-        },
-        [
-            (
-                "array".to_string(),
-                Column::Array {
-                    array: Box::new(Column::Int16((0..100).collect())),
-                    offsets: vec![0, 20, 40, 60, 80, 100],
-                },
-            ),
-            ("idx".to_string(), Column::UInt8(vec![0, 1, 2, 3, 4].into())),
-        ]
+    assert_eq!(F32(f32::NAN), F32(-f32::NAN));
+    assert!(F32(f32::NAN) > F32(f32::INFINITY));
+    assert_eq!(F32(0.0), F32(-0.0));
+
+    let set = [F64(f64::NAN), F64(-f64::NAN), F64(0.0), F64(-0.0)]
         .into_iter()
-        .collect(),
-    );
-
-    run_ast(
-        output,
-        &AST::FunctionCall {
-            name: "get".to_string(),
-            args: vec![
-                AST::ColumnRef {
-                    name: "array".to_string(),
-                    data_type: DataType::Array(Box::new(DataType::Array(Box::new(
-                        DataType::Int16,
-                    )))),
-                    property: ValueProperty::default().not_null(true),
-                },
-                AST::ColumnRef {
-                    name: "idx".to_string(),
-                    data_type: DataType::UInt8,
-                    property: ValueProperty::default().not_null(true),
-                },
-            ],
-            params: vec![],
-        },
-        [
+        .collect::<HashSet<_>>();
+    assert_eq!(set.len(), 2);
+}
+
+#[test]
+pub fn test_decimal_result_size() {
+    use typed_type_exercise::types::decimal::decimal_op_result_size;
+
+    assert_eq!(
+        decimal_op_result_size(DecimalOp::Plus, (5, 2), (4, 3)),
+        (7, 3)
+    );
+    assert_eq!(
+        decimal_op_result_size(DecimalOp::Minus, (3, 0), (10, 10)),
+        (14, 10)
+    );
+    assert_eq!(
+        decimal_op_result_size(DecimalOp::Multiply, (5, 2), (4, 1)),
+        (10, 3)
+    );
+    assert_eq!(
+        decimal_op_result_size(DecimalOp::Divide, (4, 2), (10, 0)),
+        (15, 13)
+    );
+    // The precision is capped at 38 by reducing the scale, but not below 6.
+    assert_eq!(
+        decimal_op_result_size(DecimalOp::Multiply, (38, 10), (38, 10)),
+        (38, 6)
+    );
+    assert_eq!(
+        decimal_op_result_size(DecimalOp::Divide, (38, 4), (20, 2)),
+        (38, 6)
+    );
+    assert_eq!(
+        decimal_op_result_size(DecimalOp::Plus, (38, 2), (10, 9)),
+        (38, 6)
+    );
+}
+
+#[test]
+pub fn test_arrow_zero_copy() {
+    use arrow2::array::{PrimitiveArray, Utf8Array};
+
+    let ints = Column::Int32(vec![1, 2, 3].into());
+    let arrow = ints.to_arrow();
+    let array = arrow
+        .as_any()
+        .downcast_ref::<PrimitiveArray<i32>>()
+        .unwrap();
+    assert_eq!(array.values().as_ptr(), ints.as_int32().unwrap().as_ptr());
+    let back = Column::from_arrow(arrow.as_ref(), &DataType::Int32).unwrap();
+    assert_eq!(
+        back.as_int32().unwrap().as_ptr(),
+        ints.as_int32().unwrap().as_ptr()
+    );
+
+    let strings = Column::String {
+        data: "abcde".as_bytes().to_vec().into(),
+        offsets: vec![0, 2, 5],
+    };
+    let arrow = strings.to_arrow();
+    let array = arrow.as_any().downcast_ref::<Utf8Array<i64>>().unwrap();
+    assert_eq!(
+        array.values().as_ptr(),
+        strings.as_string().unwrap().0.as_ptr()
+    );
+}
+
+#[test]
+pub fn test_arrow_invalid() {
+    use std::sync::Arc;
+
+    use arrow2::array::{BinaryArray, DictionaryArray, PrimitiveArray, Utf8Array};
+    use arrow2::datatypes::DataType as ArrowDataType;
+
+    let variant_type = DataType::Variant.to_arrow();
+    let garbage = BinaryArray::<i64>::new(
+        variant_type.clone(),
+        vec![0, 2].into(),
+        vec![0xff, 0xff].into(),
+        None,
+    );
+    assert_eq!(
+        Column::from_arrow(&garbage, &DataType::Variant).unwrap_err(),
+        "invalid variant at row 0"
+    );
+    // The bytes under a null are not checked.
+    let null = BinaryArray::<i64>::new(
+        variant_type,
+        vec![0, 0].into(),
+        Vec::new().into(),
+        Some([false].into_iter().collect()),
+    );
+    let ty = DataType::Nullable(Box::new(DataType::Variant));
+    assert!(Column::from_arrow(&null, &ty).is_ok());
+
+    let dictionary = DictionaryArray::<u32>::from_data(
+        PrimitiveArray::from_vec(vec![0, 2]),
+        Arc::new(Utf8Array::<i64>::from_slice(["a", "b"])),
+    );
+    assert_eq!(
+        Column::from_arrow(&dictionary, &DataType::String).unwrap_err(),
+        "key 2 is out of bounds for 2 values"
+    );
+
+    assert_eq!(
+        DataType::from_arrow(&ArrowDataType::Decimal(10, 12)).unwrap_err(),
+        "invalid decimal type Decimal(10, 12)"
+    );
+    assert_eq!(
+        DataType::from_arrow(&ArrowDataType::Decimal(39, 0)).unwrap_err(),
+        "invalid decimal type Decimal(39, 0)"
+    );
+}
+
+#[test]
+pub fn test_ipc_eval() {
+    use std::io::Cursor;
+
+    let mut file = Vec::new();
+    ipc::write_ipc(
+        &mut file,
+        &[
+            ("a", Column::Int16(vec![1, 2, 3].into())),
             (
-                "array".to_string(),
-                Column::Array {
-                    array: Box::new(Column::Array {
-                        array: Box::new(Column::Int16((0..100).collect())),
-                        offsets: vec![
-                            0, 5, 10, 15, 20, 25, 30, 35, 40, 45, 50, 55, 60, 65, 70, 75, 80, 85,
-                            90, 100,
-                        ],
-                    }),
-                    offsets: vec![0, 4, 8, 12, 16, 20],
+                "b",
+                Column::Nullable {
+                    column: Box::new(Column::Int16(vec![10, 20, 30].into())),
+                    validity: vec![true, false, true].into(),
                 },
             ),
-            ("idx".to_string(), Column::UInt8(vec![0, 1, 2].into())),
-        ]
-        .into_iter()
-        .collect(),
+        ],
+    )
+    .unwrap();
+    let columns = ipc::read_ipc(&mut Cursor::new(file)).unwrap();
+    assert_eq!(
+        columns["b"].data_type(),
+        DataType::Nullable(Box::new(DataType::Int16))
+    );
+
+    let ast = AST::FunctionCall {
+        span: None,
+        name: "plus".to_string(),
+        args: ["a", "b"]
+            .into_iter()
+            .map(|name| AST::ColumnRef {
+                span: None,
+                name: name.to_string(),
+                data_type: columns[name].data_type(),
+                property: ValueProperty::default(),
+            })
+            .collect(),
+        params: vec![],
+    };
+    let (expr, _, _) = type_check::check(&ast, &builtin_functions()).unwrap();
+    let runtime = Runtime {
+        columns,
+        selection: None,
+    };
+    let result = match runtime.run(&expr).unwrap() {
+        Value::Column(col) => col,
+        Value::Scalar(_) => unreachable!(),
+    };
+
+    let mut file = Vec::new();
+    ipc::write_ipc(&mut file, &[("result", result)]).unwrap();
+    let columns = ipc::read_ipc(&mut Cursor::new(file)).unwrap();
+    assert_eq!(
+        columns["result"].iter().collect::<Vec<_>>(),
+        vec![ScalarRef::Int32(11), ScalarRef::Null, ScalarRef::Int32(33)]
     );
 }
 
-fn builtin_functions() -> FunctionRegistry {
-    let mut registry = FunctionRegistry::default();
+#[test]
+pub fn test_ipc_invalid() {
+    use std::io::Cursor;
+    use std::sync::Arc;
 
-    registry.register_2_arg::<BooleanType, BooleanType, BooleanType, _>(
-        "and",
-        FunctionProperty::default(),
-        |lhs, rhs| lhs && rhs,
+    use arrow2::array::{Array, DictionaryArray, PrimitiveArray, Utf8Array};
+    use arrow2::chunk::Chunk;
+    use arrow2::datatypes::{DataType as ArrowDataType, Field, Schema};
+    use arrow2::io::ipc::write::{FileWriter, WriteOptions};
+
+    // The column is written with the "Variant" extension type as is.
+    let mut file = Vec::new();
+    let garbage = Column::Variant {
+        data: vec![0xff, 0xff, 0xff].into(),
+        offsets: vec![0, 3],
+    };
+    ipc::write_ipc(&mut file, &[("a", garbage)]).unwrap();
+    assert_eq!(
+        ipc::read_ipc(&mut Cursor::new(file)).unwrap_err(),
+        "invalid variant at row 0"
     );
 
-    registry.register_2_arg::<NumberType<i16>, NumberType<i16>, NumberType<i16>, _>(
-        "plus",
-        FunctionProperty::default(),
-        |lhs, rhs| lhs + rhs,
+    let array = PrimitiveArray::<i128>::from_slice([1]).to(ArrowDataType::Decimal(10, 12));
+    let schema = Schema::from(vec![Field::new("a", array.data_type().clone(), false)]);
+    let mut file = Vec::new();
+    let mut writer =
+        FileWriter::try_new(&mut file, &schema, None, WriteOptions { compression: None }).unwrap();
+    writer
+        .write(&Chunk::new(vec![Arc::new(array) as Arc<dyn Array>]), None)
+        .unwrap();
+    writer.finish().unwrap();
+    assert_eq!(
+        ipc::read_ipc(&mut Cursor::new(file)).unwrap_err(),
+        "invalid decimal type Decimal(10, 12)"
     );
 
-    registry.register_1_arg::<BooleanType, BooleanType, _>(
-        "not",
-        FunctionProperty::default(),
-        |val| !val,
+    let array = DictionaryArray::<u32>::from_data(
+        PrimitiveArray::from_vec(vec![0, 5]),
+        Arc::new(Utf8Array::<i64>::from_slice(["a", "b"])),
     );
+    let schema = Schema::from(vec![Field::new("a", array.data_type().clone(), false)]);
+    let mut file = Vec::new();
+    let mut writer =
+        FileWriter::try_new(&mut file, &schema, None, WriteOptions { compression: None }).unwrap();
+    writer
+        .write(&Chunk::new(vec![Arc::new(array) as Arc<dyn Array>]), None)
+        .unwrap();
+    writer.finish().unwrap();
+    assert_eq!(
+        ipc::read_ipc(&mut Cursor::new(file)).unwrap_err(),
+        "key 5 is out of bounds for 2 values"
+    );
+}
 
-    registry.register_function_factory("least", |_, args_type| {
-        Some(Arc::new(Function {
-            signature: FunctionSignature {
-                name: "least",
-                args_type: vec![DataType::Int16; args_type.len()],
-                return_type: DataType::Int16,
-                property: FunctionProperty::default().preserve_not_null(true),
-            },
-            eval: Box::new(|args, generics| {
-                if args.is_empty() {
-                    Value::Scalar(Scalar::Int16(0))
-                } else if args.len() == 1 {
-                    args[0].clone().to_owned()
-                } else {
-                    let mut min: Value<NumberType<i16>> = vectorize_2_arg(
-                        NumberType::<i16>::try_downcast_value(&args[0]).unwrap(),
-                        NumberType::<i16>::try_downcast_value(&args[1]).unwrap(),
-                        generics,
-                        |lhs, rhs| lhs.min(rhs),
-                    );
-                    for arg in &args[2..] {
-                        min = vectorize_2_arg(
-                            min.as_ref(),
-                            NumberType::<i16>::try_downcast_value(arg).unwrap(),
-                            generics,
-                            |lhs, rhs| lhs.min(rhs),
-                        );
-                    }
-                    NumberType::<i16>::upcast_value(min)
-                }
-            }),
-        }))
-    });
+#[test]
+pub fn test_wire_every_variant() {
+    use typed_type_exercise::wire::{decode_column, decode_scalar, encode_column, encode_scalar};
 
-    registry.register_0_arg_core::<EmptyArrayType, _>(
-        "create_array",
-        FunctionProperty::default(),
-        |_| Value::Scalar(()),
-    );
+    let columns = [
+        "a: Nullable<Int8> = [1, NULL, -3]",
+        "a: Int16 = const(-2, 4)",
+        "a: Int32 = [1, -2]",
+        "a: Int64 = [9223372036854775807]",
+        "a: UInt16 = [1, 65535]",
+        "a: UInt32 = [4294967295]",
+        "a: UInt64 = [18446744073709551615]",
+        "a: Float32 = [1.5, NaN::Float32, -inf::Float32]",
+        "a: Float64 = [-0.0, 2.5]",
+        "a: Nullable<Decimal(38, 10)> = [NULL, 1.5, -99999]",
+        "a: Date = ['2024-02-29', -1]",
+        "a: Timestamp = ['2024-02-29T12:34:56.789']",
+        "a: Interval = [-500000, 1]",
+        "a: Boolean = [TRUE, FALSE, TRUE, TRUE, FALSE, FALSE, TRUE, FALSE, TRUE]",
+        "a: Nullable<String> = dictionary([1, 0, 1], ['héllo', NULL])",
+        "a: Binary = [x'c328', x'']",
+        "a: Variant = ['{\"a\": [1, \"x\", null]}', '1.5', 'true']",
+        "a: Nullable<Nothing> = [NULL, NULL]",
+        "a: Array<Nothing> = [[], []]",
+        "a: Array<Array<UInt8>> = [[[1, 2], []], [], [[3]]]",
+        "a: Map<String, Nullable<Int16>> = [[('a', 1), ('b', NULL)], []]",
+        "a: (x Int16, Nullable<(Boolean, String)>) = [(1, NULL), (2, (TRUE, 'y'))]",
+    ];
+    for text in columns {
+        let (_, ty, col) = column_from_text(text).unwrap();
+        let col = col.unwrap();
+        let bytes = encode_column(&col);
+        let back = decode_column(&bytes).unwrap();
+        assert_eq!(back.data_type(), col.data_type(), "{text}");
+        assert_eq!(
+            back.iter().collect::<Vec<_>>(),
+            col.iter().collect::<Vec<_>>(),
+            "{text}"
+        );
 
-    registry.register_function_factory("create_array", |_, args_type| {
-        Some(Arc::new(Function {
-            signature: FunctionSignature {
-                name: "create_array",
-                args_type: vec![DataType::Generic(0); args_type.len()],
-                return_type: DataType::Array(Box::new(DataType::Generic(0))),
-                property: FunctionProperty::default().preserve_not_null(true),
-            },
-            eval: Box::new(|args, generics| {
-                let len = args.iter().find_map(|arg| match arg {
-                    ValueRef::Column(col) => Some(col.len()),
-                    _ => None,
-                });
-                if let Some(len) = len {
-                    let mut array_builder = ColumnBuilder::with_capacity(&generics[0], 0);
-                    for idx in 0..len {
-                        for arg in args {
-                            match arg {
-                                ValueRef::Scalar(scalar) => {
-                                    array_builder.push(scalar.as_ref());
-                                }
-                                ValueRef::Column(col) => {
-                                    array_builder.push(col.index(idx));
-                                }
-                            }
-                        }
-                    }
-                    let offsets = once(0)
-                        .chain((0..len).map(|row| args.len() * (row + 1)))
-                        .collect();
-                    Value::Column(Column::Array {
-                        array: Box::new(array_builder.build()),
-                        offsets,
-                    })
-                } else {
-                    // All args are scalars, so we return a scalar as result
-                    let mut array = ColumnBuilder::with_capacity(&generics[0], 0);
-                    for arg in args {
-                        match arg {
-                            ValueRef::Scalar(scalar) => {
-                                array.push(scalar.as_ref());
-                            }
-                            ValueRef::Column(_) => unreachable!(),
+        for pos in 1..bytes.len() {
+            for byte in [0, 1, 0x7f, 0x80, 0xff, !bytes[pos]] {
+                let mut bytes = bytes.clone();
+                bytes[pos] = byte;
+                // The decoded column can be read, including the variants.
+                if let Ok(back) = decode_column(&bytes) {
+                    for item in back.iter() {
+                        if let ScalarRef::Variant(variant) = item {
+                            typed_type_exercise::types::variant::to_json(variant);
                         }
                     }
-                    Value::Scalar(Scalar::Array(array.build()))
                 }
-            }),
-        }))
-    });
+            }
+        }
 
-    registry.register_with_writer_2_arg::<ArrayType<GenericType<0>>, NumberType<i16>, GenericType<0>, _>(
-        "get",
-        FunctionProperty::default(),
-        |array, idx, output| output.push(array.index(idx as usize)),
+        let scalar = Scalar::Array(col.clone());
+        let back = decode_scalar(&encode_scalar(&scalar)).unwrap();
+        assert_eq!(back.as_ref(), scalar.as_ref(), "{text}");
+        for item in col.iter() {
+            let item = item.to_owned();
+            let back = decode_scalar(&encode_scalar(&item)).unwrap();
+            assert_eq!(back.as_ref(), item.as_ref(), "{text}: {ty}");
+        }
+    }
+
+    let col = column_from_text("a: String = ['abc', 'de', 'f']")
+        .unwrap()
+        .2
+        .unwrap();
+    let mut bytes = encode_column(&col);
+    assert_eq!(
+        decode_column(&bytes[..bytes.len() - 1]).unwrap_err(),
+        "unexpected end of the payload"
+    );
+    bytes[0] = 2;
+    assert_eq!(
+        decode_column(&bytes).unwrap_err(),
+        "unsupported wire format version 2"
     );
+    let last = bytes.len() - 1;
+    bytes[0] = 1;
+    bytes[last] = 0xff;
+    assert!(decode_column(&bytes)
+        .unwrap_err()
+        .starts_with("invalid string"));
 
-    registry.register_function_factory("create_tuple", |_, args_type| {
-        Some(Arc::new(Function {
-            signature: FunctionSignature {
-                name: "create_tuple",
-                args_type: args_type.to_vec(),
-                return_type: DataType::Tuple(args_type.to_vec()),
-                property: FunctionProperty::default().preserve_not_null(true),
-            },
-            eval: Box::new(move |args, _generics| {
-                let len = args.iter().find_map(|arg| match arg {
-                    ValueRef::Column(col) => Some(col.len()),
-                    _ => None,
-                });
-                if let Some(len) = len {
-                    let fields = args
-                        .iter()
-                        .map(|arg| match arg {
-                            ValueRef::Scalar(scalar) => scalar.as_ref().repeat(len).build(),
-                            ValueRef::Column(col) => col.clone(),
-                        })
-                        .collect();
-                    Value::Column(Column::Tuple { fields, len })
-                } else {
-                    // All args are scalars, so we return a scalar as result
-                    let fields = args
-                        .iter()
-                        .map(|arg| match arg {
-                            ValueRef::Scalar(scalar) => (*scalar).to_owned(),
-                            ValueRef::Column(_) => unreachable!(),
-                        })
-                        .collect();
-                    Value::Scalar(Scalar::Tuple(fields))
-                }
+    // A constant column of 2^32 - 1 rows, which would be materialized later.
+    let huge = [1, 24, 0xff, 0xff, 0xff, 0xff, 0x0f, 0];
+    assert_eq!(
+        decode_column(&huge).unwrap_err(),
+        "column of 4294967295 rows exceeds the maximum of 16777216"
+    );
+    // A tuple without fields is no different.
+    let huge = [1, 21, 0xff, 0xff, 0xff, 0xff, 0x0f, 0];
+    assert_eq!(
+        decode_column(&huge).unwrap_err(),
+        "column of 4294967295 rows exceeds the maximum of 16777216"
+    );
+
+    // 100 arrays nested in each other, each with a single item.
+    let nested = std::iter::once(1)
+        .chain(std::iter::repeat_n([20, 1, 1], 100).flatten())
+        .chain([0, 1])
+        .collect::<Vec<u8>>();
+    assert_eq!(
+        decode_column(&nested).unwrap_err(),
+        "payload is nested too deeply"
+    );
+}
+
+#[test]
+#[should_panic(expected = "scatter index 3 at row 1 is out of bounds for scatter size 3")]
+pub fn test_column_scatter_out_of_bounds() {
+    Column::Int64(vec![1, 2].into()).scatter(&[0, 3], 3);
+}
+
+#[cfg(test)]
+fn arb_data_type() -> impl Strategy<Value = DataType> {
+    let leaf = prop_oneof![
+        Just(DataType::Null),
+        Just(DataType::Boolean),
+        Just(DataType::UInt8),
+        Just(DataType::Int64),
+        Just(DataType::Float64),
+        Just(DataType::Timestamp),
+        Just(DataType::Variant),
+        Just(DataType::String),
+        Just(DataType::Binary),
+        Just(DataType::Date),
+        Just(DataType::Decimal {
+            precision: 10,
+            scale: 2
+        }),
+    ];
+    leaf.prop_recursive(3, 16, 3, |inner| {
+        prop_oneof![
+            inner.clone().prop_map(|ty| match ty {
+                ty @ (DataType::Null | DataType::Nullable(_)) => ty,
+                ty => DataType::Nullable(Box::new(ty)),
+            }),
+            inner.clone().prop_map(|ty| DataType::Array(Box::new(ty))),
+            prop::collection::vec(inner, 1..3).prop_map(|fields_type| DataType::Tuple {
+                fields_name: vec![None; fields_type.len()],
+                fields_type,
             }),
-        }))
+        ]
+    })
+}
+
+#[cfg(test)]
+fn arb_scalar(ty: &DataType) -> BoxedStrategy<Scalar> {
+    match ty {
+        DataType::Null => Just(Scalar::Null).boxed(),
+        DataType::Boolean => any::<bool>().prop_map(Scalar::Boolean).boxed(),
+        DataType::UInt8 => any::<u8>().prop_map(Scalar::UInt8).boxed(),
+        DataType::UInt16 => any::<u16>().prop_map(Scalar::UInt16).boxed(),
+        DataType::UInt32 => any::<u32>().prop_map(Scalar::UInt32).boxed(),
+        DataType::UInt64 => any::<u64>().prop_map(Scalar::UInt64).boxed(),
+        DataType::Int8 => any::<i8>().prop_map(Scalar::Int8).boxed(),
+        DataType::Int16 => any::<i16>().prop_map(Scalar::Int16).boxed(),
+        DataType::Int32 => any::<i32>().prop_map(Scalar::Int32).boxed(),
+        DataType::Int64 => any::<i64>().prop_map(Scalar::Int64).boxed(),
+        DataType::Float32 => any::<f32>().prop_map(|v| Scalar::Float32(v.into())).boxed(),
+        DataType::Float64 => any::<f64>().prop_map(|v| Scalar::Float64(v.into())).boxed(),
+        DataType::String => "[a-c]{0,3}".prop_map(Scalar::String).boxed(),
+        DataType::Binary => prop::collection::vec(any::<u8>(), 0..3)
+            .prop_map(Scalar::Binary)
+            .boxed(),
+        DataType::Variant => prop_oneof![
+            any::<i64>().prop_map(|v| v.to_string()),
+            "null|true|false|\"[a-c]{0,3}\"",
+        ]
+        .prop_map(|json| Scalar::Variant(variant::parse_json(&json).unwrap()))
+        .boxed(),
+        DataType::Date => any::<i32>().prop_map(Scalar::Date).boxed(),
+        DataType::Timestamp => any::<i64>().prop_map(Scalar::Timestamp).boxed(),
+        DataType::Interval => any::<i64>().prop_map(Scalar::Interval).boxed(),
+        DataType::Decimal { precision, scale } => {
+            let (precision, scale) = (*precision, *scale);
+            (-99999i128..99999)
+                .prop_map(move |value| Scalar::Decimal {
+                    value,
+                    precision,
+                    scale,
+                })
+                .boxed()
+        }
+        DataType::Nullable(ty) => prop_oneof![Just(Scalar::Null), arb_scalar(ty)].boxed(),
+        DataType::EmptyArray => Just(Scalar::EmptyArray).boxed(),
+        DataType::Array(ty) => arb_plain_column(ty, 0..4).prop_map(Scalar::Array).boxed(),
+        DataType::Map(key, value) => arb_plain_column(&map::map_entries_type(key, value), 0..4)
+            .prop_map(Scalar::Array)
+            .boxed(),
+        DataType::Tuple { fields_type, .. } => fields_type
+            .iter()
+            .map(arb_scalar)
+            .collect::<Vec<_>>()
+            .prop_map(Scalar::Tuple)
+            .boxed(),
+        DataType::Generic(_) => unreachable!("generic type {ty} has no values"),
+    }
+}
+
+#[cfg(test)]
+fn arb_plain_column(ty: &DataType, len: std::ops::Range<usize>) -> BoxedStrategy<Column> {
+    let ty = ty.clone();
+    prop::collection::vec(arb_scalar(&ty), len)
+        .prop_map(move |scalars| {
+            let mut builder = ColumnBuilder::with_capacity(&ty, scalars.len());
+            for scalar in &scalars {
+                builder.push(scalar.as_ref());
+            }
+            builder.build()
+        })
+        .boxed()
+}
+
+/// A column of the data type, which may be sliced, dictionary-encoded or
+/// constant.
+#[cfg(test)]
+fn arb_column(ty: &DataType) -> BoxedStrategy<Column> {
+    let sliced = arb_plain_column(ty, 1..12).prop_flat_map(|col| {
+        let len = col.len();
+        (0..len)
+            .prop_flat_map(move |start| (Just(start), start..=len))
+            .prop_map(move |(start, end)| col.slice(start..end))
+    });
+    let dictionary = arb_plain_column(ty, 1..6).prop_flat_map(|values| {
+        prop::collection::vec(0..values.len() as u32, 0..12).prop_map(move |keys| {
+            Column::Dictionary {
+                keys: keys.into(),
+                values: Box::new(values.clone()),
+            }
+        })
     });
+    let constant = (arb_scalar(ty), 0..12usize).prop_map(|(scalar, len)| Column::Const {
+        scalar: Box::new(scalar),
+        len,
+    });
+    prop_oneof![
+        3 => arb_plain_column(ty, 0..12),
+        2 => sliced,
+        1 => dictionary,
+        1 => constant,
+    ]
+    .boxed()
+}
+
+#[cfg(test)]
+proptest! {
+    #[test]
+    fn test_column_filter(
+        (col, filter) in arb_data_type().prop_flat_map(|ty| arb_column(&ty)).prop_flat_map(|col| {
+            let len = col.len();
+            (Just(col), prop::collection::vec(any::<bool>(), len))
+        })
+    ) {
+        let filtered = col.filter(&filter.clone().into());
+        let expected = col
+            .iter()
+            .zip(&filter)
+            .filter(|(_, keep)| **keep)
+            .map(|(row, _)| row)
+            .collect::<Vec<_>>();
+        prop_assert_eq!(filtered.data_type(), col.data_type());
+        prop_assert_eq!(filtered.len(), expected.len());
+        prop_assert_eq!(filtered.iter().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_column_take(
+        (col, indices) in arb_data_type().prop_flat_map(|ty| arb_column(&ty)).prop_flat_map(|col| {
+            let len = col.len() as u32;
+            let indices = if len == 0 {
+                Just(vec![]).boxed()
+            } else {
+                prop::collection::vec(0..len, 0..16).boxed()
+            };
+            (Just(col), indices)
+        })
+    ) {
+        let taken = col.take(&indices);
+        let expected = indices
+            .iter()
+            .map(|index| col.index(*index as usize))
+            .collect::<Vec<_>>();
+        prop_assert_eq!(taken.data_type(), col.data_type());
+        prop_assert_eq!(taken.len(), indices.len());
+        prop_assert_eq!(taken.iter().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_column_concat(
+        (ty, cols) in arb_data_type().prop_flat_map(|ty| {
+            (Just(ty.clone()), prop::collection::vec(arb_column(&ty), 1..4))
+        })
+    ) {
+        let concated = Column::concat(&cols);
+        let expected = cols.iter().flat_map(Column::iter).collect::<Vec<_>>();
+        // A constant column only knows the type of its scalar.
+        if cols.iter().any(|col| !matches!(col, Column::Const { .. })) {
+            prop_assert_eq!(concated.data_type(), ty);
+        }
+        prop_assert_eq!(concated.len(), expected.len());
+        prop_assert_eq!(concated.iter().collect::<Vec<_>>(), expected);
+    }
 
-    registry.register_function_factory("get_tuple", |params, args_type| {
-        let idx = *params.get(0)?;
-        let tuple_tys = match args_type.get(0) {
-            Some(DataType::Tuple(tys)) => tys,
-            _ => return None,
+    #[test]
+    fn test_column_scatter(
+        (col, indices) in arb_data_type().prop_flat_map(|ty| arb_column(&ty)).prop_flat_map(|col| {
+            let len = col.len();
+            (Just(col), prop::collection::vec(0..3u32, len))
+        })
+    ) {
+        let scattered = col.scatter(&indices, 3);
+        prop_assert_eq!(scattered.len(), 3);
+        for (partition, scattered) in scattered.iter().enumerate() {
+            let expected = col
+                .iter()
+                .zip(&indices)
+                .filter(|(_, index)| **index as usize == partition)
+                .map(|(row, _)| row)
+                .collect::<Vec<_>>();
+            prop_assert_eq!(scattered.data_type(), col.data_type());
+            prop_assert_eq!(scattered.iter().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_arrow_round_trip(
+        (ty, col) in arb_data_type().prop_flat_map(|ty| (Just(ty.clone()), arb_column(&ty)))
+    ) {
+        let arrow_ty = match &ty {
+            DataType::Nullable(ty) => (**ty).clone(),
+            ty => ty.clone(),
         };
-        if idx >= tuple_tys.len() {
-            return None;
+        prop_assert_eq!(DataType::from_arrow(&ty.to_arrow()), Ok(arrow_ty));
+
+        let back = Column::from_arrow(col.to_arrow().as_ref(), &ty);
+        prop_assert!(back.is_ok(), "{:?}", back);
+        let back = back.unwrap();
+        prop_assert_eq!(back.len(), col.len());
+        prop_assert_eq!(back.iter().collect::<Vec<_>>(), col.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_wire_round_trip(
+        col in arb_data_type().prop_flat_map(|ty| arb_column(&ty))
+    ) {
+        use typed_type_exercise::wire::{decode_column, encode_column};
+
+        let back = decode_column(&encode_column(&col));
+        prop_assert!(back.is_ok(), "{:?}", back);
+        let back = back.unwrap();
+        prop_assert_eq!(back.data_type(), col.data_type());
+        prop_assert_eq!(back.iter().collect::<Vec<_>>(), col.iter().collect::<Vec<_>>());
+    }
+
+    /// A corrupted payload is either rejected or decodes to a column that can
+    /// be read without panicking.
+    #[test]
+    fn test_wire_corrupted(
+        (col, pos, byte) in arb_data_type()
+            .prop_flat_map(|ty| arb_column(&ty))
+            .prop_flat_map(|col| {
+                let len = typed_type_exercise::wire::encode_column(&col).len();
+                (Just(col), 1..len.max(2), any::<u8>())
+            })
+    ) {
+        use typed_type_exercise::wire::{decode_column, encode_column};
+
+        let bytes = encode_column(&col);
+        prop_assert!(decode_column(&bytes[..pos.min(bytes.len() - 1)]).is_err());
+
+        let mut bytes = bytes;
+        if let Some(old) = bytes.get_mut(pos) {
+            *old = byte;
+        }
+        if let Ok(back) = decode_column(&bytes) {
+            back.data_type();
+            back.iter().for_each(drop);
         }
+    }
 
-        Some(Arc::new(Function {
-            signature: FunctionSignature {
-                name: "get_tuple",
-                args_type: vec![DataType::Tuple(tuple_tys.to_vec())],
-                return_type: tuple_tys[idx].clone(),
-                property: FunctionProperty::default().preserve_not_null(true),
-            },
-            eval: Box::new(move |args, _| match &args[0] {
-                ValueRef::Scalar(Scalar::Tuple(fields)) => Value::Scalar(fields[idx].to_owned()),
-                ValueRef::Column(Column::Tuple { fields, .. }) => {
-                    Value::Column(fields[idx].to_owned())
-                }
-                _ => unreachable!(),
-            }),
-        }))
-    });
+    #[test]
+    fn test_ipc_round_trip(
+        (ty, col) in arb_data_type().prop_flat_map(|ty| (Just(ty.clone()), arb_column(&ty)))
+    ) {
+        let mut file = Vec::new();
+        let written = typed_type_exercise::ipc::write_ipc(&mut file, &[("col", col.clone())]);
+        prop_assert!(written.is_ok(), "{:?}", written);
+
+        let columns = typed_type_exercise::ipc::read_ipc(&mut std::io::Cursor::new(file));
+        prop_assert!(columns.is_ok(), "{:?}", columns);
+        let columns = columns.unwrap();
+        prop_assert_eq!(columns.len(), 1);
+        let back = &columns["col"];
+        prop_assert_eq!(back.len(), col.len(), "{}", ty);
+        prop_assert_eq!(back.iter().collect::<Vec<_>>(), col.iter().collect::<Vec<_>>());
+    }
+}
+
+/// The case files in `tests/cases`, one per function family, sorted by name.
+fn case_files() -> Vec<std::path::PathBuf> {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/cases");
+    let mut files = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect::<Vec<_>>();
+    files.sort();
+    files
+}
 
-    registry.register_function_factory("get_tuple", |params, args_type| {
-        let idx = *params.get(0)?;
-        let tuple_tys = match args_type.get(0) {
-            Some(DataType::Nullable(box DataType::Tuple(tys))) => tys,
-            _ => return None,
+/// Runs the cases of a case file and writes the file back with the output of
+/// every case. A case is an `eval:` line with the expression, followed by the
+/// columns it reads and an optional selection:
+///
+/// ```text
+/// eval: plus(a, b)
+/// a: Nullable<UInt8> = [10, NULL, 12]
+/// b: String = dictionary([0, 1, 0], ['x', 'y'])
+/// c: Int16 = const(3, 3)
+/// selection: [TRUE, FALSE, TRUE]
+/// ----
+/// <output>
+/// ```
+///
+/// The output of a case runs until the next `eval:` or `#` comment line, and
+/// is replaced by the output of this run.
+fn run_case_file(output: &mut impl Write, name: &str, text: &str) {
+    for case in read_cases(name, text) {
+        for line in &case.input {
+            writeln!(output, "{line}").unwrap();
+        }
+        writeln!(output, "----").unwrap();
+        run_case(
+            output,
+            case.source,
+            &case.schema,
+            case.columns,
+            case.selection,
+        );
+    }
+}
+
+/// A case of a case file, see `run_case_file`.
+struct Case<'a> {
+    /// The comments before the case, and the lines that define it.
+    input: Vec<&'a str>,
+    source: &'a str,
+    schema: HashMap<String, DataType>,
+    columns: HashMap<String, Column>,
+    selection: Option<Bitmap>,
+}
+
+fn read_cases<'a>(name: &str, text: &'a str) -> Vec<Case<'a>> {
+    let mut cases = Vec::new();
+    let mut input = Vec::new();
+    let mut lines = text.lines().enumerate().peekable();
+    while let Some((index, line)) = lines.next() {
+        if line.is_empty() {
+            continue;
+        }
+        input.push(line);
+        let source = match line.strip_prefix("eval:") {
+            Some(source) => source.trim(),
+            None if line.starts_with('#') => continue,
+            None => panic!(
+                "{name}:{}: expected `eval:` or a comment, but got `{line}`",
+                index + 1
+            ),
         };
-        if idx >= tuple_tys.len() {
-            return None;
+        let mut schema = HashMap::new();
+        let mut columns = HashMap::new();
+        let mut selection = None;
+        while let Some((index, line)) = lines.next_if(|(_, line)| {
+            !line.is_empty() && !line.starts_with("eval:") && !line.starts_with('#')
+        }) {
+            if line == "----" {
+                while lines
+                    .next_if(|(_, line)| !line.starts_with("eval:") && !line.starts_with('#'))
+                    .is_some()
+                {}
+                break;
+            }
+            input.push(line);
+            let result = match line.strip_prefix("selection:") {
+                Some(value) => bitmap_from_text(value).map(|bitmap| selection = Some(bitmap)),
+                None => column_from_text(line).map(|(column, ty, values)| {
+                    schema.insert(column.clone(), ty);
+                    if let Some(values) = values {
+                        columns.insert(column, values);
+                    }
+                }),
+            };
+            if let Err(err) = result {
+                panic!("{name}:{}: {err}", index + 1);
+            }
         }
+        cases.push(Case {
+            input: std::mem::take(&mut input),
+            source,
+            schema,
+            columns,
+            selection,
+        });
+    }
+    assert!(input.is_empty(), "{name}: comments after the last case");
+    cases
+}
+
+/// Reads a column definition `name: Type = values`. Without values, the column
+/// is only known to the parser, but not to the runtime.
+fn column_from_text(line: &str) -> Result<(String, DataType, Option<Column>), String> {
+    let (name, rest) = line
+        .split_once(':')
+        .ok_or_else(|| format!("expected a column definition, but got `{line}`"))?;
+    let (ty, values) = match rest.split_once('=') {
+        Some((ty, values)) => (ty, Some(values)),
+        None => (rest, None),
+    };
+    let ty = parse_data_type(ty.trim()).map_err(|err| err.to_string())?;
+    let values = values
+        .map(|values| {
+            let ast = parse(values.trim(), &HashMap::new()).map_err(|err| err.to_string())?;
+            column_from_ast(&ast, &ty)
+        })
+        .transpose()?;
+    Ok((name.trim().to_string(), ty, values))
+}
 
-        Some(Arc::new(Function {
-            signature: FunctionSignature {
-                name: "get_tuple",
-                args_type: vec![DataType::Nullable(Box::new(DataType::Tuple(
-                    tuple_tys.to_vec(),
-                )))],
-                return_type: DataType::Nullable(Box::new(tuple_tys[idx].clone())),
-                property: FunctionProperty::default().preserve_not_null(true),
+fn bitmap_from_text(text: &str) -> Result<Bitmap, String> {
+    let ast = parse(text.trim(), &HashMap::new()).map_err(|err| err.to_string())?;
+    match column_from_ast(&ast, &DataType::Boolean)? {
+        Column::Boolean(bitmap) => Ok(bitmap),
+        _ => Err(format!("expected a list of booleans, but got `{ast}`")),
+    }
+}
+
+/// Builds a column from `[value, ...]`, `dictionary([key, ...], values)` or
+/// `const(value, len)`.
+fn column_from_ast(ast: &AST, ty: &DataType) -> Result<Column, String> {
+    match ast {
+        AST::FunctionCall { name, args, .. } => match (name.as_str(), args.as_slice()) {
+            ("create_array", items) => {
+                let mut builder = ColumnBuilder::with_capacity(ty, items.len());
+                for item in items {
+                    builder.push(scalar_from_ast(item, ty)?.as_ref());
+                }
+                Ok(builder.build())
+            }
+            ("dictionary", [keys, values]) => {
+                let keys = match column_from_ast(keys, &DataType::UInt32)? {
+                    Column::UInt32(keys) => keys,
+                    _ => unreachable!(),
+                };
+                let values = column_from_ast(values, ty)?;
+                if let Some(key) = keys.iter().find(|key| **key as usize >= values.len()) {
+                    return Err(format!(
+                        "key {key} is out of bounds for {} values",
+                        values.len()
+                    ));
+                }
+                Ok(Column::Dictionary {
+                    keys,
+                    values: Box::new(values),
+                })
+            }
+            ("const", [scalar, len]) => {
+                let len = match scalar_from_ast(len, &DataType::UInt64)? {
+                    Scalar::UInt64(len) => len as usize,
+                    _ => unreachable!(),
+                };
+                Ok(Column::Const {
+                    scalar: Box::new(scalar_from_ast(scalar, ty)?),
+                    len,
+                })
+            }
+            _ => Err(format!("expected a column of type {ty}, but got `{ast}`")),
+        },
+        _ => Err(format!("expected a column of type {ty}, but got `{ast}`")),
+    }
+}
+
+/// Builds a scalar from a literal or from the syntax of an array or a tuple.
+/// Maps are written as arrays of key-value tuples. Dates and timestamps are
+/// strings or raw integers, variants are JSON strings.
+fn scalar_from_ast(ast: &AST, ty: &DataType) -> Result<Scalar, String> {
+    use typed_type_exercise::types::decimal::rescale;
+    use typed_type_exercise::types::temporal::{parse_date, parse_timestamp};
+    use typed_type_exercise::types::variant::parse_json;
+
+    let mismatch = || format!("expected a value of type {ty}, but got `{ast}`");
+    let int = |ast: &AST| -> Option<i128> {
+        match ast {
+            AST::Literal { lit, .. } => match *lit {
+                Literal::Int8(val) => Some(val.into()),
+                Literal::Int16(val) => Some(val.into()),
+                Literal::Int32(val) => Some(val.into()),
+                Literal::Int64(val) => Some(val.into()),
+                Literal::UInt8(val) => Some(val.into()),
+                Literal::UInt16(val) => Some(val.into()),
+                Literal::UInt32(val) => Some(val.into()),
+                Literal::UInt64(val) => Some(val.into()),
+                _ => None,
             },
-            eval: Box::new(move |args, _| match &args[0] {
-                ValueRef::Scalar(Scalar::Null) => Value::Scalar(Scalar::Null),
-                ValueRef::Scalar(Scalar::Tuple(fields)) => Value::Scalar(fields[idx].to_owned()),
-                ValueRef::Column(Column::Nullable {
-                    column: box Column::Tuple { fields, .. },
-                    validity,
-                }) => Value::Column(Column::Nullable {
-                    column: Box::new(fields[idx].to_owned()),
-                    validity: validity.clone(),
-                }),
-                _ => unreachable!(),
-            }),
-        }))
-    });
+            _ => None,
+        }
+    };
+    let number = |ast: &AST| -> Option<f64> {
+        match ast {
+            AST::Literal {
+                lit: Literal::Float32(val),
+                ..
+            } => Some(val.0.into()),
+            AST::Literal {
+                lit: Literal::Float64(val),
+                ..
+            } => Some(val.0),
+            ast => int(ast).map(|val| val as f64),
+        }
+    };
+    let string = |ast: &AST| -> Option<String> {
+        match ast {
+            AST::Literal {
+                lit: Literal::String(val),
+                ..
+            } => Some(val.clone()),
+            _ => None,
+        }
+    };
+    let items = |ast: &AST, name: &str| -> Option<Vec<AST>> {
+        match ast {
+            AST::FunctionCall {
+                name: call, args, ..
+            } if call == name => Some(args.clone()),
+            _ => None,
+        }
+    };
+    macro_rules! int_scalar {
+        ($variant:ident) => {
+            Scalar::$variant(
+                int(ast)
+                    .ok_or_else(mismatch)?
+                    .try_into()
+                    .map_err(|_| format!("`{ast}` is out of range for {ty}"))?,
+            )
+        };
+    }
+
+    if let AST::Literal {
+        lit: Literal::Null, ..
+    } = ast
+    {
+        return match ty {
+            DataType::Null | DataType::Nullable(_) => Ok(Scalar::Null),
+            _ => Err(mismatch()),
+        };
+    }
+    Ok(match ty {
+        DataType::Nullable(inner) => scalar_from_ast(ast, inner)?,
+        DataType::EmptyArray => match items(ast, "create_array") {
+            Some(items) if items.is_empty() => Scalar::EmptyArray,
+            _ => return Err(mismatch()),
+        },
+        DataType::Boolean => match ast {
+            AST::Literal {
+                lit: Literal::Boolean(val),
+                ..
+            } => Scalar::Boolean(*val),
+            _ => return Err(mismatch()),
+        },
+        DataType::UInt8 => int_scalar!(UInt8),
+        DataType::UInt16 => int_scalar!(UInt16),
+        DataType::UInt32 => int_scalar!(UInt32),
+        DataType::UInt64 => int_scalar!(UInt64),
+        DataType::Int8 => int_scalar!(Int8),
+        DataType::Int16 => int_scalar!(Int16),
+        DataType::Int32 => int_scalar!(Int32),
+        DataType::Int64 => int_scalar!(Int64),
+        DataType::Interval => int_scalar!(Interval),
+        DataType::Float32 => Scalar::Float32(F32(number(ast).ok_or_else(mismatch)? as f32)),
+        DataType::Float64 => Scalar::Float64(F64(number(ast).ok_or_else(mismatch)?)),
+        DataType::Decimal { precision, scale } => {
+            let (value, from_scale) = match ast {
+                AST::Literal {
+                    lit: Literal::Decimal { value, scale, .. },
+                    ..
+                } => (*value, *scale),
+                AST::Literal {
+                    lit: Literal::Float32(_) | Literal::Float64(_),
+                    ..
+                } => {
+                    let text = number(ast).unwrap().to_string();
+                    let (int, frac) = text.split_once('.').unwrap_or((&text, ""));
+                    let value = format!("{int}{frac}").parse().map_err(|_| mismatch())?;
+                    (value, frac.len() as u8)
+                }
+                ast => (int(ast).ok_or_else(mismatch)?, 0),
+            };
+            Scalar::Decimal {
+                value: rescale(value, from_scale, *scale, *precision)?,
+                precision: *precision,
+                scale: *scale,
+            }
+        }
+        DataType::Date => match string(ast) {
+            Some(text) => Scalar::Date(parse_date(&text)?),
+            None => int_scalar!(Date),
+        },
+        DataType::Timestamp => match string(ast) {
+            Some(text) => Scalar::Timestamp(parse_timestamp(&text)?),
+            None => int_scalar!(Timestamp),
+        },
+        DataType::String => Scalar::String(string(ast).ok_or_else(mismatch)?),
+        DataType::Binary => match ast {
+            AST::Literal {
+                lit: Literal::Binary(val),
+                ..
+            } => Scalar::Binary(val.clone()),
+            ast => Scalar::Binary(string(ast).ok_or_else(mismatch)?.into_bytes()),
+        },
+        DataType::Variant => Scalar::Variant(parse_json(&string(ast).ok_or_else(mismatch)?)?),
+        DataType::Array(item) => Scalar::Array(column_from_ast(ast, item).map_err(|_| mismatch())?),
+        DataType::Map(key, value) => {
+            let entry = DataType::Tuple {
+                fields_name: vec![None, None],
+                fields_type: vec![(**key).clone(), (**value).clone()],
+            };
+            Scalar::Array(column_from_ast(ast, &entry).map_err(|_| mismatch())?)
+        }
+        DataType::Tuple { fields_type, .. } => match items(ast, "create_tuple") {
+            Some(fields) if fields.len() == fields_type.len() => Scalar::Tuple(
+                fields
+                    .iter()
+                    .zip(fields_type)
+                    .map(|(field, ty)| scalar_from_ast(field, ty))
+                    .collect::<Result<_, _>>()?,
+            ),
+            _ => return Err(mismatch()),
+        },
+        DataType::Null | DataType::Generic(_) => return Err(mismatch()),
+    })
+}
+
+/// Parses, checks and evaluates an expression, and prints each step.
+fn run_case(
+    output: &mut impl Write,
+    source: &str,
+    schema: &HashMap<String, DataType>,
+    columns: HashMap<String, Column>,
+    selection: Option<Bitmap>,
+) {
+    let write_error = |output: &mut dyn Write, message: &str, span: Option<Range<usize>>| {
+        writeln!(output, "error: {message}").unwrap();
+        if let Some(span) = span {
+            writeln!(output, "{}", display_span(source, span)).unwrap();
+        }
+        writeln!(output).unwrap();
+    };
 
-    registry
+    let ast = match parse(source, schema) {
+        Ok(ast) => ast,
+        Err(err) => return write_error(output, &err.message, Some(err.span)),
+    };
+    writeln!(output, "ast: {ast}").unwrap();
+    let fn_registry = builtin_functions();
+    let (expr, ty, prop) = match type_check::check(&ast, &fn_registry) {
+        Ok(res) => res,
+        Err(err) => return write_error(output, &err.to_string(), err.ast().span()),
+    };
+    writeln!(output, "expr: {expr}").unwrap();
+    writeln!(output, "type: {ty}").unwrap();
+    writeln!(output, "property: {prop}").unwrap();
+    if let Some(selection) = &selection {
+        writeln!(output, "selection: {selection:?}").unwrap();
+    }
+    let runtime = Runtime { columns, selection };
+    match runtime.run(&expr) {
+        Ok(result) => writeln!(output, "result: {}\n", runtime.compact(result)).unwrap(),
+        Err(err) => write_error(output, &err.to_string(), err.span.clone()),
+    }
 }
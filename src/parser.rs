@@ -0,0 +1,807 @@
+use std::{collections::HashMap, iter::Peekable, ops::Range, str::CharIndices};
+
+use crate::{
+    expr::{Literal, AST},
+    property::ValueProperty,
+    types::{
+        decimal::{rescale, MAX_DECIMAL_PRECISION},
+        DataType, F32, F64,
+    },
+};
+
+/// An error in the expression text, located by the span of the offending token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    pub span: Range<usize>,
+}
+
+/// Parses a SQL expression into an `AST`. The types of the columns come from
+/// `schema`.
+///
+/// The operators are, from the loosest binding to the tightest: `OR`, `AND`,
+/// `NOT`, `+` and `-`, `*` and `/`, and the postfix cast `::`. A number
+/// without a type, like `10`, is given the smallest integer type that holds
+/// it, or `Float64` if it has a fraction.
+///
+/// There is no unary minus operator. A `-` directly followed by a number, as
+/// in `-1` or `-1::Int8`, is part of the number literal, so `-a` or `- 1` is
+/// an error; write `0 - a` instead.
+pub fn parse(text: &str, schema: &HashMap<String, DataType>) -> Result<AST, ParseError> {
+    let mut parser = Parser {
+        text,
+        tokens: tokenize(text)?,
+        pos: 0,
+        schema,
+    };
+    let ast = parser.parse_or()?;
+    match parser.peek() {
+        Some(_) => Err(parser.unexpected()),
+        None => Ok(ast),
+    }
+}
+
+/// Parses a data type in the form it is displayed, such as `Nullable<UInt8>`.
+pub fn parse_data_type(text: &str) -> Result<DataType, ParseError> {
+    let mut parser = Parser {
+        text,
+        tokens: tokenize(text)?,
+        pos: 0,
+        schema: &HashMap::new(),
+    };
+    let ty = parser.parse_type()?;
+    match parser.peek() {
+        Some(_) => Err(parser.unexpected()),
+        None => Ok(ty),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident,
+    Number,
+    String(String),
+    Binary(Vec<u8>),
+    Symbol(&'static str),
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    span: Range<usize>,
+}
+
+const SYMBOLS: &[&str] = &["::", "+", "-", "*", "/", "(", ")", "[", "]", ",", "<", ">"];
+
+fn tokenize(text: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        let kind = if c.is_whitespace() {
+            chars.next();
+            continue;
+        } else if (c == 'x' || c == 'X') && text[start + 1..].starts_with('\'') {
+            chars.next();
+            let hex = lex_string(text, &mut chars)?;
+            TokenKind::Binary(parse_hex(&hex).ok_or_else(|| ParseError {
+                message: "invalid hexadecimal literal".to_string(),
+                span: start..end_of(text, &mut chars),
+            })?)
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            while chars
+                .next_if(|(_, c)| c.is_ascii_alphanumeric() || *c == '_')
+                .is_some()
+            {}
+            TokenKind::Ident
+        } else if c.is_ascii_digit() {
+            while chars.next_if(|(_, c)| c.is_ascii_digit()).is_some() {}
+            if chars.next_if(|(_, c)| *c == '.').is_some() {
+                while chars.next_if(|(_, c)| c.is_ascii_digit()).is_some() {}
+            }
+            TokenKind::Number
+        } else if c == '\'' {
+            TokenKind::String(lex_string(text, &mut chars)?)
+        } else if let Some(symbol) = SYMBOLS
+            .iter()
+            .find(|symbol| text[start..].starts_with(**symbol))
+        {
+            for _ in 0..symbol.len() {
+                chars.next();
+            }
+            TokenKind::Symbol(symbol)
+        } else {
+            return Err(ParseError {
+                message: format!("unexpected character `{c}`"),
+                span: start..start + c.len_utf8(),
+            });
+        };
+        tokens.push(Token {
+            kind,
+            span: start..end_of(text, &mut chars),
+        });
+    }
+    Ok(tokens)
+}
+
+fn end_of(text: &str, chars: &mut Peekable<CharIndices>) -> usize {
+    chars.peek().map(|(i, _)| *i).unwrap_or(text.len())
+}
+
+/// Reads a single-quoted string. A quote is escaped by doubling it or by a
+/// backslash, which also escapes `\n`, `\t`, `\r`, `\0` and itself.
+fn lex_string(text: &str, chars: &mut Peekable<CharIndices>) -> Result<String, ParseError> {
+    let (start, _) = chars.next().unwrap();
+    let mut string = String::new();
+    loop {
+        match chars.next() {
+            Some((_, '\'')) => {
+                if chars.next_if(|(_, c)| *c == '\'').is_some() {
+                    string.push('\'');
+                } else {
+                    return Ok(string);
+                }
+            }
+            Some((i, '\\')) => match chars.next() {
+                Some((_, 'n')) => string.push('\n'),
+                Some((_, 't')) => string.push('\t'),
+                Some((_, 'r')) => string.push('\r'),
+                Some((_, '0')) => string.push('\0'),
+                Some((_, c @ ('\\' | '\''))) => string.push(c),
+                Some((j, c)) => {
+                    return Err(ParseError {
+                        message: format!("unknown escape `\\{c}`"),
+                        span: i..j + c.len_utf8(),
+                    })
+                }
+                None => break,
+            },
+            Some((_, c)) => string.push(c),
+            None => break,
+        }
+    }
+    Err(ParseError {
+        message: "unterminated string literal".to_string(),
+        span: start..text.len(),
+    })
+}
+
+fn parse_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+    schema: &'a HashMap<String, DataType>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn text_of(&self, token: &Token) -> &'a str {
+        &self.text[token.span.clone()]
+    }
+
+    fn peek_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Some(Token { kind: TokenKind::Symbol(s), .. }) if *s == symbol)
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(
+                token @ Token {
+                    kind: TokenKind::Ident,
+                    ..
+                },
+            ) => self.text_of(token).eq_ignore_ascii_case(keyword),
+            _ => false,
+        }
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> Option<Range<usize>> {
+        if self.peek_symbol(symbol) {
+            self.next().map(|token| token.span)
+        } else {
+            None
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> Option<Range<usize>> {
+        if self.peek_keyword(keyword) {
+            self.next().map(|token| token.span)
+        } else {
+            None
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<Range<usize>, ParseError> {
+        self.eat_symbol(symbol)
+            .ok_or_else(|| self.expected(&format!("`{symbol}`")))
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<Range<usize>, ParseError> {
+        self.eat_keyword(keyword)
+            .ok_or_else(|| self.expected(&format!("`{keyword}`")))
+    }
+
+    fn expected(&self, expected: &str) -> ParseError {
+        match self.peek() {
+            Some(token) => ParseError {
+                message: format!("expected {expected}, found `{}`", self.text_of(token)),
+                span: token.span.clone(),
+            },
+            None => ParseError {
+                message: format!("expected {expected}, found the end of the expression"),
+                span: self.text.len()..self.text.len(),
+            },
+        }
+    }
+
+    fn unexpected(&self) -> ParseError {
+        match self.peek() {
+            Some(token) => ParseError {
+                message: format!("unexpected `{}`", self.text_of(token)),
+                span: token.span.clone(),
+            },
+            None => ParseError {
+                message: "unexpected end of the expression".to_string(),
+                span: self.text.len()..self.text.len(),
+            },
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<AST, ParseError> {
+        let mut lhs = self.parse_and()?;
+        while self.eat_keyword("OR").is_some() {
+            let rhs = self.parse_and()?;
+            lhs = binary_call("or", lhs, rhs);
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<AST, ParseError> {
+        let mut lhs = self.parse_not()?;
+        while self.eat_keyword("AND").is_some() {
+            let rhs = self.parse_not()?;
+            lhs = binary_call("and", lhs, rhs);
+        }
+        Ok(lhs)
+    }
+
+    fn parse_not(&mut self) -> Result<AST, ParseError> {
+        match self.eat_keyword("NOT") {
+            Some(span) => {
+                let arg = self.parse_not()?;
+                Ok(AST::FunctionCall {
+                    span: Some(span.start..arg.span().unwrap().end),
+                    name: "not".to_string(),
+                    params: vec![],
+                    args: vec![arg],
+                })
+            }
+            None => self.parse_additive(),
+        }
+    }
+
+    fn parse_additive(&mut self) -> Result<AST, ParseError> {
+        let mut lhs = self.parse_multiplicative()?;
+        loop {
+            let name = if self.eat_symbol("+").is_some() {
+                "plus"
+            } else if self.eat_symbol("-").is_some() {
+                "minus"
+            } else {
+                return Ok(lhs);
+            };
+            let rhs = self.parse_multiplicative()?;
+            lhs = binary_call(name, lhs, rhs);
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<AST, ParseError> {
+        let mut lhs = self.parse_cast()?;
+        loop {
+            let name = if self.eat_symbol("*").is_some() {
+                "multiply"
+            } else if self.eat_symbol("/").is_some() {
+                "divide"
+            } else {
+                return Ok(lhs);
+            };
+            let rhs = self.parse_cast()?;
+            lhs = binary_call(name, lhs, rhs);
+        }
+    }
+
+    /// Parses the postfix casts `expr::Type`. A number followed by a numeric
+    /// type is read as a literal of that type, so that `-1::Int8` or
+    /// `1.50::Decimal(3, 2)` do not go through another type first.
+    fn parse_cast(&mut self) -> Result<AST, ParseError> {
+        let mut ast = match self.peek_number() {
+            Some(number) => {
+                let span = number.clone();
+                self.pos += if self.peek_symbol("-") { 2 } else { 1 };
+                let source = self.text;
+                let text = &source[number];
+                if self.peek_symbol("::") {
+                    let start = self.pos;
+                    self.pos += 1;
+                    let ty = self.parse_type()?;
+                    let span = span.start..self.tokens[self.pos - 1].span.end;
+                    match number_literal(text, Some(&ty)) {
+                        Some(lit) => AST::Literal {
+                            span: Some(span.clone()),
+                            lit: lit.map_err(|message| ParseError { message, span })?,
+                        },
+                        None => {
+                            self.pos = start;
+                            self.number_ast(text, span)?
+                        }
+                    }
+                } else {
+                    self.number_ast(text, span)?
+                }
+            }
+            None => self.parse_primary()?,
+        };
+        while self.eat_symbol("::").is_some() {
+            let ty = self.parse_type()?;
+            let span = ast.span().unwrap().start..self.tokens[self.pos - 1].span.end;
+            ast = AST::Cast {
+                span: Some(span),
+                expr: Box::new(ast),
+                dest_type: ty,
+            };
+        }
+        Ok(ast)
+    }
+
+    /// The span of the number at the cursor, including a leading minus sign
+    /// that is directly followed by the number.
+    /// `NaN` and `inf` count as numbers when they are cast to a float type.
+    fn peek_number(&self) -> Option<Range<usize>> {
+        let tokens = &self.tokens[self.pos..];
+        let (sign, tokens) = match tokens {
+            [Token {
+                kind: TokenKind::Symbol("-"),
+                span: sign,
+            }, rest @ ..]
+                if rest
+                    .first()
+                    .is_some_and(|token| token.span.start == sign.end) =>
+            {
+                (Some(sign), rest)
+            }
+            _ => (None, tokens),
+        };
+        let number = match tokens {
+            [number @ Token {
+                kind: TokenKind::Number,
+                ..
+            }, ..] => number,
+            [number @ Token {
+                kind: TokenKind::Ident,
+                ..
+            }, Token {
+                kind: TokenKind::Symbol("::"),
+                ..
+            }, ty @ Token {
+                kind: TokenKind::Ident,
+                ..
+            }, ..]
+                if matches!(&self.text[number.span.clone()], "NaN" | "inf")
+                    && matches!(&self.text[ty.span.clone()], "Float32" | "Float64") =>
+            {
+                number
+            }
+            _ => return None,
+        };
+        Some(sign.unwrap_or(&number.span).start..number.span.end)
+    }
+
+    fn number_ast(&self, text: &str, span: Range<usize>) -> Result<AST, ParseError> {
+        let lit = number_literal(text, None)
+            .unwrap()
+            .map_err(|message| ParseError {
+                message,
+                span: span.clone(),
+            })?;
+        Ok(AST::Literal {
+            span: Some(span),
+            lit,
+        })
+    }
+
+    fn parse_primary(&mut self) -> Result<AST, ParseError> {
+        let token = match self.peek() {
+            Some(token) => token.clone(),
+            None => return Err(self.unexpected()),
+        };
+        match &token.kind {
+            TokenKind::String(string) => {
+                self.pos += 1;
+                Ok(AST::Literal {
+                    span: Some(token.span),
+                    lit: Literal::String(string.clone()),
+                })
+            }
+            TokenKind::Binary(bytes) => {
+                self.pos += 1;
+                Ok(AST::Literal {
+                    span: Some(token.span),
+                    lit: Literal::Binary(bytes.clone()),
+                })
+            }
+            TokenKind::Ident => self.parse_ident(token),
+            TokenKind::Symbol("(") => {
+                self.pos += 1;
+                if let Some(end) = self.eat_symbol(")") {
+                    return Ok(call("create_tuple", token.span.start..end.end, vec![]));
+                }
+                let first = self.parse_or()?;
+                if self.eat_symbol(")").is_some() {
+                    return Ok(first);
+                }
+                self.expect_symbol(",")?;
+                let mut fields = vec![first];
+                fields.extend(self.parse_list(")")?);
+                let end = self.tokens[self.pos - 1].span.end;
+                Ok(call("create_tuple", token.span.start..end, fields))
+            }
+            TokenKind::Symbol("[") => {
+                self.pos += 1;
+                let items = self.parse_list("]")?;
+                let end = self.tokens[self.pos - 1].span.end;
+                Ok(call("create_array", token.span.start..end, items))
+            }
+            TokenKind::Symbol("-") => Err(ParseError {
+                message: "unary `-` is only supported directly before a number".to_string(),
+                span: token.span,
+            }),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    /// Parses a keyword, a function call or a column reference.
+    fn parse_ident(&mut self, token: Token) -> Result<AST, ParseError> {
+        self.pos += 1;
+        let name = self.text_of(&token);
+        let literal = |lit| AST::Literal {
+            span: Some(token.span.clone()),
+            lit,
+        };
+        if name.eq_ignore_ascii_case("NULL") {
+            return Ok(literal(Literal::Null));
+        }
+        if name.eq_ignore_ascii_case("TRUE") {
+            return Ok(literal(Literal::Boolean(true)));
+        }
+        if name.eq_ignore_ascii_case("FALSE") {
+            return Ok(literal(Literal::Boolean(false)));
+        }
+        if name.eq_ignore_ascii_case("CAST") {
+            self.expect_symbol("(")?;
+            let expr = self.parse_or()?;
+            self.expect_keyword("AS")?;
+            let dest_type = self.parse_type()?;
+            let end = self.expect_symbol(")")?;
+            return Ok(AST::Cast {
+                span: Some(token.span.start..end.end),
+                expr: Box::new(expr),
+                dest_type,
+            });
+        }
+
+        if self.eat_symbol("(").is_none() {
+            let data_type = self.schema.get(name).cloned().ok_or_else(|| ParseError {
+                message: format!("column `{name}` does not exist"),
+                span: token.span.clone(),
+            })?;
+            let not_null = !data_type.is_null() && data_type.as_nullable().is_none();
+            return Ok(AST::ColumnRef {
+                span: Some(token.span),
+                name: name.to_string(),
+                data_type,
+                property: ValueProperty::default().not_null(not_null),
+            });
+        }
+
+        let mut args = self.parse_list(")")?;
+        let mut params = vec![];
+        if self.eat_symbol("(").is_some() {
+            params = args
+                .into_iter()
+                .map(|param| match param {
+                    AST::Literal { lit, .. } if literal_to_usize(&lit).is_some() => {
+                        Ok(literal_to_usize(&lit).unwrap())
+                    }
+                    param => Err(ParseError {
+                        message: format!(
+                            "parameter of function `{name}` must be a non-negative integer"
+                        ),
+                        span: param.span().unwrap(),
+                    }),
+                })
+                .collect::<Result<_, _>>()?;
+            args = self.parse_list(")")?;
+        }
+        Ok(AST::FunctionCall {
+            span: Some(token.span.start..self.tokens[self.pos - 1].span.end),
+            name: name.to_string(),
+            params,
+            args,
+        })
+    }
+
+    /// Parses comma separated expressions till the closing symbol, which
+    /// may follow a trailing comma.
+    fn parse_list(&mut self, close: &str) -> Result<Vec<AST>, ParseError> {
+        let mut items = Vec::new();
+        loop {
+            if self.eat_symbol(close).is_some() {
+                return Ok(items);
+            }
+            items.push(self.parse_or()?);
+            if self.eat_symbol(",").is_none() {
+                self.expect_symbol(close)?;
+                return Ok(items);
+            }
+        }
+    }
+
+    /// Parses a data type in the form it is displayed, such as
+    /// `Nullable<Array<UInt8>>` or `(a Int32, String)`.
+    fn parse_type(&mut self) -> Result<DataType, ParseError> {
+        if self.eat_symbol("(").is_some() {
+            let mut fields_name = Vec::new();
+            let mut fields_type = Vec::new();
+            while self.eat_symbol(")").is_none() {
+                let name = match self.peek() {
+                    Some(
+                        token @ Token {
+                            kind: TokenKind::Ident,
+                            ..
+                        },
+                    ) if !is_type_name(self.text_of(token)) => {
+                        let name = self.text_of(token).to_string();
+                        self.pos += 1;
+                        Some(name)
+                    }
+                    _ => None,
+                };
+                fields_name.push(name);
+                fields_type.push(self.parse_type()?);
+                if self.eat_symbol(",").is_none() {
+                    self.expect_symbol(")")?;
+                    break;
+                }
+            }
+            return Ok(DataType::Tuple {
+                fields_name,
+                fields_type,
+            });
+        }
+
+        let token = match self.peek() {
+            Some(
+                token @ Token {
+                    kind: TokenKind::Ident,
+                    ..
+                },
+            ) => token.clone(),
+            _ => return Err(self.expected("a type")),
+        };
+        self.pos += 1;
+        let name = self.text_of(&token);
+        let ty = match name {
+            "Boolean" => DataType::Boolean,
+            "String" => DataType::String,
+            "Binary" => DataType::Binary,
+            "Variant" => DataType::Variant,
+            "UInt8" => DataType::UInt8,
+            "UInt16" => DataType::UInt16,
+            "UInt32" => DataType::UInt32,
+            "UInt64" => DataType::UInt64,
+            "Int8" => DataType::Int8,
+            "Int16" => DataType::Int16,
+            "Int32" => DataType::Int32,
+            "Int64" => DataType::Int64,
+            "Float32" => DataType::Float32,
+            "Float64" => DataType::Float64,
+            "Date" => DataType::Date,
+            "Timestamp" => DataType::Timestamp,
+            "Interval" => DataType::Interval,
+            "Decimal" => {
+                self.expect_symbol("(")?;
+                let precision = self.parse_type_param()?;
+                self.expect_symbol(",")?;
+                let scale = self.parse_type_param()?;
+                let end = self.expect_symbol(")")?.end;
+                if precision == 0 || precision > MAX_DECIMAL_PRECISION || scale > precision {
+                    return Err(ParseError {
+                        message: format!("invalid decimal type Decimal({precision}, {scale})"),
+                        span: token.span.start..end,
+                    });
+                }
+                DataType::Decimal { precision, scale }
+            }
+            "Nullable" | "Array" => {
+                self.expect_symbol("<")?;
+                let inner = match self.eat_keyword("Nothing") {
+                    Some(_) => None,
+                    None => Some(self.parse_type()?),
+                };
+                self.expect_symbol(">")?;
+                match (name, inner) {
+                    ("Nullable", None) => DataType::Null,
+                    ("Nullable", Some(inner)) => DataType::Nullable(Box::new(inner)),
+                    (_, None) => DataType::EmptyArray,
+                    (_, Some(inner)) => DataType::Array(Box::new(inner)),
+                }
+            }
+            "Map" => {
+                self.expect_symbol("<")?;
+                let key = self.parse_type()?;
+                self.expect_symbol(",")?;
+                let value = self.parse_type()?;
+                self.expect_symbol(">")?;
+                DataType::Map(Box::new(key), Box::new(value))
+            }
+            _ => {
+                return Err(ParseError {
+                    message: format!("type `{name}` does not exist"),
+                    span: token.span,
+                })
+            }
+        };
+        Ok(ty)
+    }
+
+    fn parse_type_param(&mut self) -> Result<u8, ParseError> {
+        match self.peek() {
+            Some(
+                token @ Token {
+                    kind: TokenKind::Number,
+                    ..
+                },
+            ) => {
+                let param = self.text_of(token).parse().map_err(|_| ParseError {
+                    message: format!("invalid type parameter `{}`", self.text_of(token)),
+                    span: token.span.clone(),
+                })?;
+                self.pos += 1;
+                Ok(param)
+            }
+            _ => Err(self.expected("a number")),
+        }
+    }
+}
+
+fn is_type_name(name: &str) -> bool {
+    matches!(
+        name,
+        "Boolean"
+            | "String"
+            | "Binary"
+            | "Variant"
+            | "UInt8"
+            | "UInt16"
+            | "UInt32"
+            | "UInt64"
+            | "Int8"
+            | "Int16"
+            | "Int32"
+            | "Int64"
+            | "Float32"
+            | "Float64"
+            | "Date"
+            | "Timestamp"
+            | "Interval"
+            | "Decimal"
+            | "Nullable"
+            | "Array"
+            | "Map"
+    )
+}
+
+fn literal_to_usize(lit: &Literal) -> Option<usize> {
+    match lit {
+        Literal::UInt8(val) => Some(*val as usize),
+        Literal::UInt16(val) => Some(*val as usize),
+        Literal::UInt32(val) => Some(*val as usize),
+        Literal::UInt64(val) => usize::try_from(*val).ok(),
+        _ => None,
+    }
+}
+
+fn call(name: &str, span: Range<usize>, args: Vec<AST>) -> AST {
+    AST::FunctionCall {
+        span: Some(span),
+        name: name.to_string(),
+        params: vec![],
+        args,
+    }
+}
+
+fn binary_call(name: &str, lhs: AST, rhs: AST) -> AST {
+    let span = lhs.span().unwrap().start..rhs.span().unwrap().end;
+    call(name, span, vec![lhs, rhs])
+}
+
+/// Reads a number as a literal of the type, or of the smallest type that
+/// holds it if no type is given. Returns `None` if the type is not numeric.
+fn number_literal(text: &str, ty: Option<&DataType>) -> Option<Result<Literal, String>> {
+    fn parse<T: std::str::FromStr>(text: &str, ty: &str) -> Result<T, String> {
+        text.parse().map_err(|_| {
+            let digits = text.strip_prefix('-').unwrap_or(text);
+            if digits.bytes().all(|b| b.is_ascii_digit()) {
+                format!("number `{text}` is out of range for {ty}")
+            } else {
+                format!("invalid {ty} literal `{text}`")
+            }
+        })
+    }
+
+    let lit = match ty {
+        None if text.contains('.') => parse(text, "Float64").map(|val| Literal::Float64(F64(val))),
+        None if text.starts_with('-') => parse::<i64>(text, "Int64").map(|val| {
+            if let Ok(val) = i8::try_from(val) {
+                Literal::Int8(val)
+            } else if let Ok(val) = i16::try_from(val) {
+                Literal::Int16(val)
+            } else if let Ok(val) = i32::try_from(val) {
+                Literal::Int32(val)
+            } else {
+                Literal::Int64(val)
+            }
+        }),
+        None => parse::<u64>(text, "UInt64").map(|val| {
+            if let Ok(val) = u8::try_from(val) {
+                Literal::UInt8(val)
+            } else if let Ok(val) = u16::try_from(val) {
+                Literal::UInt16(val)
+            } else if let Ok(val) = u32::try_from(val) {
+                Literal::UInt32(val)
+            } else {
+                Literal::UInt64(val)
+            }
+        }),
+        Some(DataType::UInt8) => parse(text, "UInt8").map(Literal::UInt8),
+        Some(DataType::UInt16) => parse(text, "UInt16").map(Literal::UInt16),
+        Some(DataType::UInt32) => parse(text, "UInt32").map(Literal::UInt32),
+        Some(DataType::UInt64) => parse(text, "UInt64").map(Literal::UInt64),
+        Some(DataType::Int8) => parse(text, "Int8").map(Literal::Int8),
+        Some(DataType::Int16) => parse(text, "Int16").map(Literal::Int16),
+        Some(DataType::Int32) => parse(text, "Int32").map(Literal::Int32),
+        Some(DataType::Int64) => parse(text, "Int64").map(Literal::Int64),
+        Some(DataType::Float32) => parse(text, "Float32").map(|val| Literal::Float32(F32(val))),
+        Some(DataType::Float64) => parse(text, "Float64").map(|val| Literal::Float64(F64(val))),
+        Some(ty @ DataType::Decimal { precision, scale }) => {
+            let (integral, fraction) = text.split_once('.').unwrap_or((text, ""));
+            parse::<i128>(&format!("{integral}{fraction}"), &ty.to_string())
+                .and_then(|value| rescale(value, fraction.len() as u8, *scale, *precision))
+                .map(|value| Literal::Decimal {
+                    value,
+                    precision: *precision,
+                    scale: *scale,
+                })
+        }
+        Some(_) => return None,
+    };
+    Some(lit)
+}
@@ -1,23 +1,88 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, str::Utf8Error};
+
+use arrow2::bitmap::Bitmap;
 
 use crate::{
-    expr::{Expr, Literal},
-    types::{any::AnyType, DataType},
+    display::{display_binary, display_decimal},
+    expr::{Expr, Literal, Span},
+    function::EvalContext,
+    types::{
+        any::AnyType,
+        decimal::rescale,
+        map::map_entries_type,
+        temporal::{
+            date_to_timestamp, format_date, format_timestamp, parse_date, parse_timestamp,
+            timestamp_to_date,
+        },
+        variant::{parse_json, to_json, upcast_to_variant},
+        DataType, F64,
+    },
     util::constant_bitmap,
     values::{Column, Value},
-    values::{ColumnBuilder, Scalar},
+    values::{ColumnBuilder, Scalar, ScalarRef},
 };
 
+/// An error raised while evaluating an expression. Besides the message, it
+/// records as much as is known about where the evaluation failed: the
+/// function, the row of the input and the offending value.
+#[derive(Debug, Clone, Default)]
+pub struct EvalError {
+    pub span: Span,
+    pub function: Option<String>,
+    pub row: Option<usize>,
+    pub value: Option<String>,
+    pub message: String,
+}
+
+impl EvalError {
+    pub fn new(message: impl Into<String>) -> Self {
+        EvalError {
+            message: message.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn span(mut self, span: Span) -> Self {
+        self.span = span;
+        self
+    }
+
+    pub fn function(mut self, function: impl Into<String>) -> Self {
+        self.function = Some(function.into());
+        self
+    }
+
+    pub fn row(mut self, row: usize) -> Self {
+        self.row = Some(row);
+        self
+    }
+
+    pub fn value(mut self, value: impl Into<String>) -> Self {
+        self.value = Some(value.into());
+        self
+    }
+}
+
 pub struct Runtime {
     pub columns: HashMap<String, Column>,
+    /// The visible rows of the columns. Functions skip the hidden rows, so the
+    /// values of those rows in the results are unspecified.
+    pub selection: Option<Bitmap>,
 }
 
 impl Runtime {
-    pub fn run(&self, expr: &Expr) -> Value<AnyType> {
+    pub fn run(&self, expr: &Expr) -> Result<Value<AnyType>, EvalError> {
         match expr {
-            Expr::Literal(lit) => Value::Scalar(self.run_lit(lit)),
-            Expr::ColumnRef { name } => Value::Column(self.columns[name].clone()),
+            Expr::Literal { lit, .. } => Ok(Value::Scalar(self.run_lit(lit))),
+            Expr::ColumnRef { span, name } => {
+                match self.columns.get(name) {
+                    Some(col) => Ok(Value::Column(col.clone())),
+                    None => Err(EvalError::new(format!("column `{name}` does not exist"))
+                        .span(span.clone())),
+                }
+            }
             Expr::FunctionCall {
+                span,
                 function,
                 args,
                 generics,
@@ -26,125 +91,321 @@ impl Runtime {
                 let cols = args
                     .iter()
                     .map(|(expr, _)| self.run(expr))
-                    .collect::<Vec<_>>();
+                    .collect::<Result<Vec<_>, _>>()?;
                 let cols_ref = cols.iter().map(Value::as_ref).collect::<Vec<_>>();
-                (function.eval)(cols_ref.as_slice(), generics)
+                let ctx = EvalContext {
+                    generics,
+                    selection: self.selection.as_ref(),
+                };
+                (function.eval)(cols_ref.as_slice(), &ctx).map_err(|err| {
+                    let err = err.span(span.clone());
+                    if err.function.is_none() {
+                        err.function(function.signature.name)
+                    } else {
+                        err
+                    }
+                })
             }
-            Expr::Cast { expr, dest_type } => {
-                let value = self.run(expr);
-                // TODO: remove me
-                let desc_value = format!("{}", value);
-                self.run_cast(value, dest_type)
-                    .unwrap_or_else(|| panic!("{desc_value} can not be cast to {dest_type}"))
+            Expr::Cast {
+                span,
+                expr,
+                dest_type,
+            } => {
+                let value = self.run(expr)?;
+                self.run_cast(value, dest_type).map_err(|err| {
+                    let err = err.span(span.clone());
+                    if err.function.is_none() {
+                        err.function(format!("cast<dest_type={dest_type}>"))
+                    } else {
+                        err
+                    }
+                })
             }
         }
     }
 
-    pub fn run_cast(&self, input: Value<AnyType>, dest_type: &DataType) -> Option<Value<AnyType>> {
+    /// Drops the hidden rows from a result.
+    pub fn compact(&self, value: Value<AnyType>) -> Value<AnyType> {
+        match (value, &self.selection) {
+            (Value::Column(col), Some(selection)) => Value::Column(col.filter(selection)),
+            (value, _) => value,
+        }
+    }
+
+    pub fn run_cast(
+        &self,
+        input: Value<AnyType>,
+        dest_type: &DataType,
+    ) -> Result<Value<AnyType>, EvalError> {
         match input {
             Value::Scalar(scalar) => match (scalar, dest_type) {
-                (Scalar::Null, DataType::Nullable(_)) => Some(Value::Scalar(Scalar::Null)),
+                (Scalar::Null, DataType::Nullable(_)) => Ok(Value::Scalar(Scalar::Null)),
                 (Scalar::EmptyArray, DataType::Array(dest_ty)) => {
                     let column = ColumnBuilder::with_capacity(dest_ty, 0).build();
-                    Some(Value::Scalar(Scalar::Array(column)))
+                    Ok(Value::Scalar(Scalar::Array(column)))
                 }
                 (scalar, DataType::Nullable(dest_ty)) => {
                     self.run_cast(Value::Scalar(scalar), dest_ty)
                 }
                 (Scalar::Array(array), DataType::Array(dest_ty)) => {
-                    let array = self
-                        .run_cast(Value::Column(array), dest_ty)?
-                        .into_column()
-                        .ok()
-                        .unwrap();
-                    Some(Value::Scalar(Scalar::Array(array)))
+                    let array = self.cast_column(array, dest_ty, None)?;
+                    Ok(Value::Scalar(Scalar::Array(array)))
                 }
-                (Scalar::UInt8(val), DataType::UInt16) => {
-                    Some(Value::Scalar(Scalar::UInt16(val as u16)))
+                (scalar @ Scalar::Array(_), DataType::Map(key, value)) => {
+                    self.run_cast(Value::Scalar(scalar), &map_entries_type(key, value))
                 }
-                (Scalar::Int8(val), DataType::Int16) => {
-                    Some(Value::Scalar(Scalar::Int16(val as i16)))
-                }
-                (Scalar::UInt8(val), DataType::Int16) => {
-                    Some(Value::Scalar(Scalar::Int16(val as i16)))
+                (Scalar::Tuple(fields), DataType::Tuple { fields_type, .. }) => {
+                    let fields = fields
+                        .into_iter()
+                        .zip(fields_type)
+                        .map(|(field, ty)| {
+                            Ok(self
+                                .run_cast(Value::Scalar(field), ty)?
+                                .into_scalar()
+                                .ok()
+                                .unwrap())
+                        })
+                        .collect::<Result<_, EvalError>>()?;
+                    Ok(Value::Scalar(Scalar::Tuple(fields)))
                 }
                 (scalar @ Scalar::Boolean(_), DataType::Boolean)
                 | (scalar @ Scalar::String(_), DataType::String)
+                | (scalar @ Scalar::Binary(_), DataType::Binary)
+                | (scalar @ Scalar::Variant(_), DataType::Variant)
                 | (scalar @ Scalar::UInt8(_), DataType::UInt8)
+                | (scalar @ Scalar::UInt16(_), DataType::UInt16)
+                | (scalar @ Scalar::UInt32(_), DataType::UInt32)
+                | (scalar @ Scalar::UInt64(_), DataType::UInt64)
                 | (scalar @ Scalar::Int8(_), DataType::Int8)
                 | (scalar @ Scalar::Int16(_), DataType::Int16)
+                | (scalar @ Scalar::Int32(_), DataType::Int32)
+                | (scalar @ Scalar::Int64(_), DataType::Int64)
+                | (scalar @ Scalar::Float32(_), DataType::Float32)
+                | (scalar @ Scalar::Float64(_), DataType::Float64)
+                | (scalar @ Scalar::Date(_), DataType::Date)
+                | (scalar @ Scalar::Timestamp(_), DataType::Timestamp)
+                | (scalar @ Scalar::Interval(_), DataType::Interval)
                 | (scalar @ Scalar::Null, DataType::Null)
-                | (scalar @ Scalar::EmptyArray, DataType::EmptyArray) => {
-                    Some(Value::Scalar(scalar))
-                }
-                _ => None,
-            },
-            Value::Column(col) => match (col, dest_type) {
-                (Column::Null { len }, DataType::Nullable(dest_ty)) => {
-                    Some(Value::Column(Column::Nullable {
-                        column: Box::new(ColumnBuilder::with_capacity(dest_ty, len).build()),
-                        validity: constant_bitmap(false, len).into(),
-                    }))
-                }
-                (Column::EmptyArray { len }, DataType::Array(dest_ty)) => {
-                    Some(Value::Column(Column::Array {
-                        array: Box::new(ColumnBuilder::with_capacity(dest_ty, 0).build()),
-                        offsets: vec![0; len + 1],
-                    }))
-                }
-                (Column::Nullable { column, validity }, DataType::Nullable(dest_ty)) => {
-                    let column = self
-                        .run_cast(Value::Column(*column), &*dest_ty)?
-                        .into_column()
-                        .ok()
-                        .unwrap();
-                    Some(Value::Column(Column::Nullable {
-                        column: Box::new(column),
-                        validity,
-                    }))
-                }
-                (col, DataType::Nullable(dest_ty)) => {
-                    let column = self
-                        .run_cast(Value::Column(col), &*dest_ty)?
-                        .into_column()
-                        .ok()
-                        .unwrap();
-                    Some(Value::Column(Column::Nullable {
-                        validity: constant_bitmap(true, column.len()).into(),
-                        column: Box::new(column),
+                | (scalar @ Scalar::EmptyArray, DataType::EmptyArray) => Ok(Value::Scalar(scalar)),
+                (scalar, DataType::Decimal { precision, scale })
+                    if decimal_scalar_value(&scalar).is_some() =>
+                {
+                    let (value, from_scale) = decimal_scalar_value(&scalar).unwrap();
+                    let value = rescale(value, from_scale, *scale, *precision)
+                        .map_err(|msg| EvalError::new(msg).value(scalar.to_string()))?;
+                    Ok(Value::Scalar(Scalar::Decimal {
+                        value,
+                        precision: *precision,
+                        scale: *scale,
                     }))
                 }
-                (Column::Array { array, offsets }, DataType::Array(dest_ty)) => {
-                    let array = self
-                        .run_cast(Value::Column(*array), &*dest_ty)?
-                        .into_column()
-                        .ok()
-                        .unwrap();
-                    Some(Value::Column(Column::Array {
-                        array: Box::new(array),
-                        offsets,
-                    }))
+                (Scalar::String(text), DataType::Binary) => {
+                    Ok(Value::Scalar(Scalar::Binary(text.into_bytes())))
                 }
-                (Column::UInt8(column), DataType::UInt16) => Some(Value::Column(Column::UInt16(
-                    column.iter().map(|v| *v as u16).collect(),
-                ))),
-                (Column::Int8(column), DataType::Int16) => Some(Value::Column(Column::Int16(
-                    column.iter().map(|v| *v as i16).collect(),
-                ))),
-                (Column::UInt8(column), DataType::Int16) => Some(Value::Column(Column::Int16(
-                    column.iter().map(|v| *v as i16).collect(),
-                ))),
-                (col @ Column::Boolean(_), DataType::Boolean)
-                | (col @ Column::String { .. }, DataType::String)
-                | (col @ Column::UInt8(_), DataType::UInt8)
-                | (col @ Column::Int8(_), DataType::Int8)
-                | (col @ Column::Int16(_), DataType::Int16)
-                | (col @ Column::Null { .. }, DataType::Null)
-                | (col @ Column::EmptyArray { .. }, DataType::EmptyArray) => {
-                    Some(Value::Column(col))
+                (Scalar::Binary(bytes), DataType::String) => match String::from_utf8(bytes) {
+                    Ok(text) => Ok(Value::Scalar(Scalar::String(text))),
+                    Err(err) => Err(invalid_utf8_error(err.utf8_error(), err.as_bytes())),
+                },
+                (
+                    scalar @ (Scalar::String(_) | Scalar::Date(_) | Scalar::Timestamp(_)),
+                    DataType::String | DataType::Date | DataType::Timestamp,
+                ) => cast_temporal(scalar.as_ref(), dest_type)
+                    .map(Value::Scalar)
+                    .map_err(|msg| EvalError::new(msg).value(display_cast_input(scalar.as_ref()))),
+                (scalar @ Scalar::Variant(_), DataType::String) | (scalar, DataType::Variant) => {
+                    cast_variant(scalar.as_ref(), dest_type)
+                        .map(Value::Scalar)
+                        .map_err(|msg| {
+                            EvalError::new(msg).value(display_cast_input(scalar.as_ref()))
+                        })
                 }
-                _ => None,
+                (scalar, dest_type) => match widen_number_scalar(&scalar, dest_type) {
+                    Some(scalar) => Ok(Value::Scalar(scalar)),
+                    None => Err(
+                        EvalError::new(format!("unable to cast scalar to {dest_type}"))
+                            .value(scalar.to_string()),
+                    ),
+                },
             },
+            Value::Column(col) => self
+                .cast_column(col, dest_type, self.selection.as_ref())
+                .map(Value::Column),
+        }
+    }
+
+    /// Casts a column, skipping the rows that are unset in `selection`. The
+    /// elements of an array column are selected along with their row.
+    fn cast_column(
+        &self,
+        col: Column,
+        dest_type: &DataType,
+        selection: Option<&Bitmap>,
+    ) -> Result<Column, EvalError> {
+        match (col, dest_type) {
+            (Column::Const { scalar, len }, dest_type) => {
+                let scalar = self
+                    .run_cast(Value::Scalar(*scalar), dest_type)?
+                    .into_scalar()
+                    .ok()
+                    .unwrap();
+                Ok(Column::Const {
+                    scalar: Box::new(scalar),
+                    len,
+                })
+            }
+            (col @ Column::Dictionary { .. }, dest_type) => {
+                self.cast_column(col.flatten().into_owned(), dest_type, selection)
+            }
+            (Column::Null { len }, DataType::Nullable(dest_ty)) => Ok(Column::Nullable {
+                column: Box::new(ColumnBuilder::with_capacity(dest_ty, len).build()),
+                validity: constant_bitmap(false, len).into(),
+            }),
+            (Column::EmptyArray { len }, DataType::Array(dest_ty)) => Ok(Column::Array {
+                array: Box::new(ColumnBuilder::with_capacity(dest_ty, 0).build()),
+                offsets: vec![0; len + 1],
+            }),
+            (
+                Column::Nullable {
+                    column: box Column::Binary { data, offsets },
+                    validity,
+                },
+                DataType::Nullable(box DataType::String),
+            ) => {
+                let rows = rows_to_cast(Some(&validity), selection);
+                let column = binary_to_string_column(&data, &offsets, rows.as_ref())?;
+                Ok(Column::Nullable {
+                    column: Box::new(column),
+                    validity,
+                })
+            }
+            (
+                Column::Nullable {
+                    column: box col @ Column::String { .. },
+                    validity,
+                },
+                DataType::Nullable(box dest_ty @ (DataType::Date | DataType::Timestamp)),
+            ) => {
+                // The values under the null rows are not valid strings to parse.
+                let rows = rows_to_cast(Some(&validity), selection);
+                let column = cast_column_rows(&col, dest_ty, rows.as_ref(), cast_temporal)?;
+                Ok(Column::Nullable {
+                    column: Box::new(column),
+                    validity,
+                })
+            }
+            (
+                Column::Nullable {
+                    column: box col @ Column::String { .. },
+                    validity,
+                },
+                DataType::Nullable(box DataType::Variant),
+            ) => {
+                let rows = rows_to_cast(Some(&validity), selection);
+                let column =
+                    cast_column_rows(&col, &DataType::Variant, rows.as_ref(), cast_variant)?;
+                Ok(Column::Nullable {
+                    column: Box::new(column),
+                    validity,
+                })
+            }
+            (Column::Nullable { column, validity }, DataType::Nullable(dest_ty)) => {
+                let column = self.cast_column(*column, dest_ty, selection)?;
+                Ok(Column::Nullable {
+                    column: Box::new(column),
+                    validity,
+                })
+            }
+            (col, DataType::Nullable(dest_ty)) => {
+                let column = self.cast_column(col, dest_ty, selection)?;
+                Ok(Column::Nullable {
+                    validity: constant_bitmap(true, column.len()).into(),
+                    column: Box::new(column),
+                })
+            }
+            (Column::Array { array, offsets }, DataType::Array(dest_ty)) => {
+                let selection = selection.map(|selection| {
+                    let mut elements = constant_bitmap(false, array.len());
+                    for (row, range) in offsets.windows(2).enumerate() {
+                        if selection.get_bit(row) {
+                            (range[0]..range[1]).for_each(|idx| elements.set(idx, true));
+                        }
+                    }
+                    Bitmap::from(elements)
+                });
+                let array = self.cast_column(*array, dest_ty, selection.as_ref())?;
+                Ok(Column::Array {
+                    array: Box::new(array),
+                    offsets,
+                })
+            }
+            (col @ Column::Array { .. }, DataType::Map(key, value)) => {
+                self.cast_column(col, &map_entries_type(key, value), selection)
+            }
+            (Column::Tuple { fields, len }, DataType::Tuple { fields_type, .. }) => {
+                let fields = fields
+                    .into_iter()
+                    .zip(fields_type)
+                    .map(|(field, ty)| self.cast_column(field, ty, selection))
+                    .collect::<Result<_, EvalError>>()?;
+                Ok(Column::Tuple { fields, len })
+            }
+            (col @ Column::Boolean(_), DataType::Boolean)
+            | (col @ Column::String { .. }, DataType::String)
+            | (col @ Column::Binary { .. }, DataType::Binary)
+            | (col @ Column::UInt8(_), DataType::UInt8)
+            | (col @ Column::UInt16(_), DataType::UInt16)
+            | (col @ Column::UInt32(_), DataType::UInt32)
+            | (col @ Column::UInt64(_), DataType::UInt64)
+            | (col @ Column::Int8(_), DataType::Int8)
+            | (col @ Column::Int16(_), DataType::Int16)
+            | (col @ Column::Int32(_), DataType::Int32)
+            | (col @ Column::Int64(_), DataType::Int64)
+            | (col @ Column::Float32(_), DataType::Float32)
+            | (col @ Column::Float64(_), DataType::Float64)
+            | (col @ Column::Date(_), DataType::Date)
+            | (col @ Column::Timestamp(_), DataType::Timestamp)
+            | (col @ Column::Interval(_), DataType::Interval)
+            | (col @ Column::Null { .. }, DataType::Null)
+            | (col @ Column::EmptyArray { .. }, DataType::EmptyArray) => Ok(col),
+            (col, DataType::Decimal { precision, scale })
+                if decimal_column_values(&col).is_some() =>
+            {
+                let (values, from_scale) = decimal_column_values(&col).unwrap();
+                let values = values
+                    .enumerate()
+                    .map(|(row, value)| {
+                        if selection.is_some_and(|selection| !selection.get_bit(row)) {
+                            return Ok(0);
+                        }
+                        rescale(value, from_scale, *scale, *precision).map_err(|msg| {
+                            EvalError::new(msg)
+                                .row(row)
+                                .value(display_decimal(value, from_scale))
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Column::Decimal {
+                    values: values.into(),
+                    precision: *precision,
+                    scale: *scale,
+                })
+            }
+            (Column::String { data, offsets }, DataType::Binary) => {
+                Ok(Column::Binary { data, offsets })
+            }
+            (Column::Binary { data, offsets }, DataType::String) => {
+                binary_to_string_column(&data, &offsets, selection)
+            }
+            (
+                col @ (Column::String { .. } | Column::Date(_) | Column::Timestamp(_)),
+                DataType::String | DataType::Date | DataType::Timestamp,
+            ) => cast_column_rows(&col, dest_type, selection, cast_temporal),
+            (col @ Column::Variant { .. }, DataType::Variant) => Ok(col),
+            (col @ Column::Variant { .. }, DataType::String) | (col, DataType::Variant) => {
+                cast_column_rows(&col, dest_type, selection, cast_variant)
+            }
+            (col, dest_type) => widen_number_column(&col, dest_type)
+                .ok_or_else(|| EvalError::new(format!("unable to cast column to {dest_type}"))),
         }
     }
 
@@ -153,10 +414,217 @@ impl Runtime {
             Literal::Null => Scalar::Null,
             Literal::Int8(val) => Scalar::Int8(*val),
             Literal::Int16(val) => Scalar::Int16(*val),
+            Literal::Int32(val) => Scalar::Int32(*val),
+            Literal::Int64(val) => Scalar::Int64(*val),
+            Literal::Float32(val) => Scalar::Float32(*val),
+            Literal::Float64(val) => Scalar::Float64(*val),
             Literal::UInt8(val) => Scalar::UInt8(*val),
             Literal::UInt16(val) => Scalar::UInt16(*val),
+            Literal::UInt32(val) => Scalar::UInt32(*val),
+            Literal::UInt64(val) => Scalar::UInt64(*val),
+            Literal::Decimal {
+                value,
+                precision,
+                scale,
+            } => Scalar::Decimal {
+                value: *value,
+                precision: *precision,
+                scale: *scale,
+            },
             Literal::Boolean(val) => Scalar::Boolean(*val),
             Literal::String(val) => Scalar::String(val.clone()),
+            Literal::Binary(val) => Scalar::Binary(val.clone()),
+        }
+    }
+}
+
+/// Lists the widening casts between number types, which are the implicit casts
+/// allowed by `type_check::cast_cost`.
+macro_rules! for_all_number_widenings {
+    ($macro:ident, $value:expr, $dest_type:expr) => {
+        $macro! {
+            $value, $dest_type;
+            UInt8 => [
+                UInt16: u16, UInt32: u32, UInt64: u64, Int16: i16, Int32: i32, Int64: i64,
+                Float32: f32, Float64: f64
+            ],
+            UInt16 => [
+                UInt32: u32, UInt64: u64, Int32: i32, Int64: i64, Float32: f32, Float64: f64
+            ],
+            UInt32 => [UInt64: u64, Int64: i64, Float64: f64],
+            Int8 => [Int16: i16, Int32: i32, Int64: i64, Float32: f32, Float64: f64],
+            Int16 => [Int32: i32, Int64: i64, Float32: f32, Float64: f64],
+            Int32 => [Int64: i64, Float64: f64]
+        }
+    };
+}
+
+fn widen_number_scalar(scalar: &Scalar, dest_type: &DataType) -> Option<Scalar> {
+    macro_rules! widen {
+        ($scalar:expr, $dest_type:expr; $($src:ident => [$($dest:ident: $ty:ty),*]),*) => {
+            match ($scalar, $dest_type) {
+                $($(
+                    (Scalar::$src(val), DataType::$dest) => {
+                        Some(Scalar::$dest((*val as $ty).into()))
+                    }
+                )*)*
+                (Scalar::Float32(val), DataType::Float64) => {
+                    Some(Scalar::Float64(F64(val.0 as f64)))
+                }
+                _ => None,
+            }
+        };
+    }
+    for_all_number_widenings!(widen, scalar, dest_type)
+}
+
+fn widen_number_column(col: &Column, dest_type: &DataType) -> Option<Column> {
+    macro_rules! widen {
+        ($col:expr, $dest_type:expr; $($src:ident => [$($dest:ident: $ty:ty),*]),*) => {
+            match ($col, $dest_type) {
+                $($(
+                    (Column::$src(col), DataType::$dest) => {
+                        Some(Column::$dest(col.iter().map(|val| *val as $ty).collect()))
+                    }
+                )*)*
+                (Column::Float32(col), DataType::Float64) => {
+                    Some(Column::Float64(col.iter().map(|val| *val as f64).collect()))
+                }
+                _ => None,
+            }
+        };
+    }
+    for_all_number_widenings!(widen, col, dest_type)
+}
+
+/// Validates the UTF-8 of every row of a binary column, skipping the rows that
+/// are unset in `rows`, which are left empty in the string column.
+fn binary_to_string_column(
+    data: &[u8],
+    offsets: &[usize],
+    rows: Option<&Bitmap>,
+) -> Result<Column, EvalError> {
+    let mut builder = ColumnBuilder::with_capacity(&DataType::String, offsets.len() - 1);
+    for (row, range) in offsets.windows(2).enumerate() {
+        if rows.is_some_and(|rows| !rows.get_bit(row)) {
+            builder.push_default();
+            continue;
+        }
+        let bytes = &data[range[0]..range[1]];
+        let text =
+            std::str::from_utf8(bytes).map_err(|err| invalid_utf8_error(err, bytes).row(row))?;
+        builder.push(ScalarRef::String(text));
+    }
+    Ok(builder.build())
+}
+
+fn invalid_utf8_error(err: Utf8Error, bytes: &[u8]) -> EvalError {
+    EvalError::new(format!(
+        "invalid UTF-8 sequence at byte {}",
+        err.valid_up_to()
+    ))
+    .value(display_binary(bytes))
+}
+
+/// Converts between dates and timestamps, and between them and their ISO-8601
+/// representation in strings.
+fn cast_temporal(scalar: ScalarRef, dest_type: &DataType) -> Result<Scalar, String> {
+    match (scalar, dest_type) {
+        (ScalarRef::Date(val), DataType::Timestamp) => {
+            date_to_timestamp(val).map(Scalar::Timestamp)
+        }
+        (ScalarRef::Timestamp(val), DataType::Date) => timestamp_to_date(val).map(Scalar::Date),
+        (ScalarRef::Date(val), DataType::String) => format_date(val).map(Scalar::String),
+        (ScalarRef::Timestamp(val), DataType::String) => format_timestamp(val).map(Scalar::String),
+        (ScalarRef::String(text), DataType::Date) => parse_date(text).map(Scalar::Date),
+        (ScalarRef::String(text), DataType::Timestamp) => {
+            parse_timestamp(text).map(Scalar::Timestamp)
+        }
+        (scalar, dest_type) => Err(format!("unable to cast {scalar} to {dest_type}")),
+    }
+}
+
+/// Parses JSON text into variants, serializes variants into JSON text, and
+/// upcasts the other scalars into variants.
+fn cast_variant(scalar: ScalarRef, dest_type: &DataType) -> Result<Scalar, String> {
+    match (scalar, dest_type) {
+        (ScalarRef::String(text), DataType::Variant) => parse_json(text).map(Scalar::Variant),
+        (ScalarRef::Variant(variant), DataType::String) => Ok(Scalar::String(to_json(variant))),
+        (scalar, DataType::Variant) => upcast_to_variant(scalar).map(Scalar::Variant),
+        (scalar, dest_type) => Err(format!("unable to cast {scalar} to {dest_type}")),
+    }
+}
+
+/// The rows that a cast converts: those that are both valid and selected.
+fn rows_to_cast(validity: Option<&Bitmap>, selection: Option<&Bitmap>) -> Option<Bitmap> {
+    match (validity, selection) {
+        (Some(validity), Some(selection)) => Some(arrow2::bitmap::and(validity, selection)),
+        (validity, selection) => validity.or(selection).cloned(),
+    }
+}
+
+/// Applies `cast` to every row of the column, skipping the rows that are unset
+/// in `rows`.
+fn cast_column_rows(
+    col: &Column,
+    dest_type: &DataType,
+    rows: Option<&Bitmap>,
+    cast: fn(ScalarRef, &DataType) -> Result<Scalar, String>,
+) -> Result<Column, EvalError> {
+    let mut builder = ColumnBuilder::with_capacity(dest_type, col.len());
+    for row in 0..col.len() {
+        if rows.is_some_and(|rows| !rows.get_bit(row)) {
+            builder.push_default();
+            continue;
         }
+        let scalar = col.index(row);
+        let value = cast(scalar.clone(), dest_type).map_err(|msg| {
+            EvalError::new(msg)
+                .row(row)
+                .value(display_cast_input(scalar))
+        })?;
+        builder.push(value.as_ref());
+    }
+    Ok(builder.build())
+}
+
+fn display_cast_input(scalar: ScalarRef) -> String {
+    match scalar {
+        ScalarRef::String(text) => text.to_string(),
+        scalar => scalar.to_string(),
+    }
+}
+
+/// Returns the value of a decimal or integer scalar as a decimal, along with
+/// its scale.
+fn decimal_scalar_value(scalar: &Scalar) -> Option<(i128, u8)> {
+    match scalar {
+        Scalar::Decimal { value, scale, .. } => Some((*value, *scale)),
+        Scalar::UInt8(val) => Some((*val as i128, 0)),
+        Scalar::UInt16(val) => Some((*val as i128, 0)),
+        Scalar::UInt32(val) => Some((*val as i128, 0)),
+        Scalar::UInt64(val) => Some((*val as i128, 0)),
+        Scalar::Int8(val) => Some((*val as i128, 0)),
+        Scalar::Int16(val) => Some((*val as i128, 0)),
+        Scalar::Int32(val) => Some((*val as i128, 0)),
+        Scalar::Int64(val) => Some((*val as i128, 0)),
+        _ => None,
+    }
+}
+
+/// Returns the values of a decimal or integer column as decimals, along with
+/// their scale.
+fn decimal_column_values(col: &Column) -> Option<(Box<dyn Iterator<Item = i128> + '_>, u8)> {
+    match col {
+        Column::Decimal { values, scale, .. } => Some((Box::new(values.iter().cloned()), *scale)),
+        Column::UInt8(col) => Some((Box::new(col.iter().map(|val| *val as i128)), 0)),
+        Column::UInt16(col) => Some((Box::new(col.iter().map(|val| *val as i128)), 0)),
+        Column::UInt32(col) => Some((Box::new(col.iter().map(|val| *val as i128)), 0)),
+        Column::UInt64(col) => Some((Box::new(col.iter().map(|val| *val as i128)), 0)),
+        Column::Int8(col) => Some((Box::new(col.iter().map(|val| *val as i128)), 0)),
+        Column::Int16(col) => Some((Box::new(col.iter().map(|val| *val as i128)), 0)),
+        Column::Int32(col) => Some((Box::new(col.iter().map(|val| *val as i128)), 0)),
+        Column::Int64(col) => Some((Box::new(col.iter().map(|val| *val as i128)), 0)),
+        _ => None,
     }
 }
@@ -4,25 +4,127 @@ use crate::{
     expr::{Expr, Literal, AST},
     function::{FunctionRegistry, FunctionSignature},
     property::ValueProperty,
-    types::DataType,
+    types::{
+        decimal::{decimal_size, decimal_super_type, integer_decimal_size},
+        variant::can_upcast_to_variant,
+        DataType,
+    },
 };
 
-pub fn check(ast: &AST, fn_registry: &FunctionRegistry) -> Option<(Expr, DataType, ValueProperty)> {
+/// The reason why an expression fails to type check. Every error carries the
+/// `AST` node where the check failed, whose span locates the error in the source.
+#[derive(Debug, Clone)]
+pub enum TypeCheckError {
+    UnknownFunction {
+        ast: AST,
+        name: String,
+    },
+    WrongNumberOfArgs {
+        ast: AST,
+        name: String,
+        expected: Vec<usize>,
+        found: usize,
+    },
+    /// None of the overloads accepts the argument types. Each candidate that
+    /// has been tried is listed along with the reason it failed.
+    NoMatchingOverload {
+        ast: AST,
+        name: String,
+        args_type: Vec<DataType>,
+        candidates: Vec<(FunctionSignature, UnifyError)>,
+    },
+    /// More than one overload accepts the argument types at the lowest cost.
+    AmbiguousOverload {
+        ast: AST,
+        name: String,
+        args_type: Vec<DataType>,
+        candidates: Vec<FunctionSignature>,
+    },
+    InvalidCast {
+        ast: AST,
+        src_ty: DataType,
+        dest_ty: DataType,
+    },
+    /// `get_field` is called with a name that the argument has no field for.
+    UnknownField {
+        ast: AST,
+        field: String,
+        data_type: DataType,
+    },
+    /// The field name passed to `get_field` is not a string literal.
+    FieldNameNotLiteral {
+        ast: AST,
+    },
+}
+
+impl TypeCheckError {
+    /// The `AST` node where the check failed.
+    pub fn ast(&self) -> &AST {
+        match self {
+            TypeCheckError::UnknownFunction { ast, .. }
+            | TypeCheckError::WrongNumberOfArgs { ast, .. }
+            | TypeCheckError::NoMatchingOverload { ast, .. }
+            | TypeCheckError::AmbiguousOverload { ast, .. }
+            | TypeCheckError::InvalidCast { ast, .. }
+            | TypeCheckError::UnknownField { ast, .. }
+            | TypeCheckError::FieldNameNotLiteral { ast } => ast,
+        }
+    }
+}
+
+/// The reason why the argument types can not be unified with a function signature.
+#[derive(Debug, Clone)]
+pub enum UnifyError {
+    CannotCast {
+        src_ty: DataType,
+        dest_ty: DataType,
+    },
+    /// Two arguments bind the same generic to types that have no common super type.
+    ConflictingGeneric {
+        idx: usize,
+        ty1: DataType,
+        ty2: DataType,
+    },
+    UnboundGeneric {
+        idx: usize,
+    },
+}
+
+pub fn check(
+    ast: &AST,
+    fn_registry: &FunctionRegistry,
+) -> Result<(Expr, DataType, ValueProperty), TypeCheckError> {
     match ast {
-        AST::Literal(lit) => {
+        AST::Literal { span, lit } => {
             let (ty, prop) = check_literal(lit);
-            Some((Expr::Literal(lit.clone()), ty, prop))
+            Ok((
+                Expr::Literal {
+                    span: span.clone(),
+                    lit: lit.clone(),
+                },
+                ty,
+                prop,
+            ))
         }
         AST::ColumnRef {
+            span,
             name,
             data_type,
             property,
-        } => Some((
-            Expr::ColumnRef { name: name.clone() },
+        } => Ok((
+            Expr::ColumnRef {
+                span: span.clone(),
+                name: name.clone(),
+            },
             data_type.clone(),
             *property,
         )),
-        AST::FunctionCall { name, args, params } => {
+        AST::FunctionCall { name, args, .. } if name == "get_field" => {
+            check_get_field(ast, args, fn_registry)
+        }
+        AST::FunctionCall {
+            name, args, params, ..
+        } => {
             let (mut args_expr, mut args_type, mut args_prop) =
                 (Vec::new(), Vec::new(), Vec::new());
 
@@ -34,6 +136,7 @@ pub fn check(ast: &AST, fn_registry: &FunctionRegistry) -> Option<(Expr, DataTyp
             }
 
             check_function(
+                ast,
                 name,
                 params,
                 &args_expr,
@@ -42,47 +145,195 @@ pub fn check(ast: &AST, fn_registry: &FunctionRegistry) -> Option<(Expr, DataTyp
                 fn_registry,
             )
         }
+        AST::Cast {
+            span,
+            expr,
+            dest_type,
+        } => {
+            let (expr, src_ty, prop) = check(expr, fn_registry)?;
+            if !can_cast_explicitly(&src_ty, dest_type) {
+                return Err(TypeCheckError::InvalidCast {
+                    ast: ast.clone(),
+                    src_ty,
+                    dest_ty: dest_type.clone(),
+                });
+            }
+            if src_ty == *dest_type {
+                return Ok((expr, src_ty, prop));
+            }
+            Ok((
+                Expr::Cast {
+                    span: span.clone(),
+                    expr: Box::new(expr),
+                    dest_type: dest_type.clone(),
+                },
+                dest_type.clone(),
+                prop,
+            ))
+        }
     }
 }
 
+/// Resolves `get_field(tuple, 'name')` into `get_tuple(idx)(tuple)`, since the
+/// position of the field is only known once the tuple has been checked.
+fn check_get_field(
+    ast: &AST,
+    args: &[AST],
+    fn_registry: &FunctionRegistry,
+) -> Result<(Expr, DataType, ValueProperty), TypeCheckError> {
+    let (tuple, field) = match args {
+        [tuple, AST::Literal {
+            lit: Literal::String(field),
+            ..
+        }] => (tuple, field),
+        [_, _] => return Err(TypeCheckError::FieldNameNotLiteral { ast: ast.clone() }),
+        _ => {
+            return Err(TypeCheckError::WrongNumberOfArgs {
+                ast: ast.clone(),
+                name: "get_field".to_string(),
+                expected: vec![2],
+                found: args.len(),
+            })
+        }
+    };
+
+    let (tuple, tuple_ty, prop) = check(tuple, fn_registry)?;
+    let idx = match &tuple_ty {
+        DataType::Tuple { fields_name, .. }
+        | DataType::Nullable(box DataType::Tuple { fields_name, .. }) => fields_name
+            .iter()
+            .position(|name| name.as_deref() == Some(field.as_str())),
+        _ => None,
+    }
+    .ok_or_else(|| TypeCheckError::UnknownField {
+        ast: ast.clone(),
+        field: field.clone(),
+        data_type: tuple_ty.clone(),
+    })?;
+
+    check_function(
+        ast,
+        "get_tuple",
+        &[idx],
+        &[tuple],
+        &[tuple_ty],
+        &[prop],
+        fn_registry,
+    )
+}
+
 pub fn check_literal(literal: &Literal) -> (DataType, ValueProperty) {
     match literal {
         Literal::Null => (DataType::Null, ValueProperty::default()),
         Literal::Int8(_) => (DataType::Int8, ValueProperty::default().not_null(true)),
         Literal::Int16(_) => (DataType::Int16, ValueProperty::default().not_null(true)),
+        Literal::Int32(_) => (DataType::Int32, ValueProperty::default().not_null(true)),
+        Literal::Int64(_) => (DataType::Int64, ValueProperty::default().not_null(true)),
+        Literal::Float32(_) => (DataType::Float32, ValueProperty::default().not_null(true)),
+        Literal::Float64(_) => (DataType::Float64, ValueProperty::default().not_null(true)),
         Literal::UInt8(_) => (DataType::UInt8, ValueProperty::default().not_null(true)),
         Literal::UInt16(_) => (DataType::UInt16, ValueProperty::default().not_null(true)),
+        Literal::UInt32(_) => (DataType::UInt32, ValueProperty::default().not_null(true)),
+        Literal::UInt64(_) => (DataType::UInt64, ValueProperty::default().not_null(true)),
+        Literal::Decimal {
+            precision, scale, ..
+        } => (
+            DataType::Decimal {
+                precision: *precision,
+                scale: *scale,
+            },
+            ValueProperty::default().not_null(true),
+        ),
         Literal::Boolean(_) => (DataType::Boolean, ValueProperty::default().not_null(true)),
         Literal::String(_) => (DataType::String, ValueProperty::default().not_null(true)),
+        Literal::Binary(_) => (DataType::Binary, ValueProperty::default().not_null(true)),
     }
 }
 
 pub fn check_function(
+    ast: &AST,
     name: &str,
     params: &[usize],
     args: &[Expr],
     args_type: &[DataType],
     args_prop: &[ValueProperty],
     fn_registry: &FunctionRegistry,
-) -> Option<(Expr, DataType, ValueProperty)> {
-    for (id, func) in fn_registry.search_candidates(name, params, args_type) {
-        if let Some((checked_args, return_ty, generics, prop)) =
-            try_check_function(args, args_type, args_prop, &func.signature)
-        {
-            return Some((
-                Expr::FunctionCall {
-                    id,
-                    function: func.clone(),
-                    generics,
-                    args: checked_args,
-                },
-                return_ty,
-                prop,
-            ));
+) -> Result<(Expr, DataType, ValueProperty), TypeCheckError> {
+    let candidates = fn_registry.search_candidates(name, params, args_type);
+
+    if candidates.is_empty() {
+        let builtins = fn_registry.funcs.get(name);
+        if builtins.is_none() && !fn_registry.factories.contains_key(name) {
+            return Err(TypeCheckError::UnknownFunction {
+                ast: ast.clone(),
+                name: name.to_string(),
+            });
+        }
+        if let Some(builtins) = builtins.filter(|_| params.is_empty()) {
+            let mut expected = builtins
+                .iter()
+                .map(|func| func.signature.args_type.len())
+                .collect::<Vec<_>>();
+            expected.sort_unstable();
+            expected.dedup();
+            return Err(TypeCheckError::WrongNumberOfArgs {
+                ast: ast.clone(),
+                name: name.to_string(),
+                expected,
+                found: args.len(),
+            });
+        }
+    }
+
+    let mut matches = Vec::new();
+    let mut fail_reasons = Vec::new();
+    for (id, func) in candidates {
+        match try_check_function(args, args_type, args_prop, &func.signature) {
+            Ok(checked) => matches.push((id, func, checked)),
+            Err(err) => fail_reasons.push((func.signature.clone(), err)),
         }
     }
 
-    None
+    let min_cost = match matches.iter().map(|(_, _, checked)| checked.4).min() {
+        Some(min_cost) => min_cost,
+        None => {
+            return Err(TypeCheckError::NoMatchingOverload {
+                ast: ast.clone(),
+                name: name.to_string(),
+                args_type: args_type.to_vec(),
+                candidates: fail_reasons,
+            })
+        }
+    };
+    let mut best_matches = matches
+        .into_iter()
+        .filter(|(_, _, checked)| checked.4 == min_cost)
+        .collect::<Vec<_>>();
+
+    if best_matches.len() > 1 {
+        return Err(TypeCheckError::AmbiguousOverload {
+            ast: ast.clone(),
+            name: name.to_string(),
+            args_type: args_type.to_vec(),
+            candidates: best_matches
+                .iter()
+                .map(|(_, func, _)| func.signature.clone())
+                .collect(),
+        });
+    }
+
+    let (id, func, (checked_args, return_ty, generics, prop, _)) = best_matches.remove(0);
+    Ok((
+        Expr::FunctionCall {
+            span: ast.span(),
+            id,
+            function: func,
+            generics,
+            args: checked_args,
+        },
+        return_ty,
+        prop,
+    ))
 }
 
 #[derive(Debug)]
@@ -99,53 +350,99 @@ impl Subsitution {
         subst
     }
 
-    pub fn merge(mut self, other: Self) -> Option<Self> {
-        for (idx, ty1) in other.0 {
-            if let Some(ty2) = self.0.remove(&idx) {
-                let common_ty = common_super_type(ty1, ty2)?;
+    pub fn merge(mut self, other: Self) -> Result<Self, UnifyError> {
+        for (idx, ty2) in other.0 {
+            if let Some(ty1) = self.0.remove(&idx) {
+                let common_ty = common_super_type(ty1.clone(), ty2.clone())
+                    .ok_or(UnifyError::ConflictingGeneric { idx, ty1, ty2 })?;
                 self.0.insert(idx, common_ty);
             } else {
-                self.0.insert(idx, ty1);
+                self.0.insert(idx, ty2);
             }
         }
 
-        Some(self)
+        Ok(self)
     }
 
-    pub fn apply(&self, ty: DataType) -> Option<DataType> {
+    pub fn apply(&self, ty: DataType) -> Result<DataType, UnifyError> {
         match ty {
-            DataType::Generic(idx) => self.0.get(&idx).cloned(),
-            DataType::Nullable(box ty) => Some(DataType::Nullable(Box::new(self.apply(ty)?))),
-            DataType::Array(box ty) => Some(DataType::Array(Box::new(self.apply(ty)?))),
-            ty => Some(ty),
+            DataType::Generic(idx) => self
+                .0
+                .get(&idx)
+                .cloned()
+                .ok_or(UnifyError::UnboundGeneric { idx }),
+            DataType::Nullable(box ty) => Ok(DataType::Nullable(Box::new(self.apply(ty)?))),
+            DataType::Array(box ty) => Ok(DataType::Array(Box::new(self.apply(ty)?))),
+            DataType::Map(box key, box value) => Ok(DataType::Map(
+                Box::new(self.apply(key)?),
+                Box::new(self.apply(value)?),
+            )),
+            DataType::Tuple {
+                fields_name,
+                fields_type,
+            } => Ok(DataType::Tuple {
+                fields_name,
+                fields_type: fields_type
+                    .into_iter()
+                    .map(|ty| self.apply(ty))
+                    .collect::<Result<_, _>>()?,
+            }),
+            ty => Ok(ty),
         }
     }
 }
 
-#[allow(clippy::type_complexity)]
-pub fn try_check_function(
-    args: &[Expr],
+/// Unifies the argument types with the signature of a function. Returns the
+/// substitution of the generics and the cost to call the function, which is
+/// the total cost of the implicit casts on the arguments.
+pub fn unify_signature(
     args_type: &[DataType],
-    args_prop: &[ValueProperty],
     sig: &FunctionSignature,
-) -> Option<(
-    Vec<(Expr, ValueProperty)>,
-    DataType,
-    Vec<DataType>,
-    ValueProperty,
-)> {
-    assert_eq!(args.len(), sig.args_type.len());
+) -> Result<(Subsitution, usize), UnifyError> {
+    assert_eq!(args_type.len(), sig.args_type.len());
 
     let substs = args_type
         .iter()
         .zip(&sig.args_type)
         .map(|(src_ty, dest_ty)| unify(src_ty, dest_ty))
-        .collect::<Option<Vec<_>>>()?;
+        .collect::<Result<Vec<_>, _>>()?;
     let subst = substs
         .into_iter()
         .try_reduce(|subst1, subst2| subst1.merge(subst2))?
         .unwrap_or_else(Subsitution::empty);
 
+    let mut cost = 0;
+    for (arg_type, sig_type) in args_type.iter().zip(&sig.args_type) {
+        let dest_type = subst.apply(sig_type.clone())?;
+        cost += cast_cost(arg_type, &dest_type).ok_or_else(|| UnifyError::CannotCast {
+            src_ty: arg_type.clone(),
+            dest_ty: dest_type.clone(),
+        })?;
+    }
+
+    Ok((subst, cost))
+}
+
+#[allow(clippy::type_complexity)]
+pub fn try_check_function(
+    args: &[Expr],
+    args_type: &[DataType],
+    args_prop: &[ValueProperty],
+    sig: &FunctionSignature,
+) -> Result<
+    (
+        Vec<(Expr, ValueProperty)>,
+        DataType,
+        Vec<DataType>,
+        ValueProperty,
+        usize,
+    ),
+    UnifyError,
+> {
+    assert_eq!(args.len(), sig.args_type.len());
+
+    let (subst, cost) = unify_signature(args_type, sig)?;
+
     let checked_args = args
         .iter()
         .zip(args_prop)
@@ -153,11 +450,12 @@ pub fn try_check_function(
         .zip(&sig.args_type)
         .map(|(((arg, arg_prop), arg_type), sig_type)| {
             let sig_type = subst.apply(sig_type.clone())?;
-            Some(if *arg_type == sig_type {
+            Ok(if *arg_type == sig_type {
                 (arg.clone(), *arg_prop)
             } else {
                 (
                     Expr::Cast {
+                        span: arg.span(),
                         expr: Box::new(arg.clone()),
                         dest_type: sig_type,
                     },
@@ -166,7 +464,7 @@ pub fn try_check_function(
                 )
             })
         })
-        .collect::<Option<Vec<_>>>()?;
+        .collect::<Result<Vec<_>, _>>()?;
 
     let return_type = subst.apply(sig.return_type.clone())?;
 
@@ -189,52 +487,160 @@ pub fn try_check_function(
         || (sig.property.preserve_not_null && args_prop.iter().all(|prop| prop.not_null));
     let prop = ValueProperty::default().not_null(not_null);
 
-    Some((checked_args, return_type, generics, prop))
+    Ok((checked_args, return_type, generics, prop, cost))
 }
 
-pub fn unify(src_ty: &DataType, dest_ty: &DataType) -> Option<Subsitution> {
+pub fn unify(src_ty: &DataType, dest_ty: &DataType) -> Result<Subsitution, UnifyError> {
     match (src_ty, dest_ty) {
         (DataType::Generic(_), _) => unreachable!("source type must not contain generic type"),
-        (ty, DataType::Generic(idx)) => Some(Subsitution::equation(*idx, ty.clone())),
-        (DataType::Null, DataType::Nullable(_)) => Some(Subsitution::empty()),
-        (DataType::EmptyArray, DataType::Array(_)) => Some(Subsitution::empty()),
+        (ty, DataType::Generic(idx)) => Ok(Subsitution::equation(*idx, ty.clone())),
+        (DataType::Null, DataType::Nullable(_)) => Ok(Subsitution::empty()),
+        (DataType::EmptyArray, DataType::Array(_)) => Ok(Subsitution::empty()),
         (DataType::Nullable(src_ty), DataType::Nullable(dest_ty)) => unify(src_ty, dest_ty),
         (src_ty, DataType::Nullable(dest_ty)) => unify(src_ty, dest_ty),
         (DataType::Array(src_ty), DataType::Array(dest_ty)) => unify(src_ty, dest_ty),
-        (DataType::Tuple(src_tys), DataType::Tuple(dest_tys))
-            if src_tys.len() == dest_tys.len() =>
-        {
+        (DataType::Map(src_key, src_value), DataType::Map(dest_key, dest_value)) => {
+            unify(src_key, dest_key)?.merge(unify(src_value, dest_value)?)
+        }
+        (
+            DataType::Tuple {
+                fields_type: src_tys,
+                ..
+            },
+            DataType::Tuple {
+                fields_type: dest_tys,
+                ..
+            },
+        ) if src_tys.len() == dest_tys.len() => {
             let substs = src_tys
                 .iter()
                 .zip(dest_tys)
                 .map(|(src_ty, dest_ty)| unify(src_ty, dest_ty))
-                .collect::<Option<Vec<_>>>()?;
+                .collect::<Result<Vec<_>, _>>()?;
             let subst = substs
                 .into_iter()
                 .try_reduce(|subst1, subst2| subst1.merge(subst2))?
                 .unwrap_or_else(Subsitution::empty);
-            Some(subst)
+            Ok(subst)
         }
-        (src_ty, dest_ty) if can_cast_to(src_ty, dest_ty) => Some(Subsitution::empty()),
-        _ => None,
+        (src_ty, dest_ty) if can_cast_to(src_ty, dest_ty) => Ok(Subsitution::empty()),
+        _ => Err(UnifyError::CannotCast {
+            src_ty: src_ty.clone(),
+            dest_ty: dest_ty.clone(),
+        }),
     }
 }
 
 pub fn can_cast_to(src_ty: &DataType, dest_ty: &DataType) -> bool {
+    cast_cost(src_ty, dest_ty).is_some()
+}
+
+/// Returns whether `src_ty` can be converted to `dest_ty` by an explicit cast.
+/// Besides the implicit casts, it allows the conversions that may fail, such as
+/// between temporal types and strings, from bytes to UTF-8 strings, between
+/// JSON text and variants, between decimals of any precision and scale (rounding
+/// half away from zero), and naming the fields of an unnamed tuple.
+pub fn can_cast_explicitly(src_ty: &DataType, dest_ty: &DataType) -> bool {
     match (src_ty, dest_ty) {
-        (src_ty, dest_ty) if src_ty == dest_ty => true,
-        (DataType::Null, DataType::Nullable(_)) => true,
-        (DataType::EmptyArray, DataType::Array(_)) => true,
-        (DataType::Nullable(src_ty), DataType::Nullable(dest_ty)) => can_cast_to(src_ty, dest_ty),
-        (src_ty, DataType::Nullable(dest_ty)) => can_cast_to(src_ty, dest_ty),
-        (DataType::Array(src_ty), DataType::Array(dest_ty)) => can_cast_to(src_ty, dest_ty),
-        (DataType::UInt8, DataType::UInt16)
-        | (DataType::Int8, DataType::Int16)
-        | (DataType::UInt8, DataType::Int16) => true,
+        (src_ty, dest_ty) if can_cast_to(src_ty, dest_ty) => true,
+        (DataType::Nullable(box src_ty), DataType::Nullable(box dest_ty))
+        | (src_ty, DataType::Nullable(box dest_ty))
+        | (DataType::Array(box src_ty), DataType::Array(box dest_ty)) => {
+            can_cast_explicitly(src_ty, dest_ty)
+        }
+        (DataType::Map(src_key, src_value), DataType::Map(dest_key, dest_value)) => {
+            can_cast_explicitly(src_key, dest_key) && can_cast_explicitly(src_value, dest_value)
+        }
+        (
+            DataType::Tuple {
+                fields_name: src_names,
+                fields_type: src_tys,
+            },
+            DataType::Tuple {
+                fields_name: dest_names,
+                fields_type: dest_tys,
+            },
+        ) if src_tys.len() == dest_tys.len()
+            && (src_names == dest_names || src_names.iter().all(Option::is_none)) =>
+        {
+            src_tys
+                .iter()
+                .zip(dest_tys)
+                .all(|(src_ty, dest_ty)| can_cast_explicitly(src_ty, dest_ty))
+        }
+        (DataType::String, DataType::Binary) | (DataType::Binary, DataType::String) => true,
+        (DataType::String, DataType::Variant) | (DataType::Variant, DataType::String) => true,
+        (DataType::String, DataType::Date | DataType::Timestamp)
+        | (DataType::Date | DataType::Timestamp, DataType::String)
+        | (DataType::Timestamp, DataType::Date) => true,
+        (DataType::Decimal { .. }, DataType::Decimal { .. }) => true,
         _ => false,
     }
 }
 
+/// The cost of the implicit cast from `src_ty` to `dest_ty`, or `None` if the
+/// cast is not allowed. Widening within the same signedness is cheaper than
+/// changing the signedness, which is cheaper than casting an integer to float
+/// or decimal, which is cheaper than upcasting a scalar into `Variant`.
+/// Wrapping a value into `Nullable` adds 1.
+pub fn cast_cost(src_ty: &DataType, dest_ty: &DataType) -> Option<usize> {
+    match (src_ty, dest_ty) {
+        (src_ty, dest_ty) if src_ty == dest_ty => Some(0),
+        (DataType::Null, DataType::Nullable(_)) => Some(1),
+        (DataType::EmptyArray, DataType::Array(_)) => Some(1),
+        (DataType::Nullable(src_ty), DataType::Nullable(dest_ty)) => cast_cost(src_ty, dest_ty),
+        (src_ty, DataType::Nullable(dest_ty)) => Some(cast_cost(src_ty, dest_ty)? + 1),
+        (DataType::Array(src_ty), DataType::Array(dest_ty)) => cast_cost(src_ty, dest_ty),
+        (DataType::Map(src_key, src_value), DataType::Map(dest_key, dest_value)) => {
+            Some(cast_cost(src_key, dest_key)? + cast_cost(src_value, dest_value)?)
+        }
+        (
+            DataType::Tuple {
+                fields_name: src_names,
+                fields_type: src_tys,
+            },
+            DataType::Tuple {
+                fields_name: dest_names,
+                fields_type: dest_tys,
+            },
+        ) if src_names == dest_names => src_tys
+            .iter()
+            .zip(dest_tys)
+            .map(|(src_ty, dest_ty)| cast_cost(src_ty, dest_ty))
+            .sum(),
+        (DataType::Float32, DataType::Float64) => Some(1),
+        (DataType::Date, DataType::Timestamp) => Some(1),
+        (src_ty, DataType::Variant) if can_upcast_to_variant(src_ty) => Some(8),
+        (
+            DataType::Decimal {
+                precision: p1,
+                scale: s1,
+            },
+            DataType::Decimal {
+                precision: p2,
+                scale: s2,
+            },
+        ) if s1 <= s2 && p1 - s1 <= p2 - s2 => Some(1),
+        (src_ty, DataType::Decimal { precision, scale }) => {
+            let (int_precision, _) = integer_decimal_size(src_ty)?;
+            (int_precision <= precision - scale).then_some(7)
+        }
+        (src_ty, DataType::Float32) if number_width(src_ty)?.1 <= 1 => Some(5),
+        (src_ty, DataType::Float64) if number_width(src_ty)?.1 <= 2 => Some(6),
+        (src_ty, dest_ty) => {
+            let (src_signed, src_width) = number_width(src_ty)?;
+            let (dest_signed, dest_width) = number_width(dest_ty)?;
+            match (src_signed, dest_signed) {
+                (false, false) | (true, true) if src_width < dest_width => {
+                    Some(dest_width - src_width)
+                }
+                (false, true) if src_width < dest_width => Some(dest_width - src_width + 1),
+                _ => None,
+            }
+        }
+    }
+}
+
 pub fn common_super_type(ty1: DataType, ty2: DataType) -> Option<DataType> {
     match (ty1, ty2) {
         (ty1, ty2) if ty1 == ty2 => Some(ty1),
@@ -251,15 +657,90 @@ pub fn common_super_type(ty1: DataType, ty2: DataType) -> Option<DataType> {
         (DataType::Array(box ty1), DataType::Array(box ty2)) => {
             Some(DataType::Array(Box::new(common_super_type(ty1, ty2)?)))
         }
-        (DataType::UInt8, DataType::UInt16) | (DataType::UInt16, DataType::UInt8) => {
-            Some(DataType::UInt16)
+        (DataType::Map(box key1, box value1), DataType::Map(box key2, box value2)) => {
+            Some(DataType::Map(
+                Box::new(common_super_type(key1, key2)?),
+                Box::new(common_super_type(value1, value2)?),
+            ))
         }
-        (DataType::Int8, DataType::Int16) | (DataType::Int16, DataType::Int8) => {
-            Some(DataType::Int16)
+        (
+            DataType::Tuple {
+                fields_name: names1,
+                fields_type: tys1,
+            },
+            DataType::Tuple {
+                fields_name: names2,
+                fields_type: tys2,
+            },
+        ) if names1 == names2 => Some(DataType::Tuple {
+            fields_name: names1,
+            fields_type: tys1
+                .into_iter()
+                .zip(tys2)
+                .map(|(ty1, ty2)| common_super_type(ty1, ty2))
+                .collect::<Option<_>>()?,
+        }),
+        (DataType::Variant, ty) | (ty, DataType::Variant) if can_upcast_to_variant(&ty) => {
+            Some(DataType::Variant)
+        }
+        (DataType::Float32, DataType::Float64) | (DataType::Float64, DataType::Float32) => {
+            Some(DataType::Float64)
+        }
+        (DataType::Date, DataType::Timestamp) | (DataType::Timestamp, DataType::Date) => {
+            Some(DataType::Timestamp)
         }
-        (DataType::Int16, DataType::UInt8) | (DataType::UInt8, DataType::Int16) => {
-            Some(DataType::Int16)
+        (ty1 @ DataType::Decimal { .. }, ty2) | (ty2, ty1 @ DataType::Decimal { .. }) => {
+            decimal_super_type(decimal_size(&ty1)?, decimal_size(&ty2)?)
         }
+        (DataType::Float32, ty) | (ty, DataType::Float32) => match number_width(&ty)?.1 {
+            0 | 1 => Some(DataType::Float32),
+            2 => Some(DataType::Float64),
+            _ => None,
+        },
+        (DataType::Float64, ty) | (ty, DataType::Float64) => match number_width(&ty)?.1 {
+            0..=2 => Some(DataType::Float64),
+            _ => None,
+        },
+        (ty1, ty2) => {
+            let (signed1, width1) = number_width(&ty1)?;
+            let (signed2, width2) = number_width(&ty2)?;
+            let (signed, width) = match (signed1, signed2) {
+                (false, false) | (true, true) => (signed1, width1.max(width2)),
+                (true, false) if width1 > width2 => (true, width1),
+                (false, true) if width2 > width1 => (true, width2),
+                _ => (true, width1.max(width2) + 1),
+            };
+            number_type(signed, width)
+        }
+    }
+}
+
+/// Returns whether the integer type is signed and the log2 of its size in bytes,
+/// or `None` if the type is not an integer.
+fn number_width(ty: &DataType) -> Option<(bool, usize)> {
+    match ty {
+        DataType::UInt8 => Some((false, 0)),
+        DataType::UInt16 => Some((false, 1)),
+        DataType::UInt32 => Some((false, 2)),
+        DataType::UInt64 => Some((false, 3)),
+        DataType::Int8 => Some((true, 0)),
+        DataType::Int16 => Some((true, 1)),
+        DataType::Int32 => Some((true, 2)),
+        DataType::Int64 => Some((true, 3)),
+        _ => None,
+    }
+}
+
+fn number_type(signed: bool, width: usize) -> Option<DataType> {
+    match (signed, width) {
+        (false, 0) => Some(DataType::UInt8),
+        (false, 1) => Some(DataType::UInt16),
+        (false, 2) => Some(DataType::UInt32),
+        (false, 3) => Some(DataType::UInt64),
+        (true, 0) => Some(DataType::Int8),
+        (true, 1) => Some(DataType::Int16),
+        (true, 2) => Some(DataType::Int32),
+        (true, 3) => Some(DataType::Int64),
         _ => None,
     }
 }
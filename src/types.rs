@@ -1,27 +1,36 @@
 pub mod any;
 pub mod array;
+pub mod binary;
 pub mod boolean;
+pub mod decimal;
 pub mod empty_array;
 pub mod generic;
+pub mod map;
 pub mod null;
 pub mod nullable;
 pub mod number;
 pub mod string;
+pub mod temporal;
+pub mod variant;
 
 pub use any::AnyType;
 pub use array::ArrayType;
 use arrow2::trusted_len::TrustedLen;
+pub use binary::BinaryType;
 pub use boolean::BooleanType;
 pub use empty_array::EmptyArrayType;
 pub use generic::GenericType;
+pub use map::MapType;
 pub use null::NullType;
 pub use nullable::NullableType;
-pub use number::NumberType;
+pub use number::{NumberType, F32, F64};
 pub use string::StringType;
+pub use variant::VariantType;
 
 use std::{fmt::Debug, ops::Range};
 
 use enum_as_inner::EnumAsInner;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     values::Scalar,
@@ -30,19 +39,41 @@ use crate::{
 
 pub type GenericMap<'a> = [DataType];
 
-#[derive(Debug, Clone, PartialEq, Eq, EnumAsInner)]
+#[derive(Debug, Clone, PartialEq, Eq, EnumAsInner, Serialize, Deserialize)]
 pub enum DataType {
     Boolean,
     String,
+    Binary,
     UInt8,
     UInt16,
+    UInt32,
+    UInt64,
     Int8,
     Int16,
+    Int32,
+    Int64,
+    Date,
+    Timestamp,
+    Interval,
+    Float32,
+    Float64,
+    Decimal {
+        precision: u8,
+        scale: u8,
+    },
     Null,
     Nullable(Box<DataType>),
     EmptyArray,
     Array(Box<DataType>),
-    Tuple(Vec<DataType>),
+    /// Stored as an array of `(key, value)` tuples.
+    Map(Box<DataType>, Box<DataType>),
+    /// A JSON-like value, see [`VariantType`] for the encoding.
+    Variant,
+    /// A field may have a name, by which `get_field` addresses it.
+    Tuple {
+        fields_name: Vec<Option<String>>,
+        fields_type: Vec<DataType>,
+    },
     Generic(usize),
 }
 
@@ -53,6 +84,15 @@ pub trait ValueType: Sized + 'static {
 
     fn to_owned_scalar<'a>(scalar: Self::ScalarRef<'a>) -> Self::Scalar;
     fn to_scalar_ref<'a>(scalar: &'a Self::Scalar) -> Self::ScalarRef<'a>;
+
+    /// Renders a scalar in results and error messages. It defaults to `Debug`,
+    /// which the types holding variants override to show JSON text.
+    fn display_scalar(scalar: &Self::ScalarRef<'_>) -> String {
+        format!("{scalar:?}")
+    }
+    fn display_column(col: &Self::Column) -> String {
+        format!("{col:?}")
+    }
 }
 
 pub trait ArgType: ValueType {
@@ -64,10 +104,16 @@ pub trait ArgType: ValueType {
     fn try_downcast_column<'a>(col: &'a Column) -> Option<Self::Column>;
     fn upcast_scalar(scalar: Self::Scalar) -> Scalar;
     fn upcast_column(col: Self::Column) -> Column;
+    /// Dictionary columns are decoded, so that the typed functions see them as
+    /// plain columns. Constant columns are downcast to their scalar, which the
+    /// kernels handle without broadcasting it.
     fn try_downcast_value<'a>(value: &'a ValueRef<'_, AnyType>) -> Option<ValueRef<'a, Self>> {
         Some(match value {
             ValueRef::Scalar(scalar) => ValueRef::Scalar(Self::try_downcast_scalar(scalar)?),
-            ValueRef::Column(col) => ValueRef::Column(Self::try_downcast_column(col)?),
+            ValueRef::Column(Column::Const { scalar, .. }) => {
+                ValueRef::Scalar(Self::try_downcast_scalar(scalar)?)
+            }
+            ValueRef::Column(col) => ValueRef::Column(Self::try_downcast_column(&col.flatten())?),
         })
     }
     fn upcast_value(value: Value<Self>) -> Value<AnyType> {
@@ -16,4 +16,12 @@ impl ValueType for AnyType {
     fn to_scalar_ref<'a>(scalar: &'a Self::Scalar) -> Self::ScalarRef<'a> {
         scalar
     }
+
+    fn display_scalar(scalar: &Self::ScalarRef<'_>) -> String {
+        scalar.to_string()
+    }
+
+    fn display_column(col: &Self::Column) -> String {
+        col.to_string()
+    }
 }
@@ -20,6 +20,14 @@ impl<T: ArgType> ValueType for ArrayType<T> {
     fn to_scalar_ref<'a>(scalar: &'a Self::Scalar) -> Self::ScalarRef<'a> {
         scalar.clone()
     }
+
+    fn display_scalar(scalar: &Self::ScalarRef<'_>) -> String {
+        T::display_column(scalar)
+    }
+
+    fn display_column((col, offsets): &Self::Column) -> String {
+        format!("({}, {offsets:?})", T::display_column(col))
+    }
 }
 
 impl<T: ArgType> ArgType for ArrayType<T> {
@@ -32,7 +40,7 @@ impl<T: ArgType> ArgType for ArrayType<T> {
 
     fn try_downcast_scalar<'a>(scalar: &'a Scalar) -> Option<Self::ScalarRef<'a>> {
         match scalar {
-            Scalar::Array(array) => T::try_downcast_column(array),
+            Scalar::Array(array) => T::try_downcast_column(&array.flatten()),
             _ => None,
         }
     }
@@ -40,7 +48,7 @@ impl<T: ArgType> ArgType for ArrayType<T> {
     fn try_downcast_column<'a>(col: &'a Column) -> Option<Self::Column> {
         match col {
             Column::Array { array, offsets } => {
-                Some((T::try_downcast_column(array)?, offsets.clone()))
+                Some((T::try_downcast_column(&array.flatten())?, offsets.clone()))
             }
             _ => None,
         }
@@ -0,0 +1,333 @@
+use arrow2::bitmap::MutableBitmap;
+
+use crate::{
+    display::display_decimal,
+    function::EvalContext,
+    runtime::EvalError,
+    types::{AnyType, DataType},
+    values::{Column, Scalar, Value, ValueRef},
+};
+
+/// The maximum number of decimal digits that fits in an `i128`.
+pub const MAX_DECIMAL_PRECISION: u8 = 38;
+
+/// The minimum scale kept when the result of an arithmetic operation has to be
+/// truncated to `MAX_DECIMAL_PRECISION`.
+const MIN_ADJUSTED_SCALE: u8 = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecimalOp {
+    Plus,
+    Minus,
+    Multiply,
+    Divide,
+}
+
+/// Returns the `(precision, scale)` of the smallest decimal that can hold every
+/// value of the integer type, or `None` if the type is not an integer.
+pub fn integer_decimal_size(ty: &DataType) -> Option<(u8, u8)> {
+    match ty {
+        DataType::UInt8 | DataType::Int8 => Some((3, 0)),
+        DataType::UInt16 | DataType::Int16 => Some((5, 0)),
+        DataType::UInt32 | DataType::Int32 => Some((10, 0)),
+        DataType::Int64 => Some((19, 0)),
+        DataType::UInt64 => Some((20, 0)),
+        _ => None,
+    }
+}
+
+/// Returns the `(precision, scale)` of a decimal or an integer type.
+pub fn decimal_size(ty: &DataType) -> Option<(u8, u8)> {
+    match ty {
+        DataType::Decimal { precision, scale } => Some((*precision, *scale)),
+        ty => integer_decimal_size(ty),
+    }
+}
+
+/// Infers the `(precision, scale)` of the result of an arithmetic operation on
+/// two decimals, following the rules of SQL Server and Spark. If the precision
+/// exceeds `MAX_DECIMAL_PRECISION`, the integral digits are kept and the scale
+/// is reduced, but not below 6.
+pub fn decimal_op_result_size(op: DecimalOp, lhs: (u8, u8), rhs: (u8, u8)) -> (u8, u8) {
+    let (p1, s1) = (lhs.0 as usize, lhs.1 as usize);
+    let (p2, s2) = (rhs.0 as usize, rhs.1 as usize);
+    let (precision, scale) = match op {
+        DecimalOp::Plus | DecimalOp::Minus => {
+            let scale = s1.max(s2);
+            ((p1 - s1).max(p2 - s2) + scale + 1, scale)
+        }
+        DecimalOp::Multiply => (p1 + p2 + 1, s1 + s2),
+        DecimalOp::Divide => {
+            let scale = (MIN_ADJUSTED_SCALE as usize).max(s1 + p2 + 1);
+            (p1 - s1 + s2 + scale, scale)
+        }
+    };
+
+    let max_precision = MAX_DECIMAL_PRECISION as usize;
+    if precision <= max_precision {
+        (precision as u8, scale as u8)
+    } else {
+        let integral = precision - scale;
+        let min_scale = scale.min(MIN_ADJUSTED_SCALE as usize);
+        let scale = max_precision.saturating_sub(integral).max(min_scale);
+        (MAX_DECIMAL_PRECISION, scale as u8)
+    }
+}
+
+/// Returns the smallest decimal that can hold every value of both types, which
+/// may be decimals or integers.
+pub fn decimal_super_type(lhs: (u8, u8), rhs: (u8, u8)) -> Option<DataType> {
+    let scale = lhs.1.max(rhs.1);
+    let integral = (lhs.0 - lhs.1).max(rhs.0 - rhs.1);
+    let precision = integral.checked_add(scale)?;
+    (precision <= MAX_DECIMAL_PRECISION).then_some(DataType::Decimal { precision, scale })
+}
+
+/// Converts a decimal value from one scale to another, rounding the extra
+/// digits half away from zero like SQL does, so that `1.235` is `1.24` at scale
+/// 2. Returns an error if the result does not fit in `precision` digits.
+pub fn rescale(value: i128, from_scale: u8, to_scale: u8, precision: u8) -> Result<i128, String> {
+    let value = if to_scale >= from_scale {
+        pow10(to_scale - from_scale).and_then(|factor| value.checked_mul(factor))
+    } else {
+        match pow10(from_scale - to_scale) {
+            Some(factor) => {
+                let (quotient, remainder) = (value / factor, value % factor);
+                if remainder.unsigned_abs() * 2 >= factor.unsigned_abs() {
+                    quotient.checked_add(value.signum())
+                } else {
+                    Some(quotient)
+                }
+            }
+            // Every `i128` is less than half of `10^39`.
+            None => Some(0),
+        }
+    };
+    value
+        .filter(|value| fits_precision(*value, precision))
+        .ok_or_else(|| format!("decimal overflow for precision {precision}"))
+}
+
+/// Multiplies two decimal values and reduces the product from `from_scale` to
+/// `to_scale`. The product is computed in 256 bits, so that operands with many
+/// digits do not overflow when the reduced result fits.
+fn multiply_rescale(
+    lhs: i128,
+    rhs: i128,
+    from_scale: u8,
+    to_scale: u8,
+    precision: u8,
+) -> Result<i128, String> {
+    let overflow = || format!("decimal overflow for precision {precision}");
+    let mut product = mul_wide(lhs.unsigned_abs(), rhs.unsigned_abs());
+    let drop = from_scale.saturating_sub(to_scale);
+    let mut round_up = false;
+    if drop > 0 {
+        let mut exp = drop - 1;
+        while exp > 0 {
+            let chunk = exp.min(19);
+            div_wide(&mut product, 10u64.pow(chunk as u32));
+            exp -= chunk;
+        }
+        round_up = div_wide(&mut product, 10) >= 5;
+    }
+    if product[2] != 0 || product[3] != 0 {
+        return Err(overflow());
+    }
+    let magnitude = ((product[1] as u128) << 64 | product[0] as u128)
+        .checked_add(round_up as u128)
+        .and_then(|magnitude| i128::try_from(magnitude).ok())
+        .ok_or_else(overflow)?;
+    let value = if (lhs < 0) != (rhs < 0) {
+        -magnitude
+    } else {
+        magnitude
+    };
+    rescale(value, from_scale - drop, to_scale, precision)
+}
+
+/// The product of two `u128` as four 64-bit limbs, least significant first.
+fn mul_wide(lhs: u128, rhs: u128) -> [u64; 4] {
+    let lhs = [lhs as u64, (lhs >> 64) as u64];
+    let rhs = [rhs as u64, (rhs >> 64) as u64];
+    let mut product = [0u64; 4];
+    for i in 0..2 {
+        let mut carry = 0u128;
+        for j in 0..2 {
+            let sum = lhs[i] as u128 * rhs[j] as u128 + product[i + j] as u128 + carry;
+            product[i + j] = sum as u64;
+            carry = sum >> 64;
+        }
+        product[i + 2] = carry as u64;
+    }
+    product
+}
+
+/// Divides the limbs by `divisor` in place and returns the remainder.
+fn div_wide(limbs: &mut [u64; 4], divisor: u64) -> u64 {
+    let mut remainder = 0u128;
+    for limb in limbs.iter_mut().rev() {
+        let current = remainder << 64 | *limb as u128;
+        *limb = (current / divisor as u128) as u64;
+        remainder = current % divisor as u128;
+    }
+    remainder as u64
+}
+
+fn pow10(exp: u8) -> Option<i128> {
+    10i128.checked_pow(exp as u32)
+}
+
+fn fits_precision(value: i128, precision: u8) -> bool {
+    match pow10(precision) {
+        Some(max) => value.unsigned_abs() < max.unsigned_abs(),
+        None => true,
+    }
+}
+
+/// Evaluates an arithmetic operation on two decimal values. The division
+/// truncates the digits beyond the result scale.
+pub fn eval_decimal_op(
+    op: DecimalOp,
+    (lhs, lhs_scale): (i128, u8),
+    (rhs, rhs_scale): (i128, u8),
+    (precision, scale): (u8, u8),
+) -> Result<i128, String> {
+    let overflow = || format!("decimal overflow for precision {precision}");
+    match op {
+        DecimalOp::Plus | DecimalOp::Minus => {
+            let lhs = rescale(lhs, lhs_scale, scale, MAX_DECIMAL_PRECISION)?;
+            let rhs = rescale(rhs, rhs_scale, scale, MAX_DECIMAL_PRECISION)?;
+            let value = if op == DecimalOp::Plus {
+                lhs.checked_add(rhs)
+            } else {
+                lhs.checked_sub(rhs)
+            };
+            rescale(value.ok_or_else(overflow)?, scale, scale, precision)
+        }
+        DecimalOp::Multiply => multiply_rescale(lhs, rhs, lhs_scale + rhs_scale, scale, precision),
+        DecimalOp::Divide => {
+            if rhs == 0 {
+                return Err("division by zero".to_string());
+            }
+            // `lhs / rhs` has the scale `lhs_scale - rhs_scale`, so scale up the
+            // dividend beforehand to keep the digits of the result scale.
+            let exp = scale as i32 + rhs_scale as i32 - lhs_scale as i32;
+            let value = if exp >= 0 {
+                lhs.checked_mul(pow10(exp as u8).ok_or_else(overflow)?)
+                    .ok_or_else(overflow)?
+                    / rhs
+            } else {
+                lhs / rhs / pow10((-exp) as u8).ok_or_else(overflow)?
+            };
+            rescale(value, scale, scale, precision)
+        }
+    }
+}
+
+/// Evaluates a decimal arithmetic function. The arguments have been cast to the
+/// decimal types of the signature, which are nullable if `nullable` is set.
+pub fn vectorize_decimal_op(
+    op: DecimalOp,
+    args: &[ValueRef<AnyType>],
+    args_size: &[(u8, u8)],
+    (precision, scale): (u8, u8),
+    nullable: bool,
+    ctx: &EvalContext,
+) -> Result<Value<AnyType>, EvalError> {
+    let len = args.iter().find_map(|arg| match arg {
+        ValueRef::Column(col) => Some(col.len()),
+        ValueRef::Scalar(_) => None,
+    });
+    let eval_row = |row: usize| -> Result<Option<i128>, EvalError> {
+        let lhs = decimal_at(&args[0], row)?;
+        let rhs = decimal_at(&args[1], row)?;
+        match (lhs, rhs) {
+            (Some(lhs), Some(rhs)) => eval_decimal_op(
+                op,
+                (lhs, args_size[0].1),
+                (rhs, args_size[1].1),
+                (precision, scale),
+            )
+            .map(Some)
+            .map_err(|msg| {
+                EvalError::new(msg).value(format!(
+                    "({}, {})",
+                    display_decimal(lhs, args_size[0].1),
+                    display_decimal(rhs, args_size[1].1)
+                ))
+            }),
+            _ => Ok(None),
+        }
+    };
+
+    match len {
+        None => Ok(Value::Scalar(match eval_row(0)? {
+            Some(value) => Scalar::Decimal {
+                value,
+                precision,
+                scale,
+            },
+            None => Scalar::Null,
+        })),
+        Some(len) => {
+            let mut values = Vec::with_capacity(len);
+            let mut validity = MutableBitmap::with_capacity(len);
+            for row in 0..len {
+                if !ctx.is_selected(row) {
+                    values.push(0);
+                    validity.push(false);
+                    continue;
+                }
+                let value = eval_row(row).map_err(|err| err.row(row))?;
+                values.push(value.unwrap_or_default());
+                validity.push(value.is_some());
+            }
+            let column = Column::Decimal {
+                values: values.into(),
+                precision,
+                scale,
+            };
+            Ok(Value::Column(if nullable {
+                Column::Nullable {
+                    column: Box::new(column),
+                    validity: validity.into(),
+                }
+            } else {
+                column
+            }))
+        }
+    }
+}
+
+fn decimal_at(arg: &ValueRef<AnyType>, row: usize) -> Result<Option<i128>, EvalError> {
+    match arg {
+        ValueRef::Scalar(scalar) => scalar_decimal(scalar),
+        ValueRef::Column(col) => column_decimal_at(col, row),
+    }
+}
+
+fn scalar_decimal(scalar: &Scalar) -> Result<Option<i128>, EvalError> {
+    match scalar {
+        Scalar::Null => Ok(None),
+        Scalar::Decimal { value, .. } => Ok(Some(*value)),
+        scalar => Err(EvalError::new(format!(
+            "argument {scalar} does not match the expected type Decimal"
+        ))),
+    }
+}
+
+fn column_decimal_at(col: &Column, row: usize) -> Result<Option<i128>, EvalError> {
+    match col {
+        Column::Decimal { values, .. } => Ok(Some(values[row])),
+        Column::Nullable {
+            column: box Column::Decimal { values, .. },
+            validity,
+        } => Ok(validity.get_bit(row).then(|| values[row])),
+        Column::Dictionary { keys, values } => column_decimal_at(values, keys[row] as usize),
+        Column::Const { scalar, .. } => scalar_decimal(scalar),
+        col => Err(EvalError::new(format!(
+            "argument {col} does not match the expected type Decimal"
+        ))),
+    }
+}
@@ -18,6 +18,14 @@ impl<const INDEX: usize> ValueType for GenericType<INDEX> {
     fn to_scalar_ref<'a>(scalar: &'a Self::Scalar) -> Self::ScalarRef<'a> {
         scalar.as_ref()
     }
+
+    fn display_scalar(scalar: &Self::ScalarRef<'_>) -> String {
+        scalar.to_string()
+    }
+
+    fn display_column(col: &Self::Column) -> String {
+        col.to_string()
+    }
 }
 
 impl<const INDEX: usize> ArgType for GenericType<INDEX> {
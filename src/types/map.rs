@@ -0,0 +1,219 @@
+use std::{marker::PhantomData, ops::Range};
+
+use arrow2::trusted_len::TrustedLen;
+
+use crate::values::{Column, Scalar};
+
+use super::{ArgType, DataType, GenericMap, ValueType};
+
+/// A map is stored as an array of `(key, value)` tuples. The keys and values
+/// of all the rows are kept in two columns, delimited by the offsets.
+pub struct MapType<K: ArgType, V: ArgType>(PhantomData<(K, V)>);
+
+impl<K: ArgType, V: ArgType> ValueType for MapType<K, V> {
+    type Scalar = (K::Column, V::Column);
+    type ScalarRef<'a> = (K::Column, V::Column);
+    type Column = (K::Column, V::Column, Vec<usize>);
+
+    fn to_owned_scalar<'a>(scalar: Self::ScalarRef<'a>) -> Self::Scalar {
+        scalar
+    }
+
+    fn to_scalar_ref<'a>(scalar: &'a Self::Scalar) -> Self::ScalarRef<'a> {
+        scalar.clone()
+    }
+
+    fn display_scalar((keys, values): &Self::ScalarRef<'_>) -> String {
+        format!(
+            "({}, {})",
+            K::display_column(keys),
+            V::display_column(values)
+        )
+    }
+
+    fn display_column((keys, values, offsets): &Self::Column) -> String {
+        format!(
+            "({}, {}, {offsets:?})",
+            K::display_column(keys),
+            V::display_column(values)
+        )
+    }
+}
+
+impl<K: ArgType, V: ArgType> ArgType for MapType<K, V> {
+    type ColumnIterator<'a> = MapIterator<'a, K, V>;
+    type ColumnBuilder = (K::ColumnBuilder, V::ColumnBuilder, Vec<usize>);
+
+    fn data_type() -> DataType {
+        DataType::Map(Box::new(K::data_type()), Box::new(V::data_type()))
+    }
+
+    fn try_downcast_scalar<'a>(scalar: &'a Scalar) -> Option<Self::ScalarRef<'a>> {
+        match scalar {
+            Scalar::Array(entries) => downcast_entries::<K, V>(entries),
+            _ => None,
+        }
+    }
+
+    fn try_downcast_column<'a>(col: &'a Column) -> Option<Self::Column> {
+        match col {
+            Column::Array { array, offsets } => {
+                let (keys, values) = downcast_entries::<K, V>(array)?;
+                Some((keys, values, offsets.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    fn upcast_scalar((keys, values): Self::Scalar) -> Scalar {
+        Scalar::Array(upcast_entries::<K, V>(keys, values))
+    }
+
+    fn upcast_column((keys, values, offsets): Self::Column) -> Column {
+        Column::Array {
+            array: Box::new(upcast_entries::<K, V>(keys, values)),
+            offsets,
+        }
+    }
+
+    fn column_len<'a>((_, _, offsets): &'a Self::Column) -> usize {
+        offsets.len() - 1
+    }
+
+    fn index_column<'a>(
+        (keys, values, offsets): &'a Self::Column,
+        index: usize,
+    ) -> Self::ScalarRef<'a> {
+        let range = offsets[index]..offsets[index + 1];
+        (
+            K::slice_column(keys, range.clone()),
+            V::slice_column(values, range),
+        )
+    }
+
+    fn slice_column<'a>(
+        (keys, values, offsets): &'a Self::Column,
+        range: Range<usize>,
+    ) -> Self::Column {
+        (
+            keys.clone(),
+            values.clone(),
+            offsets[range.start..(range.end + 1)].to_vec(),
+        )
+    }
+
+    fn iter_column<'a>((keys, values, offsets): &'a Self::Column) -> Self::ColumnIterator<'a> {
+        MapIterator {
+            keys,
+            values,
+            offsets: offsets.windows(2),
+        }
+    }
+
+    fn create_builder(_capacity: usize, generics: &GenericMap) -> Self::ColumnBuilder {
+        (
+            K::create_builder(0, generics),
+            V::create_builder(0, generics),
+            vec![0],
+        )
+    }
+
+    fn column_to_builder((keys, values, offsets): Self::Column) -> Self::ColumnBuilder {
+        (
+            K::column_to_builder(keys),
+            V::column_to_builder(values),
+            offsets,
+        )
+    }
+
+    fn builder_len((_, _, offsets): &Self::ColumnBuilder) -> usize {
+        offsets.len() - 1
+    }
+
+    fn push_item(
+        (keys_builder, values_builder, offsets): &mut Self::ColumnBuilder,
+        (keys, values): Self::ScalarRef<'_>,
+    ) {
+        K::append_builder(keys_builder, &K::column_to_builder(keys));
+        V::append_builder(values_builder, &V::column_to_builder(values));
+        offsets.push(K::builder_len(keys_builder));
+    }
+
+    fn push_default((keys_builder, _, offsets): &mut Self::ColumnBuilder) {
+        offsets.push(K::builder_len(keys_builder));
+    }
+
+    fn append_builder(
+        (keys_builder, values_builder, offsets): &mut Self::ColumnBuilder,
+        (other_keys, other_values, other_offsets): &Self::ColumnBuilder,
+    ) {
+        let end = offsets.last().cloned().unwrap();
+        offsets.extend(other_offsets.iter().skip(1).map(|offset| offset + end));
+        K::append_builder(keys_builder, other_keys);
+        V::append_builder(values_builder, other_values);
+    }
+
+    fn build_column((keys, values, offsets): Self::ColumnBuilder) -> Self::Column {
+        (K::build_column(keys), V::build_column(values), offsets)
+    }
+
+    fn build_scalar((keys, values, offsets): Self::ColumnBuilder) -> Self::Scalar {
+        assert_eq!(offsets.len(), 2);
+        let range = offsets[0]..offsets[1];
+        (
+            K::slice_column(&K::build_column(keys), range.clone()),
+            V::slice_column(&V::build_column(values), range),
+        )
+    }
+}
+
+/// The type of the array that stores the entries of a map.
+pub fn map_entries_type(key: &DataType, value: &DataType) -> DataType {
+    DataType::Array(Box::new(DataType::Tuple {
+        fields_name: vec![None, None],
+        fields_type: vec![key.clone(), value.clone()],
+    }))
+}
+
+fn downcast_entries<K: ArgType, V: ArgType>(entries: &Column) -> Option<(K::Column, V::Column)> {
+    match entries {
+        Column::Tuple { fields, .. } if fields.len() == 2 => Some((
+            K::try_downcast_column(&fields[0].flatten())?,
+            V::try_downcast_column(&fields[1].flatten())?,
+        )),
+        _ => None,
+    }
+}
+
+fn upcast_entries<K: ArgType, V: ArgType>(keys: K::Column, values: V::Column) -> Column {
+    let len = K::column_len(&keys);
+    Column::Tuple {
+        fields: vec![K::upcast_column(keys), V::upcast_column(values)],
+        len,
+    }
+}
+
+pub struct MapIterator<'a, K: ArgType, V: ArgType> {
+    keys: &'a K::Column,
+    values: &'a V::Column,
+    offsets: std::slice::Windows<'a, usize>,
+}
+
+impl<'a, K: ArgType, V: ArgType> Iterator for MapIterator<'a, K, V> {
+    type Item = (K::Column, V::Column);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.offsets.next().map(|range| {
+            (
+                K::slice_column(self.keys, range[0]..range[1]),
+                V::slice_column(self.values, range[0]..range[1]),
+            )
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.offsets.size_hint()
+    }
+}
+
+unsafe impl<'a, K: ArgType, V: ArgType> TrustedLen for MapIterator<'a, K, V> {}
@@ -26,6 +26,17 @@ impl<T: ValueType> ValueType for NullableType<T> {
     fn to_scalar_ref<'a>(scalar: &'a Self::Scalar) -> Self::ScalarRef<'a> {
         scalar.as_ref().map(T::to_scalar_ref)
     }
+
+    fn display_scalar(scalar: &Self::ScalarRef<'_>) -> String {
+        match scalar {
+            Some(scalar) => format!("Some({})", T::display_scalar(scalar)),
+            None => "None".to_string(),
+        }
+    }
+
+    fn display_column((col, validity): &Self::Column) -> String {
+        format!("({}, {validity:?})", T::display_column(col))
+    }
 }
 
 impl<T: ArgType> ArgType for NullableType<T> {
@@ -46,7 +57,7 @@ impl<T: ArgType> ArgType for NullableType<T> {
     fn try_downcast_column<'a>(col: &'a Column) -> Option<Self::Column> {
         match col {
             Column::Nullable { column, validity } => {
-                Some((T::try_downcast_column(column)?, validity.clone()))
+                Some((T::try_downcast_column(&column.flatten())?, validity.clone()))
             }
             _ => None,
         }
@@ -156,11 +167,11 @@ impl<'a, T: ArgType> Iterator for NullableIterator<'a, T> {
 
     fn next(&mut self) -> Option<Self::Item> {
         self.iter.next().zip(self.validity.next()).map(
-            |(scalar, is_null)| {
-                if is_null {
-                    None
-                } else {
+            |(scalar, is_valid)| {
+                if is_valid {
                     Some(scalar)
+                } else {
+                    None
                 }
             },
         )
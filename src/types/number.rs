@@ -1,6 +1,13 @@
-use std::{marker::PhantomData, ops::Range};
+use std::{
+    cmp::Ordering,
+    fmt::{Debug, Display},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::Range,
+};
 
 use arrow2::{buffer::Buffer, types::NativeType};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     util::buffer_into_mut,
@@ -9,6 +16,74 @@ use crate::{
 
 use super::{ArgType, DataType, GenericMap, ValueType};
 
+macro_rules! impl_total_order_float {
+    ($name:ident, $float:ty) => {
+        /// A float with a total order, used in scalars and literals. `NaN` is
+        /// equal to itself and greater than any other value, including infinity,
+        /// and `-0.0` is equal to `0.0`.
+        #[derive(Clone, Copy, Default, Serialize, Deserialize)]
+        pub struct $name(pub $float);
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                self.cmp(other) == Ordering::Equal
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $name {
+            fn cmp(&self, other: &Self) -> Ordering {
+                match (self.0.is_nan(), other.0.is_nan()) {
+                    (true, true) => Ordering::Equal,
+                    (true, false) => Ordering::Greater,
+                    (false, true) => Ordering::Less,
+                    (false, false) => self.0.partial_cmp(&other.0).unwrap(),
+                }
+            }
+        }
+
+        impl Hash for $name {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                if self.0.is_nan() {
+                    <$float>::NAN.to_bits().hash(state);
+                } else if self.0 == 0.0 {
+                    (0.0 as $float).to_bits().hash(state);
+                } else {
+                    self.0.to_bits().hash(state);
+                }
+            }
+        }
+
+        impl Debug for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                Debug::fmt(&self.0, f)
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                Display::fmt(&self.0, f)
+            }
+        }
+
+        impl From<$float> for $name {
+            fn from(val: $float) -> Self {
+                $name(val)
+            }
+        }
+    };
+}
+
+impl_total_order_float!(F32, f32);
+impl_total_order_float!(F64, f64);
+
 pub trait Number: 'static {
     type Storage: NativeType;
 
@@ -19,6 +94,214 @@ pub trait Number: 'static {
     fn upcast_column(col: Buffer<Self::Storage>) -> Column;
 }
 
+/// The number type that the operands of a binary arithmetic function are
+/// promoted to. For integers, the result is one size wider than the widest
+/// operand, up to 64 bits, and is signed if either operand is signed, so that
+/// `UInt8 + UInt8 -> UInt16` and `Int8 + UInt8 -> Int16`. If either operand is
+/// a float, the result is `Float64`. A `UInt64` operand promoted to `Int64` is
+/// range-checked at evaluation time.
+pub trait NumberPromote<Rhs: Number>: Number {
+    type Output: Number;
+}
+
+macro_rules! impl_number_promote {
+    ($($lhs:ty, $rhs:ty => $output:ty;)*) => {
+        $(
+            impl NumberPromote<$rhs> for $lhs {
+                type Output = $output;
+            }
+        )*
+    };
+}
+
+impl_number_promote! {
+    u8, u8 => u16;
+    u8, u16 => u32;
+    u8, u32 => u64;
+    u8, u64 => u64;
+    u8, i8 => i16;
+    u8, i16 => i32;
+    u8, i32 => i64;
+    u8, i64 => i64;
+    u8, f32 => f64;
+    u8, f64 => f64;
+    u16, u8 => u32;
+    u16, u16 => u32;
+    u16, u32 => u64;
+    u16, u64 => u64;
+    u16, i8 => i32;
+    u16, i16 => i32;
+    u16, i32 => i64;
+    u16, i64 => i64;
+    u16, f32 => f64;
+    u16, f64 => f64;
+    u32, u8 => u64;
+    u32, u16 => u64;
+    u32, u32 => u64;
+    u32, u64 => u64;
+    u32, i8 => i64;
+    u32, i16 => i64;
+    u32, i32 => i64;
+    u32, i64 => i64;
+    u32, f32 => f64;
+    u32, f64 => f64;
+    u64, u8 => u64;
+    u64, u16 => u64;
+    u64, u32 => u64;
+    u64, u64 => u64;
+    u64, i8 => i64;
+    u64, i16 => i64;
+    u64, i32 => i64;
+    u64, i64 => i64;
+    u64, f32 => f64;
+    u64, f64 => f64;
+    i8, u8 => i16;
+    i8, u16 => i32;
+    i8, u32 => i64;
+    i8, u64 => i64;
+    i8, i8 => i16;
+    i8, i16 => i32;
+    i8, i32 => i64;
+    i8, i64 => i64;
+    i8, f32 => f64;
+    i8, f64 => f64;
+    i16, u8 => i32;
+    i16, u16 => i32;
+    i16, u32 => i64;
+    i16, u64 => i64;
+    i16, i8 => i32;
+    i16, i16 => i32;
+    i16, i32 => i64;
+    i16, i64 => i64;
+    i16, f32 => f64;
+    i16, f64 => f64;
+    i32, u8 => i64;
+    i32, u16 => i64;
+    i32, u32 => i64;
+    i32, u64 => i64;
+    i32, i8 => i64;
+    i32, i16 => i64;
+    i32, i32 => i64;
+    i32, i64 => i64;
+    i32, f32 => f64;
+    i32, f64 => f64;
+    i64, u8 => i64;
+    i64, u16 => i64;
+    i64, u32 => i64;
+    i64, u64 => i64;
+    i64, i8 => i64;
+    i64, i16 => i64;
+    i64, i32 => i64;
+    i64, i64 => i64;
+    i64, f32 => f64;
+    i64, f64 => f64;
+    f32, u8 => f64;
+    f32, u16 => f64;
+    f32, u32 => f64;
+    f32, u64 => f64;
+    f32, i8 => f64;
+    f32, i16 => f64;
+    f32, i32 => f64;
+    f32, i64 => f64;
+    f32, f32 => f64;
+    f32, f64 => f64;
+    f64, u8 => f64;
+    f64, u16 => f64;
+    f64, u32 => f64;
+    f64, u64 => f64;
+    f64, i8 => f64;
+    f64, i16 => f64;
+    f64, i32 => f64;
+    f64, i64 => f64;
+    f64, f32 => f64;
+    f64, f64 => f64;
+}
+
+/// Arithmetic that reports an overflow instead of wrapping or panicking.
+/// Floats never overflow, they saturate to infinity.
+pub trait CheckedArithmetic: Sized + num_traits::Zero {
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    /// Integer division truncates toward zero, and is `None` for a zero
+    /// divisor.
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+}
+
+macro_rules! impl_checked_arithmetic {
+    (int: $($int:ty),*; float: $($float:ty),*) => {
+        $(
+            impl CheckedArithmetic for $int {
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$int>::checked_add(self, rhs)
+                }
+
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    <$int>::checked_sub(self, rhs)
+                }
+
+                fn checked_mul(self, rhs: Self) -> Option<Self> {
+                    <$int>::checked_mul(self, rhs)
+                }
+
+                fn checked_div(self, rhs: Self) -> Option<Self> {
+                    <$int>::checked_div(self, rhs)
+                }
+            }
+        )*
+        $(
+            impl CheckedArithmetic for $float {
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    Some(self + rhs)
+                }
+
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    Some(self - rhs)
+                }
+
+                fn checked_mul(self, rhs: Self) -> Option<Self> {
+                    Some(self * rhs)
+                }
+
+                fn checked_div(self, rhs: Self) -> Option<Self> {
+                    Some(self / rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_checked_arithmetic!(int: u8, u16, u32, u64, i8, i16, i32, i64; float: f32, f64);
+
+/// The signed number type of the same size, used as the result of subtraction
+/// so that `UInt8 - UInt8 -> Int16`. Signed and float types map to themselves.
+pub trait NumberSigned: Number {
+    type Signed: Number;
+}
+
+macro_rules! impl_number_signed {
+    ($($number:ty => $signed:ty;)*) => {
+        $(
+            impl NumberSigned for $number {
+                type Signed = $signed;
+            }
+        )*
+    };
+}
+
+impl_number_signed! {
+    u8 => i8;
+    u16 => i16;
+    u32 => i32;
+    u64 => i64;
+    i8 => i8;
+    i16 => i16;
+    i32 => i32;
+    i64 => i64;
+    f32 => f32;
+    f64 => f64;
+}
+
 pub struct NumberType<T: Number>(PhantomData<T>);
 
 impl<Int: Number> ValueType for NumberType<Int> {
@@ -161,6 +444,54 @@ impl Number for u16 {
     }
 }
 
+impl Number for u32 {
+    type Storage = u32;
+
+    fn data_type() -> DataType {
+        DataType::UInt32
+    }
+
+    fn try_downcast_scalar(scalar: &Scalar) -> Option<Self::Storage> {
+        scalar.as_u_int32().cloned()
+    }
+
+    fn try_downcast_column(col: &Column) -> Option<Buffer<Self::Storage>> {
+        col.as_u_int32().cloned()
+    }
+
+    fn upcast_scalar(scalar: Self::Storage) -> Scalar {
+        Scalar::UInt32(scalar)
+    }
+
+    fn upcast_column(col: Buffer<Self::Storage>) -> Column {
+        Column::UInt32(col)
+    }
+}
+
+impl Number for u64 {
+    type Storage = u64;
+
+    fn data_type() -> DataType {
+        DataType::UInt64
+    }
+
+    fn try_downcast_scalar(scalar: &Scalar) -> Option<Self::Storage> {
+        scalar.as_u_int64().cloned()
+    }
+
+    fn try_downcast_column(col: &Column) -> Option<Buffer<Self::Storage>> {
+        col.as_u_int64().cloned()
+    }
+
+    fn upcast_scalar(scalar: Self::Storage) -> Scalar {
+        Scalar::UInt64(scalar)
+    }
+
+    fn upcast_column(col: Buffer<Self::Storage>) -> Column {
+        Column::UInt64(col)
+    }
+}
+
 impl Number for i8 {
     type Storage = i8;
 
@@ -208,3 +539,99 @@ impl Number for i16 {
         Column::Int16(col)
     }
 }
+
+impl Number for i32 {
+    type Storage = i32;
+
+    fn data_type() -> DataType {
+        DataType::Int32
+    }
+
+    fn try_downcast_scalar(scalar: &Scalar) -> Option<Self::Storage> {
+        scalar.as_int32().cloned()
+    }
+
+    fn try_downcast_column(col: &Column) -> Option<Buffer<Self::Storage>> {
+        col.as_int32().cloned()
+    }
+
+    fn upcast_scalar(scalar: Self::Storage) -> Scalar {
+        Scalar::Int32(scalar)
+    }
+
+    fn upcast_column(col: Buffer<Self::Storage>) -> Column {
+        Column::Int32(col)
+    }
+}
+
+impl Number for i64 {
+    type Storage = i64;
+
+    fn data_type() -> DataType {
+        DataType::Int64
+    }
+
+    fn try_downcast_scalar(scalar: &Scalar) -> Option<Self::Storage> {
+        scalar.as_int64().cloned()
+    }
+
+    fn try_downcast_column(col: &Column) -> Option<Buffer<Self::Storage>> {
+        col.as_int64().cloned()
+    }
+
+    fn upcast_scalar(scalar: Self::Storage) -> Scalar {
+        Scalar::Int64(scalar)
+    }
+
+    fn upcast_column(col: Buffer<Self::Storage>) -> Column {
+        Column::Int64(col)
+    }
+}
+
+impl Number for f32 {
+    type Storage = f32;
+
+    fn data_type() -> DataType {
+        DataType::Float32
+    }
+
+    fn try_downcast_scalar(scalar: &Scalar) -> Option<Self::Storage> {
+        scalar.as_float32().map(|val| val.0)
+    }
+
+    fn try_downcast_column(col: &Column) -> Option<Buffer<Self::Storage>> {
+        col.as_float32().cloned()
+    }
+
+    fn upcast_scalar(scalar: Self::Storage) -> Scalar {
+        Scalar::Float32(F32(scalar))
+    }
+
+    fn upcast_column(col: Buffer<Self::Storage>) -> Column {
+        Column::Float32(col)
+    }
+}
+
+impl Number for f64 {
+    type Storage = f64;
+
+    fn data_type() -> DataType {
+        DataType::Float64
+    }
+
+    fn try_downcast_scalar(scalar: &Scalar) -> Option<Self::Storage> {
+        scalar.as_float64().map(|val| val.0)
+    }
+
+    fn try_downcast_column(col: &Column) -> Option<Buffer<Self::Storage>> {
+        col.as_float64().cloned()
+    }
+
+    fn upcast_scalar(scalar: Self::Storage) -> Scalar {
+        Scalar::Float64(F64(scalar))
+    }
+
+    fn upcast_column(col: Buffer<Self::Storage>) -> Column {
+        Column::Float64(col)
+    }
+}
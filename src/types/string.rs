@@ -12,12 +12,12 @@ use super::{ArgType, DataType, GenericMap, ValueType};
 pub struct StringType;
 
 impl ValueType for StringType {
-    type Scalar = Vec<u8>;
-    type ScalarRef<'a> = &'a [u8];
+    type Scalar = String;
+    type ScalarRef<'a> = &'a str;
     type Column = (Buffer<u8>, Vec<usize>);
 
     fn to_owned_scalar<'a>(scalar: Self::ScalarRef<'a>) -> Self::Scalar {
-        scalar.to_vec()
+        scalar.to_string()
     }
 
     fn to_scalar_ref<'a>(scalar: &'a Self::Scalar) -> Self::ScalarRef<'a> {
@@ -34,7 +34,7 @@ impl ArgType for StringType {
     }
 
     fn try_downcast_scalar<'a>(scalar: &'a Scalar) -> Option<Self::ScalarRef<'a>> {
-        scalar.as_string().map(Vec::as_slice)
+        scalar.as_string().map(String::as_str)
     }
 
     fn try_downcast_column<'a>(col: &'a Column) -> Option<Self::Column> {
@@ -55,7 +55,7 @@ impl ArgType for StringType {
     }
 
     fn index_column<'a>((data, offsets): &'a Self::Column, index: usize) -> Self::ScalarRef<'a> {
-        &data[offsets[index]..offsets[index + 1]]
+        str_from_utf8(&data[offsets[index]..offsets[index + 1]])
     }
 
     fn slice_column<'a>((data, offsets): &'a Self::Column, range: Range<usize>) -> Self::Column {
@@ -85,7 +85,7 @@ impl ArgType for StringType {
     }
 
     fn push_item((data, offsets): &mut Self::ColumnBuilder, item: Self::ScalarRef<'_>) {
-        data.extend_from_slice(item);
+        data.extend_from_slice(item.as_bytes());
         offsets.push(data.len());
     }
 
@@ -107,9 +107,8 @@ impl ArgType for StringType {
     }
 
     fn build_scalar((data, offsets): Self::ColumnBuilder) -> Self::Scalar {
-        assert_eq!(data.len(), 1);
         assert_eq!(offsets.len(), 2);
-        data[offsets[0]..offsets[1]].to_vec()
+        str_from_utf8(&data[offsets[0]..offsets[1]]).to_string()
     }
 }
 
@@ -119,12 +118,12 @@ pub struct StringIterator<'a> {
 }
 
 impl<'a> Iterator for StringIterator<'a> {
-    type Item = &'a [u8];
+    type Item = &'a str;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.offsets
             .next()
-            .map(|range| &self.data[range[0]..range[1]])
+            .map(|range| str_from_utf8(&self.data[range[0]..range[1]]))
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -133,3 +132,9 @@ impl<'a> Iterator for StringIterator<'a> {
 }
 
 unsafe impl<'a> TrustedLen for StringIterator<'a> {}
+
+/// String columns only contain valid UTF-8, which is checked when building them
+/// from bytes, for example in the cast from `Binary`.
+fn str_from_utf8(bytes: &[u8]) -> &str {
+    std::str::from_utf8(bytes).expect("String column must be valid UTF-8")
+}
@@ -0,0 +1,199 @@
+use arrow2::buffer::Buffer;
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
+
+use crate::values::{Column, Scalar};
+
+use super::{number::Number, DataType, NumberType};
+
+/// Days since 1970-01-01.
+pub struct Date;
+/// Microseconds since 1970-01-01 00:00:00, without time zone.
+pub struct Timestamp;
+/// A duration in microseconds.
+pub struct Interval;
+
+pub type DateType = NumberType<Date>;
+pub type TimestampType = NumberType<Timestamp>;
+pub type IntervalType = NumberType<Interval>;
+
+pub const MICROS_PER_SECOND: i64 = 1_000_000;
+pub const MICROS_PER_DAY: i64 = 24 * 60 * 60 * MICROS_PER_SECOND;
+
+/// The number of days from 0001-01-01 to 1970-01-01.
+const EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+impl Number for Date {
+    type Storage = i32;
+
+    fn data_type() -> DataType {
+        DataType::Date
+    }
+
+    fn try_downcast_scalar(scalar: &Scalar) -> Option<Self::Storage> {
+        scalar.as_date().cloned()
+    }
+
+    fn try_downcast_column(col: &Column) -> Option<Buffer<Self::Storage>> {
+        col.as_date().cloned()
+    }
+
+    fn upcast_scalar(scalar: Self::Storage) -> Scalar {
+        Scalar::Date(scalar)
+    }
+
+    fn upcast_column(col: Buffer<Self::Storage>) -> Column {
+        Column::Date(col)
+    }
+}
+
+impl Number for Timestamp {
+    type Storage = i64;
+
+    fn data_type() -> DataType {
+        DataType::Timestamp
+    }
+
+    fn try_downcast_scalar(scalar: &Scalar) -> Option<Self::Storage> {
+        scalar.as_timestamp().cloned()
+    }
+
+    fn try_downcast_column(col: &Column) -> Option<Buffer<Self::Storage>> {
+        col.as_timestamp().cloned()
+    }
+
+    fn upcast_scalar(scalar: Self::Storage) -> Scalar {
+        Scalar::Timestamp(scalar)
+    }
+
+    fn upcast_column(col: Buffer<Self::Storage>) -> Column {
+        Column::Timestamp(col)
+    }
+}
+
+impl Number for Interval {
+    type Storage = i64;
+
+    fn data_type() -> DataType {
+        DataType::Interval
+    }
+
+    fn try_downcast_scalar(scalar: &Scalar) -> Option<Self::Storage> {
+        scalar.as_interval().cloned()
+    }
+
+    fn try_downcast_column(col: &Column) -> Option<Buffer<Self::Storage>> {
+        col.as_interval().cloned()
+    }
+
+    fn upcast_scalar(scalar: Self::Storage) -> Scalar {
+        Scalar::Interval(scalar)
+    }
+
+    fn upcast_column(col: Buffer<Self::Storage>) -> Column {
+        Column::Interval(col)
+    }
+}
+
+/// The unit that dates and timestamps are truncated to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeUnit {
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+pub fn date_to_naive(days: i32) -> Result<NaiveDate, String> {
+    days.checked_add(EPOCH_DAYS_FROM_CE)
+        .and_then(NaiveDate::from_num_days_from_ce_opt)
+        .ok_or_else(|| format!("date {days} is out of range"))
+}
+
+pub fn naive_to_date(date: NaiveDate) -> i32 {
+    date.num_days_from_ce() - EPOCH_DAYS_FROM_CE
+}
+
+pub fn timestamp_to_naive(micros: i64) -> Result<NaiveDateTime, String> {
+    let secs = micros.div_euclid(MICROS_PER_SECOND);
+    let nanos = micros.rem_euclid(MICROS_PER_SECOND) as u32 * 1_000;
+    NaiveDateTime::from_timestamp_opt(secs, nanos)
+        .ok_or_else(|| format!("timestamp {micros} is out of range"))
+}
+
+pub fn naive_to_timestamp(datetime: NaiveDateTime) -> i64 {
+    datetime.timestamp() * MICROS_PER_SECOND + datetime.timestamp_subsec_micros() as i64
+}
+
+pub fn date_to_timestamp(days: i32) -> Result<i64, String> {
+    (days as i64)
+        .checked_mul(MICROS_PER_DAY)
+        .ok_or_else(|| format!("date {days} is out of range"))
+}
+
+pub fn timestamp_to_date(micros: i64) -> Result<i32, String> {
+    i32::try_from(micros.div_euclid(MICROS_PER_DAY))
+        .map_err(|_| format!("timestamp {micros} is out of range"))
+}
+
+/// Formats a date as `YYYY-MM-DD`.
+pub fn format_date(days: i32) -> Result<String, String> {
+    Ok(date_to_naive(days)?.format("%Y-%m-%d").to_string())
+}
+
+/// Formats a timestamp as `YYYY-MM-DDTHH:MM:SS`, followed by the fraction of
+/// the second if it is not zero.
+pub fn format_timestamp(micros: i64) -> Result<String, String> {
+    Ok(timestamp_to_naive(micros)?
+        .format("%Y-%m-%dT%H:%M:%S%.f")
+        .to_string())
+}
+
+/// Parses a date in the form of `YYYY-MM-DD`.
+pub fn parse_date(text: &str) -> Result<i32, String> {
+    NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d")
+        .map(naive_to_date)
+        .map_err(|err| format!("unable to parse `{text}` as date: {err}"))
+}
+
+/// Parses a timestamp in the form of `YYYY-MM-DDTHH:MM:SS[.fraction]`, where the
+/// `T` may also be a space, or a date in the form of `YYYY-MM-DD`.
+pub fn parse_timestamp(text: &str) -> Result<i64, String> {
+    let trimmed = text.trim();
+    let datetime = NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%dT%H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%d %H:%M:%S%.f"))
+        .or_else(|err| {
+            NaiveDate::parse_from_str(trimmed, "%Y-%m-%d")
+                .map(|date| date.and_hms(0, 0, 0))
+                .map_err(|_| err)
+        })
+        .map_err(|err| format!("unable to parse `{text}` as timestamp: {err}"))?;
+    Ok(naive_to_timestamp(datetime))
+}
+
+/// Truncates a date to the start of the year, month or day.
+pub fn trunc_date(days: i32, unit: TimeUnit) -> Result<i32, String> {
+    let date = date_to_naive(days)?;
+    let date = match unit {
+        TimeUnit::Year => NaiveDate::from_ymd(date.year(), 1, 1),
+        TimeUnit::Month => NaiveDate::from_ymd(date.year(), date.month(), 1),
+        TimeUnit::Day | TimeUnit::Hour | TimeUnit::Minute | TimeUnit::Second => date,
+    };
+    Ok(naive_to_date(date))
+}
+
+/// Truncates a timestamp to the start of the unit.
+pub fn trunc_timestamp(micros: i64, unit: TimeUnit) -> Result<i64, String> {
+    let datetime = timestamp_to_naive(micros)?;
+    let (date, time) = (datetime.date(), datetime.time());
+    let datetime = match unit {
+        TimeUnit::Year => NaiveDate::from_ymd(date.year(), 1, 1).and_hms(0, 0, 0),
+        TimeUnit::Month => NaiveDate::from_ymd(date.year(), date.month(), 1).and_hms(0, 0, 0),
+        TimeUnit::Day => date.and_hms(0, 0, 0),
+        TimeUnit::Hour => date.and_hms(time.hour(), 0, 0),
+        TimeUnit::Minute => date.and_hms(time.hour(), time.minute(), 0),
+        TimeUnit::Second => date.and_hms(time.hour(), time.minute(), time.second()),
+    };
+    Ok(naive_to_timestamp(datetime))
+}
@@ -0,0 +1,506 @@
+use std::ops::Range;
+
+use arrow2::buffer::Buffer;
+
+use crate::values::{Column, Scalar, ScalarRef};
+
+use super::{
+    binary::{BinaryIterator, BinaryType},
+    temporal::{format_date, format_timestamp},
+    ArgType, DataType, GenericMap, ValueType,
+};
+
+/// A JSON-like value in the binary encoding described below.
+///
+/// Every value starts with a tag byte. Integers and floats are followed by
+/// 8 little-endian bytes, and a string by its `u32` byte length and the UTF-8
+/// bytes. An array is followed by the `u32` number of items, the `u32` end
+/// offset of each item, and the items. An object is followed by the `u32`
+/// number of entries, the sorted keys each encoded like a string without the
+/// tag, the `u32` end offset of each value, and the values. The offsets are
+/// relative to the first item, so that an item can be found without decoding
+/// its siblings, and every item is a complete value by itself.
+pub struct VariantType;
+
+const TAG_NULL: u8 = 0;
+const TAG_FALSE: u8 = 1;
+const TAG_TRUE: u8 = 2;
+const TAG_INT: u8 = 3;
+/// Only used for the integers that do not fit into `i64`.
+const TAG_UINT: u8 = 4;
+const TAG_FLOAT: u8 = 5;
+const TAG_STRING: u8 = 6;
+const TAG_ARRAY: u8 = 7;
+const TAG_OBJECT: u8 = 8;
+
+/// The encoding of the JSON `null`, which is also the default value.
+pub const JSON_NULL: &[u8] = &[TAG_NULL];
+
+impl ValueType for VariantType {
+    type Scalar = Vec<u8>;
+    type ScalarRef<'a> = &'a [u8];
+    type Column = (Buffer<u8>, Vec<usize>);
+
+    fn to_owned_scalar<'a>(scalar: Self::ScalarRef<'a>) -> Self::Scalar {
+        scalar.to_vec()
+    }
+
+    fn to_scalar_ref<'a>(scalar: &'a Self::Scalar) -> Self::ScalarRef<'a> {
+        scalar
+    }
+
+    fn display_scalar(scalar: &Self::ScalarRef<'_>) -> String {
+        to_json(scalar)
+    }
+
+    fn display_column((data, offsets): &Self::Column) -> String {
+        let texts = offsets
+            .windows(2)
+            .map(|window| to_json(&data[window[0]..window[1]]))
+            .collect::<Vec<_>>();
+        format!("[{}]", texts.join(", "))
+    }
+}
+
+/// The column layout is the same as `Binary`, so most methods delegate to it.
+impl ArgType for VariantType {
+    type ColumnIterator<'a> = BinaryIterator<'a>;
+    type ColumnBuilder = (Vec<u8>, Vec<usize>);
+
+    fn data_type() -> DataType {
+        DataType::Variant
+    }
+
+    fn try_downcast_scalar<'a>(scalar: &'a Scalar) -> Option<Self::ScalarRef<'a>> {
+        scalar.as_variant().map(Vec::as_slice)
+    }
+
+    fn try_downcast_column<'a>(col: &'a Column) -> Option<Self::Column> {
+        col.as_variant()
+            .map(|(data, offsets)| (data.clone(), offsets.clone()))
+    }
+
+    fn upcast_scalar(scalar: Self::Scalar) -> Scalar {
+        Scalar::Variant(scalar)
+    }
+
+    fn upcast_column((data, offsets): Self::Column) -> Column {
+        Column::Variant { data, offsets }
+    }
+
+    fn column_len<'a>(col: &'a Self::Column) -> usize {
+        BinaryType::column_len(col)
+    }
+
+    fn index_column<'a>(col: &'a Self::Column, index: usize) -> Self::ScalarRef<'a> {
+        BinaryType::index_column(col, index)
+    }
+
+    fn slice_column<'a>(col: &'a Self::Column, range: Range<usize>) -> Self::Column {
+        BinaryType::slice_column(col, range)
+    }
+
+    fn iter_column<'a>(col: &'a Self::Column) -> Self::ColumnIterator<'a> {
+        BinaryType::iter_column(col)
+    }
+
+    fn create_builder(capacity: usize, generics: &GenericMap) -> Self::ColumnBuilder {
+        BinaryType::create_builder(capacity, generics)
+    }
+
+    fn column_to_builder(col: Self::Column) -> Self::ColumnBuilder {
+        BinaryType::column_to_builder(col)
+    }
+
+    fn builder_len(builder: &Self::ColumnBuilder) -> usize {
+        BinaryType::builder_len(builder)
+    }
+
+    fn push_item(builder: &mut Self::ColumnBuilder, item: Self::ScalarRef<'_>) {
+        BinaryType::push_item(builder, item)
+    }
+
+    fn push_default(builder: &mut Self::ColumnBuilder) {
+        BinaryType::push_item(builder, JSON_NULL)
+    }
+
+    fn append_builder(builder: &mut Self::ColumnBuilder, other: &Self::ColumnBuilder) {
+        BinaryType::append_builder(builder, other)
+    }
+
+    fn build_column(builder: Self::ColumnBuilder) -> Self::Column {
+        BinaryType::build_column(builder)
+    }
+
+    fn build_scalar(builder: Self::ColumnBuilder) -> Self::Scalar {
+        BinaryType::build_scalar(builder)
+    }
+}
+
+/// Parses JSON text into the variant encoding.
+pub fn parse_json(text: &str) -> Result<Vec<u8>, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(text).map_err(|err| format!("invalid JSON: {err}"))?;
+    let mut buf = Vec::new();
+    encode(&value, &mut buf);
+    Ok(buf)
+}
+
+/// Serializes a variant into JSON text.
+pub fn to_json(variant: &[u8]) -> String {
+    decode(variant).to_string()
+}
+
+fn encode(value: &serde_json::Value, buf: &mut Vec<u8>) {
+    match value {
+        serde_json::Value::Null => buf.push(TAG_NULL),
+        serde_json::Value::Bool(false) => buf.push(TAG_FALSE),
+        serde_json::Value::Bool(true) => buf.push(TAG_TRUE),
+        serde_json::Value::Number(num) => {
+            if let Some(val) = num.as_i64() {
+                encode_i64(val, buf);
+            } else if let Some(val) = num.as_u64() {
+                encode_u64(val, buf);
+            } else {
+                encode_f64(num.as_f64().unwrap(), buf);
+            }
+        }
+        serde_json::Value::String(val) => encode_str(val, buf),
+        serde_json::Value::Array(items) => {
+            buf.push(TAG_ARRAY);
+            write_u32(items.len(), buf);
+            encode_items(items.iter(), buf);
+        }
+        serde_json::Value::Object(entries) => {
+            buf.push(TAG_OBJECT);
+            write_u32(entries.len(), buf);
+            for key in entries.keys() {
+                write_u32(key.len(), buf);
+                buf.extend_from_slice(key.as_bytes());
+            }
+            encode_items(entries.values(), buf);
+        }
+    }
+}
+
+/// Writes the end offsets of the items followed by the items.
+fn encode_items<'a>(items: impl Iterator<Item = &'a serde_json::Value>, buf: &mut Vec<u8>) {
+    let mut payload = Vec::new();
+    for item in items {
+        encode(item, &mut payload);
+        write_u32(payload.len(), buf);
+    }
+    buf.extend_from_slice(&payload);
+}
+
+fn encode_i64(val: i64, buf: &mut Vec<u8>) {
+    buf.push(TAG_INT);
+    buf.extend_from_slice(&val.to_le_bytes());
+}
+
+fn encode_u64(val: u64, buf: &mut Vec<u8>) {
+    match i64::try_from(val) {
+        Ok(val) => encode_i64(val, buf),
+        Err(_) => {
+            buf.push(TAG_UINT);
+            buf.extend_from_slice(&val.to_le_bytes());
+        }
+    }
+}
+
+fn encode_f64(val: f64, buf: &mut Vec<u8>) {
+    buf.push(TAG_FLOAT);
+    buf.extend_from_slice(&val.to_le_bytes());
+}
+
+fn encode_str(val: &str, buf: &mut Vec<u8>) {
+    buf.push(TAG_STRING);
+    write_u32(val.len(), buf);
+    buf.extend_from_slice(val.as_bytes());
+}
+
+fn write_u32(val: usize, buf: &mut Vec<u8>) {
+    let val = u32::try_from(val).expect("variant is too large");
+    buf.extend_from_slice(&val.to_le_bytes());
+}
+
+fn read_u32(bytes: &[u8], pos: usize) -> usize {
+    u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap()) as usize
+}
+
+fn read_8_bytes(variant: &[u8]) -> [u8; 8] {
+    variant[1..9].try_into().unwrap()
+}
+
+fn decode(variant: &[u8]) -> serde_json::Value {
+    match variant[0] {
+        TAG_NULL => serde_json::Value::Null,
+        TAG_FALSE => serde_json::Value::Bool(false),
+        TAG_TRUE => serde_json::Value::Bool(true),
+        TAG_INT => i64::from_le_bytes(read_8_bytes(variant)).into(),
+        TAG_UINT => u64::from_le_bytes(read_8_bytes(variant)).into(),
+        TAG_FLOAT => f64::from_le_bytes(read_8_bytes(variant)).into(),
+        TAG_STRING => as_str(variant).unwrap().into(),
+        TAG_ARRAY => {
+            let len = read_u32(variant, 1);
+            (0..len)
+                .map(|idx| decode(item_at(variant, 5, len, idx)))
+                .collect()
+        }
+        TAG_OBJECT => {
+            let (keys, values_pos) = object_keys(variant);
+            let len = keys.len();
+            keys.iter()
+                .enumerate()
+                .map(|(idx, key)| {
+                    (
+                        key.to_string(),
+                        decode(item_at(variant, values_pos, len, idx)),
+                    )
+                })
+                .collect::<serde_json::Map<_, _>>()
+                .into()
+        }
+        tag => unreachable!("invalid variant tag {tag}"),
+    }
+}
+
+/// Checks that the bytes are a complete variant, so that reading it can not
+/// go out of bounds. Used on the variants received from other nodes.
+pub fn validate(variant: &[u8]) -> Result<(), String> {
+    validate_nested(variant, 0)
+}
+
+/// The same limit as the JSON parser, which bounds the depth of the variants
+/// built by `parse_json`.
+const MAX_DEPTH: usize = 128;
+
+fn validate_nested(variant: &[u8], depth: usize) -> Result<(), String> {
+    let invalid = || "invalid variant".to_string();
+    if depth > MAX_DEPTH {
+        return Err("variant is nested too deeply".to_string());
+    }
+    let read_len = |pos: usize| -> Result<usize, String> {
+        let bytes = variant.get(pos..pos + 4).ok_or_else(invalid)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+    };
+    let expect_len = |len: usize| {
+        if variant.len() == len {
+            Ok(())
+        } else {
+            Err(invalid())
+        }
+    };
+    let validate_str = |pos: usize| -> Result<usize, String> {
+        let len = read_len(pos)?;
+        let bytes = variant.get(pos + 4..pos + 4 + len).ok_or_else(invalid)?;
+        std::str::from_utf8(bytes).map_err(|_| invalid())?;
+        Ok(pos + 4 + len)
+    };
+    let validate_items = |pos: usize, len: usize| -> Result<(), String> {
+        let payload = pos + 4 * len;
+        let mut start = 0;
+        for idx in 0..len {
+            let end = read_len(pos + 4 * idx)?;
+            let item = variant.get(payload + start..payload + end);
+            validate_nested(item.ok_or_else(invalid)?, depth + 1)?;
+            start = end;
+        }
+        expect_len(payload + start)
+    };
+
+    match *variant.first().ok_or_else(invalid)? {
+        TAG_NULL | TAG_FALSE | TAG_TRUE => expect_len(1),
+        TAG_INT | TAG_UINT | TAG_FLOAT => expect_len(9),
+        TAG_STRING => expect_len(validate_str(1)?),
+        TAG_ARRAY => validate_items(5, read_len(1)?),
+        TAG_OBJECT => {
+            let len = read_len(1)?;
+            let mut pos = 5;
+            for _ in 0..len {
+                pos = validate_str(pos)?;
+            }
+            validate_items(pos, len)
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Returns the `idx`-th of the `len` items whose end offsets start at `pos`.
+fn item_at(variant: &[u8], pos: usize, len: usize, idx: usize) -> &[u8] {
+    let payload = pos + 4 * len;
+    let start = if idx == 0 {
+        0
+    } else {
+        read_u32(variant, pos + 4 * (idx - 1))
+    };
+    let end = read_u32(variant, pos + 4 * idx);
+    &variant[payload + start..payload + end]
+}
+
+/// Returns the keys of an object and the position of the value offsets.
+fn object_keys(variant: &[u8]) -> (Vec<&str>, usize) {
+    let len = read_u32(variant, 1);
+    let mut pos = 5;
+    let mut keys = Vec::with_capacity(len);
+    for _ in 0..len {
+        let key_len = read_u32(variant, pos);
+        let key = &variant[pos + 4..pos + 4 + key_len];
+        keys.push(std::str::from_utf8(key).expect("variant key must be valid UTF-8"));
+        pos += 4 + key_len;
+    }
+    (keys, pos)
+}
+
+/// A step in a path, either a key of an object or an index into an array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathElement<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// Parses a path such as `a.b[0]`. Keys are separated by dots, and array
+/// indexes are enclosed in brackets.
+pub fn parse_path(path: &str) -> Result<Vec<PathElement<'_>>, String> {
+    let invalid_path = || format!("invalid path `{path}`");
+    let mut elements = Vec::new();
+    let mut rest = path;
+    let mut expect_key = true;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').ok_or_else(invalid_path)?;
+            let idx = after[..end].parse().map_err(|_| invalid_path())?;
+            elements.push(PathElement::Index(idx));
+            rest = &after[end + 1..];
+            expect_key = false;
+        } else {
+            if !expect_key {
+                rest = rest.strip_prefix('.').ok_or_else(invalid_path)?;
+            }
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            if end == 0 {
+                return Err(invalid_path());
+            }
+            elements.push(PathElement::Key(&rest[..end]));
+            rest = &rest[end..];
+            expect_key = false;
+        }
+    }
+    Ok(elements)
+}
+
+/// Returns the value at `path`, or `None` if the path does not exist.
+pub fn get_path<'a>(variant: &'a [u8], path: &[PathElement]) -> Option<&'a [u8]> {
+    path.iter()
+        .try_fold(variant, |variant, element| match element {
+            PathElement::Index(idx) if variant[0] == TAG_ARRAY => {
+                let len = read_u32(variant, 1);
+                (*idx < len).then(|| item_at(variant, 5, len, *idx))
+            }
+            PathElement::Key(key) if variant[0] == TAG_OBJECT => {
+                let (keys, values_pos) = object_keys(variant);
+                let idx = keys.iter().position(|k| k == key)?;
+                Some(item_at(variant, values_pos, keys.len(), idx))
+            }
+            _ => None,
+        })
+}
+
+pub fn as_bool(variant: &[u8]) -> Option<bool> {
+    match variant[0] {
+        TAG_FALSE => Some(false),
+        TAG_TRUE => Some(true),
+        _ => None,
+    }
+}
+
+pub fn as_i64(variant: &[u8]) -> Option<i64> {
+    match variant[0] {
+        TAG_INT => Some(i64::from_le_bytes(read_8_bytes(variant))),
+        _ => None,
+    }
+}
+
+pub fn as_u64(variant: &[u8]) -> Option<u64> {
+    match variant[0] {
+        TAG_INT => u64::try_from(i64::from_le_bytes(read_8_bytes(variant))).ok(),
+        TAG_UINT => Some(u64::from_le_bytes(read_8_bytes(variant))),
+        _ => None,
+    }
+}
+
+/// Integers are converted to `f64`, possibly losing precision.
+pub fn as_f64(variant: &[u8]) -> Option<f64> {
+    match variant[0] {
+        TAG_INT => Some(i64::from_le_bytes(read_8_bytes(variant)) as f64),
+        TAG_UINT => Some(u64::from_le_bytes(read_8_bytes(variant)) as f64),
+        TAG_FLOAT => Some(f64::from_le_bytes(read_8_bytes(variant))),
+        _ => None,
+    }
+}
+
+pub fn as_str(variant: &[u8]) -> Option<&str> {
+    match variant[0] {
+        TAG_STRING => {
+            let len = read_u32(variant, 1);
+            Some(
+                std::str::from_utf8(&variant[5..5 + len])
+                    .expect("variant string must be valid UTF-8"),
+            )
+        }
+        _ => None,
+    }
+}
+
+/// Returns whether values of the type can be implicitly upcast into `Variant`.
+///
+/// Strings are excluded, because the cast from `String` to `Variant` parses
+/// the string as JSON instead of wrapping it.
+pub fn can_upcast_to_variant(ty: &DataType) -> bool {
+    matches!(
+        ty,
+        DataType::Boolean
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+            | DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::Float32
+            | DataType::Float64
+            | DataType::Decimal { .. }
+            | DataType::Date
+            | DataType::Timestamp
+            | DataType::Interval
+    )
+}
+
+/// Encodes a scalar whose type passes [`can_upcast_to_variant`]. Decimals
+/// become floats, dates and timestamps become strings, and intervals become
+/// integers of microseconds.
+pub fn upcast_to_variant(scalar: ScalarRef) -> Result<Vec<u8>, String> {
+    let mut buf = Vec::new();
+    match scalar {
+        ScalarRef::Boolean(false) => buf.push(TAG_FALSE),
+        ScalarRef::Boolean(true) => buf.push(TAG_TRUE),
+        ScalarRef::UInt8(val) => encode_i64(val as i64, &mut buf),
+        ScalarRef::UInt16(val) => encode_i64(val as i64, &mut buf),
+        ScalarRef::UInt32(val) => encode_i64(val as i64, &mut buf),
+        ScalarRef::UInt64(val) => encode_u64(val, &mut buf),
+        ScalarRef::Int8(val) => encode_i64(val as i64, &mut buf),
+        ScalarRef::Int16(val) => encode_i64(val as i64, &mut buf),
+        ScalarRef::Int32(val) => encode_i64(val as i64, &mut buf),
+        ScalarRef::Int64(val) | ScalarRef::Interval(val) => encode_i64(val, &mut buf),
+        ScalarRef::Float32(val) => encode_f64(val.0 as f64, &mut buf),
+        ScalarRef::Float64(val) => encode_f64(val.0, &mut buf),
+        ScalarRef::Decimal { value, scale, .. } => {
+            encode_f64(value as f64 / 10f64.powi(scale as i32), &mut buf)
+        }
+        ScalarRef::Date(val) => encode_str(&format_date(val)?, &mut buf),
+        ScalarRef::Timestamp(val) => encode_str(&format_timestamp(val)?, &mut buf),
+        scalar => return Err(format!("unable to cast {scalar} to Variant")),
+    }
+    Ok(buf)
+}
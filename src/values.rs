@@ -1,4 +1,4 @@
-use std::{iter::once, ops::Range};
+use std::{borrow::Cow, iter::once, ops::Range};
 
 use arrow2::{
     bitmap::{Bitmap, MutableBitmap},
@@ -8,7 +8,7 @@ use arrow2::{
 use enum_as_inner::EnumAsInner;
 
 use crate::{
-    types::*,
+    types::{map::map_entries_type, variant::JSON_NULL, *},
     util::{append_bitmap, bitmap_into_mut, buffer_into_mut, constant_bitmap},
 };
 
@@ -31,10 +31,26 @@ pub enum Scalar {
     EmptyArray,
     Int8(i8),
     Int16(i16),
+    Int32(i32),
+    Int64(i64),
+    Date(i32),
+    Timestamp(i64),
+    Interval(i64),
+    Float32(F32),
+    Float64(F64),
+    Decimal {
+        value: i128,
+        precision: u8,
+        scale: u8,
+    },
     UInt8(u8),
     UInt16(u16),
+    UInt32(u32),
+    UInt64(u64),
     Boolean(bool),
-    String(Vec<u8>),
+    String(String),
+    Binary(Vec<u8>),
+    Variant(Vec<u8>),
     Array(Column),
     Tuple(Vec<Scalar>),
 }
@@ -46,10 +62,26 @@ pub enum ScalarRef<'a> {
     EmptyArray,
     Int8(i8),
     Int16(i16),
+    Int32(i32),
+    Int64(i64),
+    Date(i32),
+    Timestamp(i64),
+    Interval(i64),
+    Float32(F32),
+    Float64(F64),
+    Decimal {
+        value: i128,
+        precision: u8,
+        scale: u8,
+    },
     UInt8(u8),
     UInt16(u16),
+    UInt32(u32),
+    UInt64(u64),
     Boolean(bool),
-    String(&'a [u8]),
+    String(&'a str),
+    Binary(&'a [u8]),
+    Variant(&'a [u8]),
     Array(Column),
     Tuple(Vec<ScalarRef<'a>>),
 }
@@ -64,13 +96,36 @@ pub enum Column {
     },
     Int8(Buffer<i8>),
     Int16(Buffer<i16>),
+    Int32(Buffer<i32>),
+    Int64(Buffer<i64>),
+    Date(Buffer<i32>),
+    Timestamp(Buffer<i64>),
+    Interval(Buffer<i64>),
+    Float32(Buffer<f32>),
+    Float64(Buffer<f64>),
+    Decimal {
+        values: Buffer<i128>,
+        precision: u8,
+        scale: u8,
+    },
     UInt8(Buffer<u8>),
     UInt16(Buffer<u16>),
+    UInt32(Buffer<u32>),
+    UInt64(Buffer<u64>),
     Boolean(Bitmap),
+    /// The data is valid UTF-8.
     String {
         data: Buffer<u8>,
         offsets: Vec<usize>,
     },
+    Binary {
+        data: Buffer<u8>,
+        offsets: Vec<usize>,
+    },
+    Variant {
+        data: Buffer<u8>,
+        offsets: Vec<usize>,
+    },
     Array {
         array: Box<Column>,
         offsets: Vec<usize>,
@@ -83,6 +138,17 @@ pub enum Column {
         fields: Vec<Column>,
         len: usize,
     },
+    /// Row `i` is `values[keys[i]]`. It has the same data type as `values`,
+    /// and is decoded when downcast to a typed column.
+    Dictionary {
+        keys: Buffer<u32>,
+        values: Box<Column>,
+    },
+    /// A scalar broadcast to `len` rows without materializing them.
+    Const {
+        scalar: Box<Scalar>,
+        len: usize,
+    },
 }
 
 #[derive(Debug, Clone, EnumAsInner)]
@@ -95,13 +161,36 @@ pub enum ColumnBuilder {
     },
     Int8(Vec<i8>),
     Int16(Vec<i16>),
+    Int32(Vec<i32>),
+    Int64(Vec<i64>),
+    Date(Vec<i32>),
+    Timestamp(Vec<i64>),
+    Interval(Vec<i64>),
+    Float32(Vec<f32>),
+    Float64(Vec<f64>),
+    Decimal {
+        values: Vec<i128>,
+        precision: u8,
+        scale: u8,
+    },
     UInt8(Vec<u8>),
     UInt16(Vec<u16>),
+    UInt32(Vec<u32>),
+    UInt64(Vec<u64>),
     Boolean(MutableBitmap),
+    /// The data is valid UTF-8.
     String {
         data: Vec<u8>,
         offsets: Vec<usize>,
     },
+    Binary {
+        data: Vec<u8>,
+        offsets: Vec<usize>,
+    },
+    Variant {
+        data: Vec<u8>,
+        offsets: Vec<usize>,
+    },
     Array {
         array: Box<ColumnBuilder>,
         offsets: Vec<usize>,
@@ -144,22 +233,83 @@ impl<'a, T: ValueType> Clone for ValueRef<'a, T> {
 }
 
 impl Scalar {
-    pub fn as_ref(&self) -> ScalarRef {
+    pub fn as_ref(&self) -> ScalarRef<'_> {
         match self {
             Scalar::Null => ScalarRef::Null,
             Scalar::EmptyArray => ScalarRef::EmptyArray,
             Scalar::Int8(i) => ScalarRef::Int8(*i),
             Scalar::Int16(i) => ScalarRef::Int16(*i),
+            Scalar::Int32(i) => ScalarRef::Int32(*i),
+            Scalar::Int64(i) => ScalarRef::Int64(*i),
+            Scalar::Date(i) => ScalarRef::Date(*i),
+            Scalar::Timestamp(i) => ScalarRef::Timestamp(*i),
+            Scalar::Interval(i) => ScalarRef::Interval(*i),
+            Scalar::Float32(i) => ScalarRef::Float32(*i),
+            Scalar::Float64(i) => ScalarRef::Float64(*i),
+            Scalar::Decimal {
+                value,
+                precision,
+                scale,
+            } => ScalarRef::Decimal {
+                value: *value,
+                precision: *precision,
+                scale: *scale,
+            },
             Scalar::UInt8(i) => ScalarRef::UInt8(*i),
             Scalar::UInt16(i) => ScalarRef::UInt16(*i),
+            Scalar::UInt32(i) => ScalarRef::UInt32(*i),
+            Scalar::UInt64(i) => ScalarRef::UInt64(*i),
             Scalar::Boolean(b) => ScalarRef::Boolean(*b),
-            Scalar::String(s) => ScalarRef::String(s.as_slice()),
+            Scalar::String(s) => ScalarRef::String(s.as_str()),
+            Scalar::Binary(s) => ScalarRef::Binary(s.as_slice()),
+            Scalar::Variant(s) => ScalarRef::Variant(s.as_slice()),
             Scalar::Array(col) => ScalarRef::Array(col.clone()),
             Scalar::Tuple(fields) => ScalarRef::Tuple(fields.iter().map(Scalar::as_ref).collect()),
         }
     }
 }
 
+/// Arrays are compared item by item, so that slices of different columns are
+/// equal if they hold the same values.
+impl PartialEq for ScalarRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ScalarRef::Null, ScalarRef::Null) => true,
+            (ScalarRef::EmptyArray, ScalarRef::EmptyArray) => true,
+            (ScalarRef::Int8(lhs), ScalarRef::Int8(rhs)) => lhs == rhs,
+            (ScalarRef::Int16(lhs), ScalarRef::Int16(rhs)) => lhs == rhs,
+            (ScalarRef::Int32(lhs), ScalarRef::Int32(rhs)) => lhs == rhs,
+            (ScalarRef::Int64(lhs), ScalarRef::Int64(rhs)) => lhs == rhs,
+            (ScalarRef::Date(lhs), ScalarRef::Date(rhs)) => lhs == rhs,
+            (ScalarRef::Timestamp(lhs), ScalarRef::Timestamp(rhs)) => lhs == rhs,
+            (ScalarRef::Interval(lhs), ScalarRef::Interval(rhs)) => lhs == rhs,
+            (ScalarRef::Float32(lhs), ScalarRef::Float32(rhs)) => lhs == rhs,
+            (ScalarRef::Float64(lhs), ScalarRef::Float64(rhs)) => lhs == rhs,
+            (ScalarRef::UInt8(lhs), ScalarRef::UInt8(rhs)) => lhs == rhs,
+            (ScalarRef::UInt16(lhs), ScalarRef::UInt16(rhs)) => lhs == rhs,
+            (ScalarRef::UInt32(lhs), ScalarRef::UInt32(rhs)) => lhs == rhs,
+            (ScalarRef::UInt64(lhs), ScalarRef::UInt64(rhs)) => lhs == rhs,
+            (ScalarRef::Boolean(lhs), ScalarRef::Boolean(rhs)) => lhs == rhs,
+            (ScalarRef::String(lhs), ScalarRef::String(rhs)) => lhs == rhs,
+            (ScalarRef::Binary(lhs), ScalarRef::Binary(rhs)) => lhs == rhs,
+            (ScalarRef::Variant(lhs), ScalarRef::Variant(rhs)) => lhs == rhs,
+            (
+                ScalarRef::Decimal { value, scale, .. },
+                ScalarRef::Decimal {
+                    value: other_value,
+                    scale: other_scale,
+                    ..
+                },
+            ) => value == other_value && scale == other_scale,
+            (ScalarRef::Array(lhs), ScalarRef::Array(rhs)) => {
+                lhs.len() == rhs.len() && lhs.iter().eq(rhs.iter())
+            }
+            (ScalarRef::Tuple(lhs), ScalarRef::Tuple(rhs)) => lhs == rhs,
+            _ => false,
+        }
+    }
+}
+
 impl<'a> ScalarRef<'a> {
     pub fn to_owned(&self) -> Scalar {
         match self {
@@ -167,10 +317,30 @@ impl<'a> ScalarRef<'a> {
             ScalarRef::EmptyArray => Scalar::EmptyArray,
             ScalarRef::Int8(i) => Scalar::Int8(*i),
             ScalarRef::Int16(i) => Scalar::Int16(*i),
+            ScalarRef::Int32(i) => Scalar::Int32(*i),
+            ScalarRef::Int64(i) => Scalar::Int64(*i),
+            ScalarRef::Date(i) => Scalar::Date(*i),
+            ScalarRef::Timestamp(i) => Scalar::Timestamp(*i),
+            ScalarRef::Interval(i) => Scalar::Interval(*i),
+            ScalarRef::Float32(i) => Scalar::Float32(*i),
+            ScalarRef::Float64(i) => Scalar::Float64(*i),
+            ScalarRef::Decimal {
+                value,
+                precision,
+                scale,
+            } => Scalar::Decimal {
+                value: *value,
+                precision: *precision,
+                scale: *scale,
+            },
             ScalarRef::UInt8(i) => Scalar::UInt8(*i),
             ScalarRef::UInt16(i) => Scalar::UInt16(*i),
+            ScalarRef::UInt32(i) => Scalar::UInt32(*i),
+            ScalarRef::UInt64(i) => Scalar::UInt64(*i),
             ScalarRef::Boolean(b) => Scalar::Boolean(*b),
-            ScalarRef::String(s) => Scalar::String(s.to_vec()),
+            ScalarRef::String(s) => Scalar::String(s.to_string()),
+            ScalarRef::Binary(s) => Scalar::Binary(s.to_vec()),
+            ScalarRef::Variant(s) => Scalar::Variant(s.to_vec()),
             ScalarRef::Array(col) => Scalar::Array(col.clone()),
             ScalarRef::Tuple(fields) => {
                 Scalar::Tuple(fields.iter().map(ScalarRef::to_owned).collect())
@@ -184,18 +354,39 @@ impl<'a> ScalarRef<'a> {
             ScalarRef::EmptyArray => ColumnBuilder::EmptyArray { len: n },
             ScalarRef::Int8(i) => ColumnBuilder::Int8(vec![*i; n]),
             ScalarRef::Int16(i) => ColumnBuilder::Int16(vec![*i; n]),
+            ScalarRef::Int32(i) => ColumnBuilder::Int32(vec![*i; n]),
+            ScalarRef::Int64(i) => ColumnBuilder::Int64(vec![*i; n]),
+            ScalarRef::Date(i) => ColumnBuilder::Date(vec![*i; n]),
+            ScalarRef::Timestamp(i) => ColumnBuilder::Timestamp(vec![*i; n]),
+            ScalarRef::Interval(i) => ColumnBuilder::Interval(vec![*i; n]),
+            ScalarRef::Float32(i) => ColumnBuilder::Float32(vec![i.0; n]),
+            ScalarRef::Float64(i) => ColumnBuilder::Float64(vec![i.0; n]),
+            ScalarRef::Decimal {
+                value,
+                precision,
+                scale,
+            } => ColumnBuilder::Decimal {
+                values: vec![*value; n],
+                precision: *precision,
+                scale: *scale,
+            },
             ScalarRef::UInt8(i) => ColumnBuilder::UInt8(vec![*i; n]),
             ScalarRef::UInt16(i) => ColumnBuilder::UInt16(vec![*i; n]),
+            ScalarRef::UInt32(i) => ColumnBuilder::UInt32(vec![*i; n]),
+            ScalarRef::UInt64(i) => ColumnBuilder::UInt64(vec![*i; n]),
             ScalarRef::Boolean(b) => ColumnBuilder::Boolean(constant_bitmap(*b, n)),
             ScalarRef::String(s) => {
-                let len = s.len();
-                let mut data = Vec::with_capacity(len * n);
-                for _ in 0..n {
-                    data.extend_from_slice(s);
-                }
-                let offsets = once(0).chain((0..n).map(|i| len * (i + 1))).collect();
+                let (data, offsets) = repeat_bytes(s.as_bytes(), n);
                 ColumnBuilder::String { data, offsets }
             }
+            ScalarRef::Binary(s) => {
+                let (data, offsets) = repeat_bytes(s, n);
+                ColumnBuilder::Binary { data, offsets }
+            }
+            ScalarRef::Variant(s) => {
+                let (data, offsets) = repeat_bytes(s, n);
+                ColumnBuilder::Variant { data, offsets }
+            }
             ScalarRef::Array(col) => {
                 let col = ColumnBuilder::from_column(col.clone());
                 let len = col.len();
@@ -224,30 +415,69 @@ impl Column {
             Column::EmptyArray { len } => *len,
             Column::Int8(col) => col.len(),
             Column::Int16(col) => col.len(),
+            Column::Int32(col) => col.len(),
+            Column::Int64(col) => col.len(),
+            Column::Date(col) => col.len(),
+            Column::Timestamp(col) => col.len(),
+            Column::Interval(col) => col.len(),
+            Column::Float32(col) => col.len(),
+            Column::Float64(col) => col.len(),
+            Column::Decimal { values, .. } => values.len(),
             Column::UInt8(col) => col.len(),
             Column::UInt16(col) => col.len(),
+            Column::UInt32(col) => col.len(),
+            Column::UInt64(col) => col.len(),
             Column::Boolean(col) => col.len(),
-            Column::String { data: _, offsets } => offsets.len() - 1,
+            Column::String { data: _, offsets }
+            | Column::Binary { data: _, offsets }
+            | Column::Variant { data: _, offsets } => offsets.len() - 1,
             Column::Array { array: _, offsets } => offsets.len() - 1,
             Column::Nullable {
                 column: _,
                 validity,
             } => validity.len(),
             Column::Tuple { len, .. } => *len,
+            Column::Dictionary { keys, .. } => keys.len(),
+            Column::Const { len, .. } => *len,
         }
     }
 
-    pub fn index(&self, index: usize) -> ScalarRef {
+    pub fn index(&self, index: usize) -> ScalarRef<'_> {
         match self {
             Column::Null { .. } => ScalarRef::Null,
             Column::EmptyArray { .. } => ScalarRef::EmptyArray,
             Column::Int8(col) => ScalarRef::Int8(col[index]),
             Column::Int16(col) => ScalarRef::Int16(col[index]),
+            Column::Int32(col) => ScalarRef::Int32(col[index]),
+            Column::Int64(col) => ScalarRef::Int64(col[index]),
+            Column::Date(col) => ScalarRef::Date(col[index]),
+            Column::Timestamp(col) => ScalarRef::Timestamp(col[index]),
+            Column::Interval(col) => ScalarRef::Interval(col[index]),
+            Column::Float32(col) => ScalarRef::Float32(F32(col[index])),
+            Column::Float64(col) => ScalarRef::Float64(F64(col[index])),
+            Column::Decimal {
+                values,
+                precision,
+                scale,
+            } => ScalarRef::Decimal {
+                value: values[index],
+                precision: *precision,
+                scale: *scale,
+            },
             Column::UInt8(col) => ScalarRef::UInt8(col[index]),
             Column::UInt16(col) => ScalarRef::UInt16(col[index]),
+            Column::UInt32(col) => ScalarRef::UInt32(col[index]),
+            Column::UInt64(col) => ScalarRef::UInt64(col[index]),
             Column::Boolean(col) => ScalarRef::Boolean(col.get(index).unwrap()),
-            Column::String { data, offsets } => {
-                ScalarRef::String(&data[offsets[index]..offsets[index + 1]])
+            Column::String { data, offsets } => ScalarRef::String(
+                std::str::from_utf8(&data[offsets[index]..offsets[index + 1]])
+                    .expect("String column must be valid UTF-8"),
+            ),
+            Column::Binary { data, offsets } => {
+                ScalarRef::Binary(&data[offsets[index]..offsets[index + 1]])
+            }
+            Column::Variant { data, offsets } => {
+                ScalarRef::Variant(&data[offsets[index]..offsets[index + 1]])
             }
             Column::Array { array, offsets } => {
                 ScalarRef::Array((*array).clone().slice(offsets[index]..offsets[index + 1]))
@@ -262,6 +492,8 @@ impl Column {
             Column::Tuple { fields, .. } => {
                 ScalarRef::Tuple(fields.iter().map(|field| field.index(index)).collect())
             }
+            Column::Dictionary { keys, values } => values.index(keys[index] as usize),
+            Column::Const { scalar, .. } => Scalar::as_ref(scalar),
         }
     }
 
@@ -285,12 +517,48 @@ impl Column {
             Column::Int16(col) => {
                 Column::Int16(col.clone().slice(range.start, range.end - range.start))
             }
+            Column::Int32(col) => {
+                Column::Int32(col.clone().slice(range.start, range.end - range.start))
+            }
+            Column::Int64(col) => {
+                Column::Int64(col.clone().slice(range.start, range.end - range.start))
+            }
+            Column::Date(col) => {
+                Column::Date(col.clone().slice(range.start, range.end - range.start))
+            }
+            Column::Timestamp(col) => {
+                Column::Timestamp(col.clone().slice(range.start, range.end - range.start))
+            }
+            Column::Interval(col) => {
+                Column::Interval(col.clone().slice(range.start, range.end - range.start))
+            }
+            Column::Float32(col) => {
+                Column::Float32(col.clone().slice(range.start, range.end - range.start))
+            }
+            Column::Float64(col) => {
+                Column::Float64(col.clone().slice(range.start, range.end - range.start))
+            }
+            Column::Decimal {
+                values,
+                precision,
+                scale,
+            } => Column::Decimal {
+                values: values.clone().slice(range.start, range.end - range.start),
+                precision: *precision,
+                scale: *scale,
+            },
             Column::UInt8(col) => {
                 Column::UInt8(col.clone().slice(range.start, range.end - range.start))
             }
             Column::UInt16(col) => {
                 Column::UInt16(col.clone().slice(range.start, range.end - range.start))
             }
+            Column::UInt32(col) => {
+                Column::UInt32(col.clone().slice(range.start, range.end - range.start))
+            }
+            Column::UInt64(col) => {
+                Column::UInt64(col.clone().slice(range.start, range.end - range.start))
+            }
             Column::Boolean(col) => {
                 Column::Boolean(col.clone().slice(range.start, range.end - range.start))
             }
@@ -301,6 +569,20 @@ impl Column {
                     offsets,
                 }
             }
+            Column::Binary { data, offsets } => {
+                let offsets = offsets[range.start..(range.end + 1)].to_vec();
+                Column::Binary {
+                    data: data.clone(),
+                    offsets,
+                }
+            }
+            Column::Variant { data, offsets } => {
+                let offsets = offsets[range.start..(range.end + 1)].to_vec();
+                Column::Variant {
+                    data: data.clone(),
+                    offsets,
+                }
+            }
             Column::Array { array, offsets } => {
                 let offsets = offsets[range.start..(range.end + 1)].to_vec();
                 Column::Array {
@@ -322,32 +604,123 @@ impl Column {
                     .collect(),
                 len: range.end - range.start,
             },
+            Column::Dictionary { keys, values } => Column::Dictionary {
+                keys: keys.clone().slice(range.start, range.end - range.start),
+                values: values.clone(),
+            },
+            Column::Const { scalar, .. } => Column::Const {
+                scalar: scalar.clone(),
+                len: range.end - range.start,
+            },
         }
     }
 
-    pub fn iter(&self) -> ColumnIterator {
+    pub fn iter(&self) -> ColumnIterator<'_> {
         ColumnIterator {
             column: self,
             index: 0,
             len: self.len(),
         }
     }
+
+    /// The data type of the column. The names of the tuple fields are not
+    /// known to the column, so they are left unnamed.
+    pub fn data_type(&self) -> DataType {
+        match self {
+            Column::Null { .. } => DataType::Null,
+            Column::EmptyArray { .. } => DataType::EmptyArray,
+            Column::Int8(_) => DataType::Int8,
+            Column::Int16(_) => DataType::Int16,
+            Column::Int32(_) => DataType::Int32,
+            Column::Int64(_) => DataType::Int64,
+            Column::Date(_) => DataType::Date,
+            Column::Timestamp(_) => DataType::Timestamp,
+            Column::Interval(_) => DataType::Interval,
+            Column::Float32(_) => DataType::Float32,
+            Column::Float64(_) => DataType::Float64,
+            Column::Decimal {
+                precision, scale, ..
+            } => DataType::Decimal {
+                precision: *precision,
+                scale: *scale,
+            },
+            Column::UInt8(_) => DataType::UInt8,
+            Column::UInt16(_) => DataType::UInt16,
+            Column::UInt32(_) => DataType::UInt32,
+            Column::UInt64(_) => DataType::UInt64,
+            Column::Boolean(_) => DataType::Boolean,
+            Column::String { .. } => DataType::String,
+            Column::Binary { .. } => DataType::Binary,
+            Column::Variant { .. } => DataType::Variant,
+            Column::Array { array, .. } => DataType::Array(Box::new(array.data_type())),
+            Column::Nullable { column, .. } => DataType::Nullable(Box::new(column.data_type())),
+            Column::Tuple { fields, .. } => DataType::Tuple {
+                fields_name: vec![None; fields.len()],
+                fields_type: fields.iter().map(Column::data_type).collect(),
+            },
+            Column::Dictionary { values, .. } => values.data_type(),
+            Column::Const { scalar, .. } => Scalar::as_ref(scalar).repeat(0).build().data_type(),
+        }
+    }
+
+    /// Materializes a dictionary or constant column into a plain column, or
+    /// returns other columns as they are.
+    pub fn flatten(&self) -> Cow<'_, Column> {
+        match self {
+            Column::Dictionary { keys, values } => {
+                Cow::Owned(values.take(keys).flatten().into_owned())
+            }
+            Column::Const { scalar, len } => {
+                Cow::Owned(Scalar::as_ref(scalar).repeat(*len).build())
+            }
+            col => Cow::Borrowed(col),
+        }
+    }
 }
 
 impl ColumnBuilder {
     pub fn from_column(col: Column) -> Self {
         match col {
+            col @ (Column::Dictionary { .. } | Column::Const { .. }) => {
+                ColumnBuilder::from_column(col.flatten().into_owned())
+            }
             Column::Null { len } => ColumnBuilder::Null { len },
             Column::EmptyArray { len } => ColumnBuilder::EmptyArray { len },
             Column::Int8(col) => ColumnBuilder::Int8(buffer_into_mut(col)),
             Column::Int16(col) => ColumnBuilder::Int16(buffer_into_mut(col)),
+            Column::Int32(col) => ColumnBuilder::Int32(buffer_into_mut(col)),
+            Column::Int64(col) => ColumnBuilder::Int64(buffer_into_mut(col)),
+            Column::Date(col) => ColumnBuilder::Date(buffer_into_mut(col)),
+            Column::Timestamp(col) => ColumnBuilder::Timestamp(buffer_into_mut(col)),
+            Column::Interval(col) => ColumnBuilder::Interval(buffer_into_mut(col)),
+            Column::Float32(col) => ColumnBuilder::Float32(buffer_into_mut(col)),
+            Column::Float64(col) => ColumnBuilder::Float64(buffer_into_mut(col)),
+            Column::Decimal {
+                values,
+                precision,
+                scale,
+            } => ColumnBuilder::Decimal {
+                values: buffer_into_mut(values),
+                precision,
+                scale,
+            },
             Column::UInt8(col) => ColumnBuilder::UInt8(buffer_into_mut(col)),
             Column::UInt16(col) => ColumnBuilder::UInt16(buffer_into_mut(col)),
+            Column::UInt32(col) => ColumnBuilder::UInt32(buffer_into_mut(col)),
+            Column::UInt64(col) => ColumnBuilder::UInt64(buffer_into_mut(col)),
             Column::Boolean(col) => ColumnBuilder::Boolean(bitmap_into_mut(col)),
             Column::String { data, offsets } => ColumnBuilder::String {
                 data: buffer_into_mut(data),
                 offsets,
             },
+            Column::Binary { data, offsets } => ColumnBuilder::Binary {
+                data: buffer_into_mut(data),
+                offsets,
+            },
+            Column::Variant { data, offsets } => ColumnBuilder::Variant {
+                data: buffer_into_mut(data),
+                offsets,
+            },
             Column::Array { array, offsets } => ColumnBuilder::Array {
                 array: Box::new(ColumnBuilder::from_column(*array)),
                 offsets,
@@ -372,10 +745,22 @@ impl ColumnBuilder {
             ColumnBuilder::EmptyArray { len } => *len,
             ColumnBuilder::Int8(col) => col.len(),
             ColumnBuilder::Int16(col) => col.len(),
+            ColumnBuilder::Int32(col) => col.len(),
+            ColumnBuilder::Int64(col) => col.len(),
+            ColumnBuilder::Date(col) => col.len(),
+            ColumnBuilder::Timestamp(col) => col.len(),
+            ColumnBuilder::Interval(col) => col.len(),
+            ColumnBuilder::Float32(col) => col.len(),
+            ColumnBuilder::Float64(col) => col.len(),
+            ColumnBuilder::Decimal { values, .. } => values.len(),
             ColumnBuilder::UInt8(col) => col.len(),
             ColumnBuilder::UInt16(col) => col.len(),
+            ColumnBuilder::UInt32(col) => col.len(),
+            ColumnBuilder::UInt64(col) => col.len(),
             ColumnBuilder::Boolean(col) => col.len(),
-            ColumnBuilder::String { data: _, offsets } => offsets.len() - 1,
+            ColumnBuilder::String { data: _, offsets }
+            | ColumnBuilder::Binary { data: _, offsets }
+            | ColumnBuilder::Variant { data: _, offsets } => offsets.len() - 1,
             ColumnBuilder::Array { array: _, offsets } => offsets.len() - 1,
             ColumnBuilder::Nullable {
                 column: _,
@@ -398,10 +783,40 @@ impl ColumnBuilder {
                     offsets,
                 }
             }
+            DataType::Binary => {
+                let mut offsets = Vec::with_capacity(capacity + 1);
+                offsets.push(0);
+                ColumnBuilder::Binary {
+                    data: Vec::new(),
+                    offsets,
+                }
+            }
+            DataType::Variant => {
+                let mut offsets = Vec::with_capacity(capacity + 1);
+                offsets.push(0);
+                ColumnBuilder::Variant {
+                    data: Vec::new(),
+                    offsets,
+                }
+            }
             DataType::UInt8 => ColumnBuilder::UInt8(Vec::with_capacity(capacity)),
             DataType::UInt16 => ColumnBuilder::UInt16(Vec::with_capacity(capacity)),
+            DataType::UInt32 => ColumnBuilder::UInt32(Vec::with_capacity(capacity)),
+            DataType::UInt64 => ColumnBuilder::UInt64(Vec::with_capacity(capacity)),
             DataType::Int8 => ColumnBuilder::Int8(Vec::with_capacity(capacity)),
             DataType::Int16 => ColumnBuilder::Int16(Vec::with_capacity(capacity)),
+            DataType::Int32 => ColumnBuilder::Int32(Vec::with_capacity(capacity)),
+            DataType::Int64 => ColumnBuilder::Int64(Vec::with_capacity(capacity)),
+            DataType::Date => ColumnBuilder::Date(Vec::with_capacity(capacity)),
+            DataType::Timestamp => ColumnBuilder::Timestamp(Vec::with_capacity(capacity)),
+            DataType::Interval => ColumnBuilder::Interval(Vec::with_capacity(capacity)),
+            DataType::Float32 => ColumnBuilder::Float32(Vec::with_capacity(capacity)),
+            DataType::Float64 => ColumnBuilder::Float64(Vec::with_capacity(capacity)),
+            DataType::Decimal { precision, scale } => ColumnBuilder::Decimal {
+                values: Vec::with_capacity(capacity),
+                precision: *precision,
+                scale: *scale,
+            },
             DataType::Nullable(ty) => ColumnBuilder::Nullable {
                 column: Box::new(Self::with_capacity(ty, capacity)),
                 validity: MutableBitmap::with_capacity(capacity),
@@ -414,8 +829,11 @@ impl ColumnBuilder {
                     offsets,
                 }
             }
-            DataType::Tuple(fields) => ColumnBuilder::Tuple {
-                fields: fields
+            DataType::Map(key, value) => {
+                Self::with_capacity(&map_entries_type(key, value), capacity)
+            }
+            DataType::Tuple { fields_type, .. } => ColumnBuilder::Tuple {
+                fields: fields_type
                     .iter()
                     .map(|field| Self::with_capacity(field, capacity))
                     .collect(),
@@ -431,10 +849,27 @@ impl ColumnBuilder {
             (ColumnBuilder::EmptyArray { len }, ScalarRef::EmptyArray) => *len += 1,
             (ColumnBuilder::Int8(col), ScalarRef::Int8(value)) => col.push(value),
             (ColumnBuilder::Int16(col), ScalarRef::Int16(value)) => col.push(value),
+            (ColumnBuilder::Int32(col), ScalarRef::Int32(value)) => col.push(value),
+            (ColumnBuilder::Int64(col), ScalarRef::Int64(value)) => col.push(value),
+            (ColumnBuilder::Date(col), ScalarRef::Date(value)) => col.push(value),
+            (ColumnBuilder::Timestamp(col), ScalarRef::Timestamp(value)) => col.push(value),
+            (ColumnBuilder::Interval(col), ScalarRef::Interval(value)) => col.push(value),
+            (ColumnBuilder::Float32(col), ScalarRef::Float32(value)) => col.push(value.0),
+            (ColumnBuilder::Float64(col), ScalarRef::Float64(value)) => col.push(value.0),
+            (ColumnBuilder::Decimal { values, .. }, ScalarRef::Decimal { value, .. }) => {
+                values.push(value)
+            }
             (ColumnBuilder::UInt8(col), ScalarRef::UInt8(value)) => col.push(value),
             (ColumnBuilder::UInt16(col), ScalarRef::UInt16(value)) => col.push(value),
+            (ColumnBuilder::UInt32(col), ScalarRef::UInt32(value)) => col.push(value),
+            (ColumnBuilder::UInt64(col), ScalarRef::UInt64(value)) => col.push(value),
             (ColumnBuilder::Boolean(col), ScalarRef::Boolean(value)) => col.push(value),
             (ColumnBuilder::String { data, offsets }, ScalarRef::String(value)) => {
+                data.extend_from_slice(value.as_bytes());
+                offsets.push(data.len());
+            }
+            (ColumnBuilder::Binary { data, offsets }, ScalarRef::Binary(value))
+            | (ColumnBuilder::Variant { data, offsets }, ScalarRef::Variant(value)) => {
                 data.extend_from_slice(value);
                 offsets.push(data.len());
             }
@@ -467,10 +902,24 @@ impl ColumnBuilder {
             ColumnBuilder::EmptyArray { len } => *len += 1,
             ColumnBuilder::Int8(col) => col.push(0),
             ColumnBuilder::Int16(col) => col.push(0),
+            ColumnBuilder::Int32(col) => col.push(0),
+            ColumnBuilder::Int64(col) => col.push(0),
+            ColumnBuilder::Date(col) => col.push(0),
+            ColumnBuilder::Timestamp(col) => col.push(0),
+            ColumnBuilder::Interval(col) => col.push(0),
+            ColumnBuilder::Float32(col) => col.push(0.0),
+            ColumnBuilder::Float64(col) => col.push(0.0),
+            ColumnBuilder::Decimal { values, .. } => values.push(0),
             ColumnBuilder::UInt8(col) => col.push(0),
             ColumnBuilder::UInt16(col) => col.push(0),
+            ColumnBuilder::UInt32(col) => col.push(0),
+            ColumnBuilder::UInt64(col) => col.push(0),
             ColumnBuilder::Boolean(col) => col.push(false),
-            ColumnBuilder::String { data, offsets } => {
+            ColumnBuilder::String { data, offsets } | ColumnBuilder::Binary { data, offsets } => {
+                offsets.push(data.len());
+            }
+            ColumnBuilder::Variant { data, offsets } => {
+                data.extend_from_slice(JSON_NULL);
                 offsets.push(data.len());
             }
             ColumnBuilder::Array { array, offsets } => {
@@ -503,12 +952,48 @@ impl ColumnBuilder {
             (ColumnBuilder::Int16(builder), ColumnBuilder::Int16(other_builder)) => {
                 builder.extend_from_slice(other_builder);
             }
+            (ColumnBuilder::Int32(builder), ColumnBuilder::Int32(other_builder)) => {
+                builder.extend_from_slice(other_builder);
+            }
+            (ColumnBuilder::Int64(builder), ColumnBuilder::Int64(other_builder)) => {
+                builder.extend_from_slice(other_builder);
+            }
+            (ColumnBuilder::Date(builder), ColumnBuilder::Date(other_builder)) => {
+                builder.extend_from_slice(other_builder);
+            }
+            (ColumnBuilder::Timestamp(builder), ColumnBuilder::Timestamp(other_builder)) => {
+                builder.extend_from_slice(other_builder);
+            }
+            (ColumnBuilder::Interval(builder), ColumnBuilder::Interval(other_builder)) => {
+                builder.extend_from_slice(other_builder);
+            }
+            (ColumnBuilder::Float32(builder), ColumnBuilder::Float32(other_builder)) => {
+                builder.extend_from_slice(other_builder);
+            }
+            (ColumnBuilder::Float64(builder), ColumnBuilder::Float64(other_builder)) => {
+                builder.extend_from_slice(other_builder);
+            }
+            (
+                ColumnBuilder::Decimal { values, .. },
+                ColumnBuilder::Decimal {
+                    values: other_values,
+                    ..
+                },
+            ) => {
+                values.extend_from_slice(other_values);
+            }
             (ColumnBuilder::UInt8(builder), ColumnBuilder::UInt8(other_builder)) => {
                 builder.extend_from_slice(other_builder);
             }
             (ColumnBuilder::UInt16(builder), ColumnBuilder::UInt16(other_builder)) => {
                 builder.extend_from_slice(other_builder);
             }
+            (ColumnBuilder::UInt32(builder), ColumnBuilder::UInt32(other_builder)) => {
+                builder.extend_from_slice(other_builder);
+            }
+            (ColumnBuilder::UInt64(builder), ColumnBuilder::UInt64(other_builder)) => {
+                builder.extend_from_slice(other_builder);
+            }
             (ColumnBuilder::Boolean(builder), ColumnBuilder::Boolean(other_builder)) => {
                 append_bitmap(builder, other_builder);
             }
@@ -518,6 +1003,20 @@ impl ColumnBuilder {
                     data: other_data,
                     offsets: other_offsets,
                 },
+            )
+            | (
+                ColumnBuilder::Binary { data, offsets },
+                ColumnBuilder::Binary {
+                    data: other_data,
+                    offsets: other_offsets,
+                },
+            )
+            | (
+                ColumnBuilder::Variant { data, offsets },
+                ColumnBuilder::Variant {
+                    data: other_data,
+                    offsets: other_offsets,
+                },
             ) => {
                 data.extend_from_slice(other_data);
                 let start = offsets.last().cloned().unwrap();
@@ -567,13 +1066,39 @@ impl ColumnBuilder {
             ColumnBuilder::EmptyArray { len } => Column::EmptyArray { len },
             ColumnBuilder::Int8(builder) => Column::Int8(builder.into()),
             ColumnBuilder::Int16(builder) => Column::Int16(builder.into()),
+            ColumnBuilder::Int32(builder) => Column::Int32(builder.into()),
+            ColumnBuilder::Int64(builder) => Column::Int64(builder.into()),
+            ColumnBuilder::Date(builder) => Column::Date(builder.into()),
+            ColumnBuilder::Timestamp(builder) => Column::Timestamp(builder.into()),
+            ColumnBuilder::Interval(builder) => Column::Interval(builder.into()),
+            ColumnBuilder::Float32(builder) => Column::Float32(builder.into()),
+            ColumnBuilder::Float64(builder) => Column::Float64(builder.into()),
+            ColumnBuilder::Decimal {
+                values,
+                precision,
+                scale,
+            } => Column::Decimal {
+                values: values.into(),
+                precision,
+                scale,
+            },
             ColumnBuilder::UInt8(builder) => Column::UInt8(builder.into()),
             ColumnBuilder::UInt16(builder) => Column::UInt16(builder.into()),
+            ColumnBuilder::UInt32(builder) => Column::UInt32(builder.into()),
+            ColumnBuilder::UInt64(builder) => Column::UInt64(builder.into()),
             ColumnBuilder::Boolean(builder) => Column::Boolean(builder.into()),
             ColumnBuilder::String { data, offsets } => Column::String {
                 data: data.into(),
                 offsets,
             },
+            ColumnBuilder::Binary { data, offsets } => Column::Binary {
+                data: data.into(),
+                offsets,
+            },
+            ColumnBuilder::Variant { data, offsets } => Column::Variant {
+                data: data.into(),
+                offsets,
+            },
             ColumnBuilder::Array { array, offsets } => Column::Array {
                 array: Box::new(array.build()),
                 offsets,
@@ -607,6 +1132,46 @@ impl ColumnBuilder {
                 assert_eq!(builder.len(), 1);
                 Scalar::Int16(builder[0])
             }
+            ColumnBuilder::Int32(builder) => {
+                assert_eq!(builder.len(), 1);
+                Scalar::Int32(builder[0])
+            }
+            ColumnBuilder::Int64(builder) => {
+                assert_eq!(builder.len(), 1);
+                Scalar::Int64(builder[0])
+            }
+            ColumnBuilder::Date(builder) => {
+                assert_eq!(builder.len(), 1);
+                Scalar::Date(builder[0])
+            }
+            ColumnBuilder::Timestamp(builder) => {
+                assert_eq!(builder.len(), 1);
+                Scalar::Timestamp(builder[0])
+            }
+            ColumnBuilder::Interval(builder) => {
+                assert_eq!(builder.len(), 1);
+                Scalar::Interval(builder[0])
+            }
+            ColumnBuilder::Float32(builder) => {
+                assert_eq!(builder.len(), 1);
+                Scalar::Float32(F32(builder[0]))
+            }
+            ColumnBuilder::Float64(builder) => {
+                assert_eq!(builder.len(), 1);
+                Scalar::Float64(F64(builder[0]))
+            }
+            ColumnBuilder::Decimal {
+                values,
+                precision,
+                scale,
+            } => {
+                assert_eq!(values.len(), 1);
+                Scalar::Decimal {
+                    value: values[0],
+                    precision,
+                    scale,
+                }
+            }
             ColumnBuilder::UInt8(builder) => {
                 assert_eq!(builder.len(), 1);
                 Scalar::UInt8(builder[0])
@@ -615,14 +1180,32 @@ impl ColumnBuilder {
                 assert_eq!(builder.len(), 1);
                 Scalar::UInt16(builder[0])
             }
+            ColumnBuilder::UInt32(builder) => {
+                assert_eq!(builder.len(), 1);
+                Scalar::UInt32(builder[0])
+            }
+            ColumnBuilder::UInt64(builder) => {
+                assert_eq!(builder.len(), 1);
+                Scalar::UInt64(builder[0])
+            }
             ColumnBuilder::Boolean(builder) => {
                 assert_eq!(builder.len(), 1);
                 Scalar::Boolean(builder.get(0))
             }
             ColumnBuilder::String { data, offsets } => {
-                assert_eq!(data.len(), 1);
                 assert_eq!(offsets.len(), 2);
-                Scalar::String(data[offsets[0]..offsets[1]].to_vec())
+                Scalar::String(
+                    String::from_utf8(data[offsets[0]..offsets[1]].to_vec())
+                        .expect("String column must be valid UTF-8"),
+                )
+            }
+            ColumnBuilder::Binary { data, offsets } => {
+                assert_eq!(offsets.len(), 2);
+                Scalar::Binary(data[offsets[0]..offsets[1]].to_vec())
+            }
+            ColumnBuilder::Variant { data, offsets } => {
+                assert_eq!(offsets.len(), 2);
+                Scalar::Variant(data[offsets[0]..offsets[1]].to_vec())
             }
             ColumnBuilder::Array { array, offsets } => {
                 assert_eq!(array.len(), 1);
@@ -651,6 +1234,16 @@ impl ColumnBuilder {
     }
 }
 
+fn repeat_bytes(bytes: &[u8], n: usize) -> (Vec<u8>, Vec<usize>) {
+    let len = bytes.len();
+    let mut data = Vec::with_capacity(len * n);
+    for _ in 0..n {
+        data.extend_from_slice(bytes);
+    }
+    let offsets = once(0).chain((0..n).map(|i| len * (i + 1))).collect();
+    (data, offsets)
+}
+
 pub struct ColumnIterator<'a> {
     column: &'a Column,
     index: usize,
@@ -0,0 +1,574 @@
+use arrow2::{bitmap::Bitmap, buffer::Buffer, types::NativeType};
+
+use crate::{
+    types::{decimal::MAX_DECIMAL_PRECISION, variant, F32, F64},
+    values::{Column, Scalar},
+};
+
+/// The version of the encoding, written as the first byte. A decoder rejects
+/// the payloads of the other versions.
+pub const WIRE_VERSION: u8 = 1;
+
+/// Bounds the recursion of the decoder on a corrupted payload. The columns
+/// built by the type checker are nested far less deeply.
+const MAX_DEPTH: usize = 64;
+
+/// The most rows that a decoded column without data per row may have: `Null`,
+/// `EmptyArray`, `Const` and a tuple without fields. The other columns are
+/// bounded by the size of the payload.
+pub const MAX_ROWS: usize = 1 << 24;
+
+/// Encodes a column to send it to another node.
+///
+/// The payload starts with `WIRE_VERSION`, followed by the column. A column is
+/// a tag byte followed by its fields. Lengths are LEB128 varints, and numbers
+/// are big-endian. Strings, binaries, variants and arrays are written as the
+/// length of each row followed by the data of the rows, so that a sliced
+/// column only sends the rows it has. A bitmap is its length followed by the
+/// bits, least significant bit first.
+pub fn encode_column(col: &Column) -> Vec<u8> {
+    let mut buf = vec![WIRE_VERSION];
+    write_column(col, &mut buf);
+    buf
+}
+
+/// Decodes a column written by `encode_column`. A payload of another version,
+/// with lengths and offsets that do not fit together, or with more than
+/// `MAX_ROWS` rows in a column without data per row, is an error.
+pub fn decode_column(bytes: &[u8]) -> Result<Column, String> {
+    Reader::new(bytes)?.finish(Reader::column)
+}
+
+/// Encodes a scalar in the same way as `encode_column`.
+pub fn encode_scalar(scalar: &Scalar) -> Vec<u8> {
+    let mut buf = vec![WIRE_VERSION];
+    write_scalar(scalar, &mut buf);
+    buf
+}
+
+pub fn decode_scalar(bytes: &[u8]) -> Result<Scalar, String> {
+    Reader::new(bytes)?.finish(Reader::scalar)
+}
+
+/// Checks the length of a column without data per row, see `MAX_ROWS`.
+fn check_rows(rows: usize) -> Result<usize, String> {
+    if rows > MAX_ROWS {
+        return Err(format!(
+            "column of {rows} rows exceeds the maximum of {MAX_ROWS}"
+        ));
+    }
+    Ok(rows)
+}
+
+const TAG_NULL: u8 = 0;
+const TAG_EMPTY_ARRAY: u8 = 1;
+const TAG_INT8: u8 = 2;
+const TAG_INT16: u8 = 3;
+const TAG_INT32: u8 = 4;
+const TAG_INT64: u8 = 5;
+const TAG_DATE: u8 = 6;
+const TAG_TIMESTAMP: u8 = 7;
+const TAG_INTERVAL: u8 = 8;
+const TAG_FLOAT32: u8 = 9;
+const TAG_FLOAT64: u8 = 10;
+const TAG_DECIMAL: u8 = 11;
+const TAG_UINT8: u8 = 12;
+const TAG_UINT16: u8 = 13;
+const TAG_UINT32: u8 = 14;
+const TAG_UINT64: u8 = 15;
+const TAG_BOOLEAN: u8 = 16;
+const TAG_STRING: u8 = 17;
+const TAG_BINARY: u8 = 18;
+const TAG_VARIANT: u8 = 19;
+const TAG_ARRAY: u8 = 20;
+const TAG_TUPLE: u8 = 21;
+/// Only used for columns.
+const TAG_NULLABLE: u8 = 22;
+const TAG_DICTIONARY: u8 = 23;
+const TAG_CONST: u8 = 24;
+
+fn write_column(col: &Column, buf: &mut Vec<u8>) {
+    match col {
+        Column::Null { len } => {
+            buf.push(TAG_NULL);
+            write_len(*len, buf);
+        }
+        Column::EmptyArray { len } => {
+            buf.push(TAG_EMPTY_ARRAY);
+            write_len(*len, buf);
+        }
+        Column::Int8(values) => write_buffer(TAG_INT8, values, buf),
+        Column::Int16(values) => write_buffer(TAG_INT16, values, buf),
+        Column::Int32(values) => write_buffer(TAG_INT32, values, buf),
+        Column::Int64(values) => write_buffer(TAG_INT64, values, buf),
+        Column::Date(values) => write_buffer(TAG_DATE, values, buf),
+        Column::Timestamp(values) => write_buffer(TAG_TIMESTAMP, values, buf),
+        Column::Interval(values) => write_buffer(TAG_INTERVAL, values, buf),
+        Column::Float32(values) => write_buffer(TAG_FLOAT32, values, buf),
+        Column::Float64(values) => write_buffer(TAG_FLOAT64, values, buf),
+        Column::Decimal {
+            values,
+            precision,
+            scale,
+        } => {
+            write_buffer(TAG_DECIMAL, values, buf);
+            buf.extend_from_slice(&[*precision, *scale]);
+        }
+        Column::UInt8(values) => write_buffer(TAG_UINT8, values, buf),
+        Column::UInt16(values) => write_buffer(TAG_UINT16, values, buf),
+        Column::UInt32(values) => write_buffer(TAG_UINT32, values, buf),
+        Column::UInt64(values) => write_buffer(TAG_UINT64, values, buf),
+        Column::Boolean(bitmap) => {
+            buf.push(TAG_BOOLEAN);
+            write_bitmap(bitmap, buf);
+        }
+        Column::String { data, offsets } => write_bytes(TAG_STRING, data, offsets, buf),
+        Column::Binary { data, offsets } => write_bytes(TAG_BINARY, data, offsets, buf),
+        Column::Variant { data, offsets } => write_bytes(TAG_VARIANT, data, offsets, buf),
+        Column::Array { array, offsets } => {
+            buf.push(TAG_ARRAY);
+            write_lengths(offsets, buf);
+            write_column(&array.slice(offsets[0]..offsets[offsets.len() - 1]), buf);
+        }
+        Column::Nullable { column, validity } => {
+            buf.push(TAG_NULLABLE);
+            write_column(column, buf);
+            write_bitmap(validity, buf);
+        }
+        Column::Tuple { fields, len } => {
+            buf.push(TAG_TUPLE);
+            write_len(*len, buf);
+            write_len(fields.len(), buf);
+            for field in fields {
+                write_column(field, buf);
+            }
+        }
+        Column::Dictionary { keys, values } => {
+            buf.push(TAG_DICTIONARY);
+            write_len(keys.len(), buf);
+            for key in keys.iter() {
+                buf.extend_from_slice(&key.to_be_bytes());
+            }
+            write_column(values, buf);
+        }
+        Column::Const { scalar, len } => {
+            buf.push(TAG_CONST);
+            write_len(*len, buf);
+            write_scalar(scalar, buf);
+        }
+    }
+}
+
+fn write_scalar(scalar: &Scalar, buf: &mut Vec<u8>) {
+    fn write_value<T: NativeType>(tag: u8, value: T, buf: &mut Vec<u8>) {
+        buf.push(tag);
+        buf.extend_from_slice(value.to_be_bytes().as_ref());
+    }
+
+    fn write_value_bytes(tag: u8, value: &[u8], buf: &mut Vec<u8>) {
+        buf.push(tag);
+        write_len(value.len(), buf);
+        buf.extend_from_slice(value);
+    }
+
+    match scalar {
+        Scalar::Null => buf.push(TAG_NULL),
+        Scalar::EmptyArray => buf.push(TAG_EMPTY_ARRAY),
+        Scalar::Int8(value) => write_value(TAG_INT8, *value, buf),
+        Scalar::Int16(value) => write_value(TAG_INT16, *value, buf),
+        Scalar::Int32(value) => write_value(TAG_INT32, *value, buf),
+        Scalar::Int64(value) => write_value(TAG_INT64, *value, buf),
+        Scalar::Date(value) => write_value(TAG_DATE, *value, buf),
+        Scalar::Timestamp(value) => write_value(TAG_TIMESTAMP, *value, buf),
+        Scalar::Interval(value) => write_value(TAG_INTERVAL, *value, buf),
+        Scalar::Float32(value) => write_value(TAG_FLOAT32, value.0, buf),
+        Scalar::Float64(value) => write_value(TAG_FLOAT64, value.0, buf),
+        Scalar::Decimal {
+            value,
+            precision,
+            scale,
+        } => {
+            write_value(TAG_DECIMAL, *value, buf);
+            buf.extend_from_slice(&[*precision, *scale]);
+        }
+        Scalar::UInt8(value) => write_value(TAG_UINT8, *value, buf),
+        Scalar::UInt16(value) => write_value(TAG_UINT16, *value, buf),
+        Scalar::UInt32(value) => write_value(TAG_UINT32, *value, buf),
+        Scalar::UInt64(value) => write_value(TAG_UINT64, *value, buf),
+        Scalar::Boolean(value) => buf.extend_from_slice(&[TAG_BOOLEAN, *value as u8]),
+        Scalar::String(value) => write_value_bytes(TAG_STRING, value.as_bytes(), buf),
+        Scalar::Binary(value) => write_value_bytes(TAG_BINARY, value, buf),
+        Scalar::Variant(value) => write_value_bytes(TAG_VARIANT, value, buf),
+        Scalar::Array(col) => {
+            buf.push(TAG_ARRAY);
+            write_column(col, buf);
+        }
+        Scalar::Tuple(fields) => {
+            buf.push(TAG_TUPLE);
+            write_len(fields.len(), buf);
+            for field in fields {
+                write_scalar(field, buf);
+            }
+        }
+    }
+}
+
+fn write_len(mut len: usize, buf: &mut Vec<u8>) {
+    while len >= 0x80 {
+        buf.push(len as u8 | 0x80);
+        len >>= 7;
+    }
+    buf.push(len as u8);
+}
+
+fn write_buffer<T: NativeType>(tag: u8, values: &Buffer<T>, buf: &mut Vec<u8>) {
+    buf.push(tag);
+    write_len(values.len(), buf);
+    for value in values.iter() {
+        buf.extend_from_slice(value.to_be_bytes().as_ref());
+    }
+}
+
+fn write_bitmap(bitmap: &Bitmap, buf: &mut Vec<u8>) {
+    write_len(bitmap.len(), buf);
+    let mut byte = 0;
+    for (i, bit) in bitmap.iter().enumerate() {
+        byte |= (bit as u8) << (i % 8);
+        if i % 8 == 7 {
+            buf.push(byte);
+            byte = 0;
+        }
+    }
+    if !bitmap.len().is_multiple_of(8) {
+        buf.push(byte);
+    }
+}
+
+/// Writes the number of rows and the length of each row.
+fn write_lengths(offsets: &[usize], buf: &mut Vec<u8>) {
+    write_len(offsets.len() - 1, buf);
+    for window in offsets.windows(2) {
+        write_len(window[1] - window[0], buf);
+    }
+}
+
+fn write_bytes(tag: u8, data: &[u8], offsets: &[usize], buf: &mut Vec<u8>) {
+    buf.push(tag);
+    write_lengths(offsets, buf);
+    buf.extend_from_slice(&data[offsets[0]..offsets[offsets.len() - 1]]);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    depth: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Result<Self, String> {
+        let mut reader = Reader { bytes, depth: 0 };
+        match reader.u8()? {
+            WIRE_VERSION => Ok(reader),
+            version => Err(format!("unsupported wire format version {version}")),
+        }
+    }
+
+    /// Reads the whole payload with `read`.
+    fn finish<T>(mut self, read: impl FnOnce(&mut Self) -> Result<T, String>) -> Result<T, String> {
+        let value = read(&mut self)?;
+        if self.bytes.is_empty() {
+            Ok(value)
+        } else {
+            Err(format!("{} unexpected bytes at the end", self.bytes.len()))
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if len > self.bytes.len() {
+            return Err("unexpected end of the payload".to_string());
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn value<T: NativeType>(&mut self) -> Result<T, String> {
+        let bytes = self.take(std::mem::size_of::<T>())?;
+        Ok(T::from_be_bytes(bytes.try_into().ok().unwrap()))
+    }
+
+    fn len(&mut self) -> Result<usize, String> {
+        let mut len = 0usize;
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.u8()?;
+            let bits = (byte & 0x7f) as usize;
+            if bits << shift >> shift != bits {
+                break;
+            }
+            len |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(len);
+            }
+        }
+        Err("length is too large".to_string())
+    }
+
+    /// Reads a number of items that take at least one byte each, which bounds
+    /// the memory allocated for them by the size of the payload.
+    fn count(&mut self) -> Result<usize, String> {
+        let count = self.len()?;
+        if count > self.bytes.len() {
+            return Err(format!(
+                "{count} items do not fit into the {} remaining bytes",
+                self.bytes.len()
+            ));
+        }
+        Ok(count)
+    }
+
+    fn buffer<T: NativeType>(&mut self) -> Result<Buffer<T>, String> {
+        let len = self.len()?;
+        let size = len
+            .checked_mul(std::mem::size_of::<T>())
+            .ok_or("length is too large")?;
+        let bytes = self.take(size)?;
+        Ok(bytes
+            .chunks_exact(std::mem::size_of::<T>())
+            .map(|chunk| T::from_be_bytes(chunk.try_into().ok().unwrap()))
+            .collect())
+    }
+
+    fn bitmap(&mut self) -> Result<Bitmap, String> {
+        let len = self.len()?;
+        let bytes = self.take(len.div_ceil(8))?;
+        Bitmap::try_new(bytes.to_vec(), len).map_err(|err| err.to_string())
+    }
+
+    /// Reads the lengths of the rows into offsets starting at zero.
+    fn offsets(&mut self) -> Result<Vec<usize>, String> {
+        let len = self.count()?;
+        let mut offsets = Vec::with_capacity(len + 1);
+        offsets.push(0);
+        let mut end = 0usize;
+        for _ in 0..len {
+            end = end.checked_add(self.len()?).ok_or("length is too large")?;
+            offsets.push(end);
+        }
+        Ok(offsets)
+    }
+
+    fn bytes(&mut self) -> Result<(Buffer<u8>, Vec<usize>), String> {
+        let offsets = self.offsets()?;
+        let data = self.take(offsets[offsets.len() - 1])?;
+        Ok((data.to_vec().into(), offsets))
+    }
+
+    fn decimal_type(&mut self) -> Result<(u8, u8), String> {
+        let (precision, scale) = (self.u8()?, self.u8()?);
+        if precision == 0 || precision > MAX_DECIMAL_PRECISION || scale > precision {
+            return Err(format!(
+                "invalid decimal type Decimal({precision}, {scale})"
+            ));
+        }
+        Ok((precision, scale))
+    }
+
+    fn nested<T>(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> Result<T, String>,
+    ) -> Result<T, String> {
+        if self.depth == MAX_DEPTH {
+            return Err("payload is nested too deeply".to_string());
+        }
+        self.depth += 1;
+        let value = read(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn column(&mut self) -> Result<Column, String> {
+        self.nested(|reader| {
+            Ok(match reader.u8()? {
+                TAG_NULL => Column::Null {
+                    len: check_rows(reader.len()?)?,
+                },
+                TAG_EMPTY_ARRAY => Column::EmptyArray {
+                    len: check_rows(reader.len()?)?,
+                },
+                TAG_INT8 => Column::Int8(reader.buffer()?),
+                TAG_INT16 => Column::Int16(reader.buffer()?),
+                TAG_INT32 => Column::Int32(reader.buffer()?),
+                TAG_INT64 => Column::Int64(reader.buffer()?),
+                TAG_DATE => Column::Date(reader.buffer()?),
+                TAG_TIMESTAMP => Column::Timestamp(reader.buffer()?),
+                TAG_INTERVAL => Column::Interval(reader.buffer()?),
+                TAG_FLOAT32 => Column::Float32(reader.buffer()?),
+                TAG_FLOAT64 => Column::Float64(reader.buffer()?),
+                TAG_DECIMAL => {
+                    let values = reader.buffer()?;
+                    let (precision, scale) = reader.decimal_type()?;
+                    Column::Decimal {
+                        values,
+                        precision,
+                        scale,
+                    }
+                }
+                TAG_UINT8 => Column::UInt8(reader.buffer()?),
+                TAG_UINT16 => Column::UInt16(reader.buffer()?),
+                TAG_UINT32 => Column::UInt32(reader.buffer()?),
+                TAG_UINT64 => Column::UInt64(reader.buffer()?),
+                TAG_BOOLEAN => Column::Boolean(reader.bitmap()?),
+                TAG_STRING => {
+                    let (data, offsets) = reader.bytes()?;
+                    for window in offsets.windows(2) {
+                        std::str::from_utf8(&data[window[0]..window[1]])
+                            .map_err(|err| format!("invalid string: {err}"))?;
+                    }
+                    Column::String { data, offsets }
+                }
+                TAG_BINARY => {
+                    let (data, offsets) = reader.bytes()?;
+                    Column::Binary { data, offsets }
+                }
+                TAG_VARIANT => {
+                    let (data, offsets) = reader.bytes()?;
+                    for window in offsets.windows(2) {
+                        variant::validate(&data[window[0]..window[1]])?;
+                    }
+                    Column::Variant { data, offsets }
+                }
+                TAG_ARRAY => {
+                    let offsets = reader.offsets()?;
+                    let array = reader.column()?;
+                    if array.len() != offsets[offsets.len() - 1] {
+                        return Err(format!(
+                            "array items have {} rows, but the offsets end at {}",
+                            array.len(),
+                            offsets[offsets.len() - 1]
+                        ));
+                    }
+                    Column::Array {
+                        array: Box::new(array),
+                        offsets,
+                    }
+                }
+                TAG_NULLABLE => {
+                    let column = reader.column()?;
+                    let validity = reader.bitmap()?;
+                    if validity.len() != column.len() {
+                        return Err(format!(
+                            "validity has {} rows, but the column has {}",
+                            validity.len(),
+                            column.len()
+                        ));
+                    }
+                    Column::Nullable {
+                        column: Box::new(column),
+                        validity,
+                    }
+                }
+                TAG_TUPLE => {
+                    let len = reader.len()?;
+                    let fields = (0..reader.count()?)
+                        .map(|_| reader.column())
+                        .collect::<Result<Vec<_>, _>>()?;
+                    if fields.is_empty() {
+                        check_rows(len)?;
+                    }
+                    if let Some(field) = fields.iter().find(|field| field.len() != len) {
+                        return Err(format!(
+                            "tuple field has {} rows, but the tuple has {len}",
+                            field.len()
+                        ));
+                    }
+                    Column::Tuple { fields, len }
+                }
+                TAG_DICTIONARY => {
+                    let keys = reader.buffer::<u32>()?;
+                    let values = reader.column()?;
+                    if let Some(key) = keys.iter().find(|key| **key as usize >= values.len()) {
+                        return Err(format!(
+                            "dictionary key {key} is out of bounds for {} values",
+                            values.len()
+                        ));
+                    }
+                    Column::Dictionary {
+                        keys,
+                        values: Box::new(values),
+                    }
+                }
+                TAG_CONST => {
+                    let len = check_rows(reader.len()?)?;
+                    Column::Const {
+                        scalar: Box::new(reader.scalar()?),
+                        len,
+                    }
+                }
+                tag => return Err(format!("invalid column tag {tag}")),
+            })
+        })
+    }
+
+    fn scalar(&mut self) -> Result<Scalar, String> {
+        self.nested(|reader| {
+            Ok(match reader.u8()? {
+                TAG_NULL => Scalar::Null,
+                TAG_EMPTY_ARRAY => Scalar::EmptyArray,
+                TAG_INT8 => Scalar::Int8(reader.value()?),
+                TAG_INT16 => Scalar::Int16(reader.value()?),
+                TAG_INT32 => Scalar::Int32(reader.value()?),
+                TAG_INT64 => Scalar::Int64(reader.value()?),
+                TAG_DATE => Scalar::Date(reader.value()?),
+                TAG_TIMESTAMP => Scalar::Timestamp(reader.value()?),
+                TAG_INTERVAL => Scalar::Interval(reader.value()?),
+                TAG_FLOAT32 => Scalar::Float32(F32(reader.value()?)),
+                TAG_FLOAT64 => Scalar::Float64(F64(reader.value()?)),
+                TAG_DECIMAL => {
+                    let value = reader.value()?;
+                    let (precision, scale) = reader.decimal_type()?;
+                    Scalar::Decimal {
+                        value,
+                        precision,
+                        scale,
+                    }
+                }
+                TAG_UINT8 => Scalar::UInt8(reader.value()?),
+                TAG_UINT16 => Scalar::UInt16(reader.value()?),
+                TAG_UINT32 => Scalar::UInt32(reader.value()?),
+                TAG_UINT64 => Scalar::UInt64(reader.value()?),
+                TAG_BOOLEAN => match reader.u8()? {
+                    0 => Scalar::Boolean(false),
+                    1 => Scalar::Boolean(true),
+                    byte => return Err(format!("invalid boolean {byte}")),
+                },
+                TAG_STRING => {
+                    let len = reader.len()?;
+                    let bytes = reader.take(len)?.to_vec();
+                    Scalar::String(
+                        String::from_utf8(bytes).map_err(|err| format!("invalid string: {err}"))?,
+                    )
+                }
+                TAG_BINARY => {
+                    let len = reader.len()?;
+                    Scalar::Binary(reader.take(len)?.to_vec())
+                }
+                TAG_VARIANT => {
+                    let len = reader.len()?;
+                    let bytes = reader.take(len)?;
+                    variant::validate(bytes)?;
+                    Scalar::Variant(bytes.to_vec())
+                }
+                TAG_ARRAY => Scalar::Array(reader.column()?),
+                TAG_TUPLE => Scalar::Tuple(
+                    (0..reader.count()?)
+                        .map(|_| reader.scalar())
+                        .collect::<Result<_, _>>()?,
+                ),
+                tag => return Err(format!("invalid scalar tag {tag}")),
+            })
+        })
+    }
+}